@@ -0,0 +1,5 @@
+alter table session
+    add column created   timestamptz not null default now(),
+    add column last_seen timestamptz not null default now();
+
+create index session_expiration_idx on session (expiration);
@@ -0,0 +1,17 @@
+create type api_token_scope as enum ('read_only', 'read_write');
+
+create table api_token
+(
+    id         uuid primary key,
+    user_id    uuid            not null
+        constraint api_token_user_fk
+            references "user" (id) on delete cascade,
+    name       text            not null,
+    -- sha256 of the token, the token itself is only shown once on creation
+    token_hash text            not null unique,
+    scope      api_token_scope not null,
+    -- null means the token does not expire
+    expiration timestamptz,
+    last_used  timestamptz,
+    created    timestamptz     not null
+);
@@ -0,0 +1,19 @@
+-- Set when an admin impersonates another user, refers to the admin
+alter table session
+    add column impersonated_by uuid
+        constraint session_impersonated_by_fk
+            references "user" (id) on delete cascade;
+
+create table audit_log
+(
+    id       uuid primary key,
+    -- the person that actually performed the action
+    actor_id uuid        references "user" (id) on delete set null,
+    -- the user the action was performed as, differs from the actor when impersonating
+    user_id  uuid        references "user" (id) on delete set null,
+    method   text        not null,
+    path     text        not null,
+    created  timestamptz not null
+);
+
+create index audit_log_actor_idx on audit_log (actor_id);
@@ -0,0 +1,26 @@
+create type event_status as enum ('draft', 'published', 'cancelled', 'archived');
+
+alter table event
+    add column status              event_status not null default 'published',
+    -- drafts with a publish date are published automatically once it has passed
+    add column publish_at          timestamptz,
+    add column cancellation_reason text;
+
+update event
+set status = case when is_published then 'published'::event_status else 'draft'::event_status end;
+
+alter table event
+    drop column is_published;
+
+create table notification
+(
+    id         uuid primary key,
+    user_id    uuid        not null references "user" (id) on delete cascade,
+    event_id   uuid references event (id) on delete cascade,
+    message_nl text        not null,
+    message_en text        not null,
+    read       boolean     not null default false,
+    created    timestamptz not null
+);
+
+create index notification_user_idx on notification (user_id);
@@ -0,0 +1,10 @@
+create table event_template
+(
+    id           uuid primary key,
+    committee_id uuid        not null references committee (id) on delete cascade,
+    name         text        not null,
+    content      jsonb       not null,
+    created      timestamptz not null default now(),
+    updated      timestamptz not null default now(),
+    unique (committee_id, name)
+);
@@ -0,0 +1,18 @@
+create type recurrence_frequency as enum ('weekly', 'biweekly');
+
+create table event_series
+(
+    id         uuid primary key,
+    frequency  recurrence_frequency not null,
+    until      date                 not null,
+    exceptions date[]               not null default '{}',
+    content    jsonb                not null,
+    created_by uuid                 not null references committee (id) on delete cascade,
+    created    timestamptz          not null default now(),
+    updated    timestamptz          not null default now()
+);
+
+alter table event
+    add column series_id uuid references event_series (id) on delete set null;
+
+create index event_series_id_idx on event (series_id);
@@ -0,0 +1,2 @@
+alter table event
+    add column membership_rules jsonb not null default '[]';
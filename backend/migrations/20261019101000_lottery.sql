@@ -0,0 +1,25 @@
+alter table event
+    add column lottery boolean not null default false;
+
+create type lottery_outcome as enum ('placed', 'waiting_list', 'rejected');
+
+create table lottery_draw
+(
+    event_id uuid primary key references event (id) on delete cascade,
+    seed     text        not null,
+    drawn    timestamptz not null default now()
+);
+
+create table lottery_result
+(
+    event_id        uuid            not null references lottery_draw (event_id) on delete cascade,
+    registration_id uuid            not null,
+    user_id         uuid references "user" (id) on delete set null,
+    weight          float8          not null,
+    ticket          float8          not null,
+    rank            int             not null,
+    outcome         lottery_outcome not null,
+    primary key (event_id, registration_id)
+);
+
+create index lottery_result_user_id_idx on lottery_result (user_id);
@@ -0,0 +1,18 @@
+alter table event
+    add column cancellation_deadline timestamptz;
+
+alter table event_registration
+    add column cancellation_requested timestamptz;
+
+create table late_cancellation
+(
+    id                     uuid primary key,
+    event_id               uuid        not null references event (id) on delete cascade,
+    registration_id        uuid        not null,
+    user_id                uuid references "user" (id) on delete set null,
+    cancellation_requested timestamptz not null,
+    approved               timestamptz not null default now(),
+    approved_by            uuid references "user" (id) on delete set null
+);
+
+create index late_cancellation_event_id_idx on late_cancellation (event_id);
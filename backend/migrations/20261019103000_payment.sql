@@ -0,0 +1,9 @@
+alter table event
+    add column pricing jsonb not null default '{}';
+
+create type payment_status as enum ('unpaid', 'paid', 'refunded');
+
+alter table event_registration
+    add column amount_due      int            not null default 0,
+    add column payment_status  payment_status not null default 'unpaid',
+    add column payment_updated timestamptz;
@@ -0,0 +1,18 @@
+create type transaction_status as enum ('open', 'paid', 'failed', 'expired', 'canceled', 'refunded');
+
+create table payment_transaction
+(
+    id              uuid primary key,
+    -- id of the payment at the payment service provider
+    provider_id     text               not null unique,
+    registration_id uuid references event_registration (registration_id) on delete set null,
+    user_id         uuid references "user" (id) on delete set null,
+    amount          int                not null,
+    status          transaction_status not null default 'open',
+    checkout_url    text,
+    refund_id       text,
+    created         timestamptz        not null default now(),
+    updated         timestamptz        not null default now()
+);
+
+create index payment_transaction_registration_id_idx on payment_transaction (registration_id);
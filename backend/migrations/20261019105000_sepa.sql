@@ -0,0 +1,50 @@
+create table sepa_mandate
+(
+    user_id        uuid primary key references "user" (id) on delete cascade,
+    account_holder text        not null,
+    iban           text        not null,
+    mandate_id     text        not null unique,
+    signed         date        not null,
+    created        timestamptz not null,
+    updated        timestamptz not null
+);
+
+create table membership_fee
+(
+    year       int        not null,
+    membership membership not null,
+    amount     int        not null,
+    primary key (year, membership)
+);
+
+create type collection_status as enum ('pending', 'collected', 'failed');
+create type sequence_type as enum ('first', 'recurring');
+
+create table collection_batch
+(
+    id              uuid primary key,
+    year            int         not null,
+    collection_date date        not null,
+    created_by      uuid references "user" (id) on delete set null,
+    created         timestamptz not null default now()
+);
+
+-- The mandate is copied, so the batch can be exported again after the mandate changed
+create table fee_collection
+(
+    id             uuid primary key,
+    batch_id       uuid              not null references collection_batch (id) on delete cascade,
+    user_id        uuid references "user" (id) on delete set null,
+    year           int               not null,
+    amount         int               not null,
+    account_holder text              not null,
+    iban           text              not null,
+    mandate_id     text              not null,
+    signed         date              not null,
+    sequence_type  sequence_type     not null,
+    status         collection_status not null default 'pending',
+    updated        timestamptz       not null default now()
+);
+
+create index fee_collection_user_id_idx on fee_collection (user_id);
+create index fee_collection_batch_id_idx on fee_collection (batch_id);
@@ -0,0 +1,2 @@
+alter table event_registration
+    add column checked_in timestamptz;
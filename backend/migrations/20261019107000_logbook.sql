@@ -0,0 +1,24 @@
+create type logbook_visibility as enum ('private', 'members', 'public');
+
+create table logbook_entry
+(
+    id          uuid primary key,
+    user_id     uuid               not null references "user" (id) on delete cascade,
+    date        date               not null,
+    area        text               not null,
+    route       text               not null,
+    grade       text,
+    description text               not null default '',
+    visibility  logbook_visibility not null default 'private',
+    created     timestamptz        not null default now(),
+    updated     timestamptz        not null default now()
+);
+
+create index logbook_entry_user_id_idx on logbook_entry (user_id);
+
+create table logbook_partner
+(
+    entry_id uuid not null references logbook_entry (id) on delete cascade,
+    user_id  uuid not null references "user" (id) on delete cascade,
+    primary key (entry_id, user_id)
+);
@@ -0,0 +1,31 @@
+create table certification
+(
+    id              uuid primary key,
+    name_nl         text        not null,
+    name_en         text        not null,
+    description_nl  text        not null default '',
+    description_en  text        not null default '',
+    -- null if the certification does not expire
+    validity_months int,
+    created         timestamptz not null default now(),
+    updated         timestamptz not null default now()
+);
+
+create table user_certification
+(
+    id               uuid primary key,
+    certification_id uuid        not null references certification (id) on delete cascade,
+    user_id          uuid        not null references "user" (id) on delete cascade,
+    -- the course the certification was earned at
+    event_id         uuid references event (id) on delete set null,
+    issued           date        not null,
+    expires          date,
+    issued_by        uuid references "user" (id) on delete set null,
+    created          timestamptz not null default now(),
+    unique (certification_id, user_id, event_id)
+);
+
+create index user_certification_user_id_idx on user_certification (user_id);
+
+alter table event
+    add column required_certifications uuid[] not null default '{}';
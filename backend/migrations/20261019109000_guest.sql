@@ -0,0 +1,8 @@
+alter table event
+    add column max_guests int not null default 0;
+
+-- anonymous registrations take one place, even without guests
+alter table event_registration
+    add column guests jsonb not null default '[]',
+    add column places int not null generated always as
+        (greatest(1, (user_id is not null)::int + jsonb_array_length(guests))) stored;
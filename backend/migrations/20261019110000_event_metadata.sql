@@ -0,0 +1,34 @@
+-- metadata is typed per activity type now, drop everything the backend does not understand
+create function typed_metadata(metadata jsonb) returns jsonb as
+$$
+select jsonb_strip_nulls(jsonb_build_object(
+        'type', (select coalesce(jsonb_agg(t), '[]')
+                 from jsonb_array_elements(case jsonb_typeof(metadata -> 'type') when 'array' then metadata -> 'type' end) t
+                 where t #>> '{}' in ('sp', 'mp', 'boulder', 'trad', 'education')),
+        'experience', (select coalesce(jsonb_agg(t), '[]')
+                       from jsonb_array_elements(case jsonb_typeof(metadata -> 'experience') when 'array' then metadata -> 'experience' end) t
+                       where t #>> '{}' in ('sp', 'mp', 'boulder', 'trad', 'education')),
+        'gear', case
+                    when jsonb_typeof(metadata -> 'gear') = 'object'
+                        then jsonb_build_object('en', coalesce(metadata -> 'gear' ->> 'en', ''),
+                                                'nl', coalesce(metadata -> 'gear' ->> 'nl', ''))
+            end,
+        -- kept for every event type until the organisers move to event_organiser,
+        -- 'nobody' was used for events without organiser
+        'worga', case
+                     when metadata ->> 'worga' ~* '^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$'
+                         then metadata -> 'worga'
+            end
+       ))
+$$ language sql immutable;
+
+update event
+set metadata = typed_metadata(metadata);
+
+update event_template
+set content = jsonb_set(content, '{metadata}', typed_metadata(content -> 'metadata'));
+
+update event_series
+set content = jsonb_set(content, '{metadata}', typed_metadata(content -> 'metadata'));
+
+drop function typed_metadata;
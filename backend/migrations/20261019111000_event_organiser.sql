@@ -0,0 +1,28 @@
+create type organiser_role as enum ('lead', 'co_organiser', 'first_aid', 'treasurer');
+
+create table event_organiser
+(
+    event_id uuid           not null references event (id) on delete cascade,
+    user_id  uuid           not null references "user" (id) on delete cascade,
+    role     organiser_role not null,
+    created  timestamptz    not null default now(),
+    primary key (event_id, user_id)
+);
+
+create index event_organiser_user_id_idx on event_organiser (user_id);
+
+-- the organiser used to be kept in the metadata
+insert into event_organiser (event_id, user_id, role)
+select e.id, u.id, 'lead'
+from event e
+         join "user" u on u.id = (e.metadata ->> 'worga')::uuid
+where e.metadata ? 'worga';
+
+update event
+set metadata = metadata - 'worga';
+
+update event_template
+set content = jsonb_set(content, '{metadata}', (content -> 'metadata') - 'worga');
+
+update event_series
+set content = jsonb_set(content, '{metadata}', (content -> 'metadata') - 'worga');
@@ -0,0 +1,7 @@
+-- seats are those left for others, besides the driver and their guests
+alter table event_registration
+    add column car_seats              int,
+    add column car_departure          text,
+    add column driver_registration_id uuid references event_registration (registration_id) on delete set null;
+
+create index event_registration_driver_idx on event_registration (driver_registration_id);
@@ -0,0 +1,31 @@
+create table feedback_form
+(
+    event_id          uuid primary key references event (id) on delete cascade,
+    questions         jsonb       not null,
+    anonymous         bool        not null,
+    shared_with_board bool        not null default false,
+    -- when attendees were last asked to respond
+    sent              timestamptz,
+    created           timestamptz not null default now(),
+    updated           timestamptz not null default now()
+);
+
+-- kept apart from the responses, so anonymous responses cannot be traced back
+create table feedback_respondent
+(
+    event_id uuid not null references feedback_form (event_id) on delete cascade,
+    user_id  uuid not null references "user" (id) on delete cascade,
+    primary key (event_id, user_id)
+);
+
+create table feedback_response
+(
+    id       uuid primary key,
+    event_id uuid        not null references feedback_form (event_id) on delete cascade,
+    -- null for anonymous forms
+    user_id  uuid references "user" (id) on delete set null,
+    answers  jsonb       not null,
+    created  timestamptz not null default now()
+);
+
+create index feedback_response_event_id_idx on feedback_response (event_id);
@@ -0,0 +1,10 @@
+-- approved late cancellations keep their registration, so the amount due stays visible
+alter table event_registration
+    add column cancelled timestamptz;
+
+-- people may register again after cancelling
+alter table event_registration
+    drop constraint user_can_register_only_once;
+
+create unique index user_can_register_only_once on event_registration (event_id, user_id)
+    where cancelled is null;
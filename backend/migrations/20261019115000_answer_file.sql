@@ -0,0 +1,3 @@
+-- files answering registration questions are only readable by the uploader and the organisers
+alter table file
+    add column is_answer boolean not null default false;
@@ -0,0 +1,62 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, is_admin_or_board},
+    api_token::{ApiToken, ApiTokenId, CreatedApiToken, NewApiToken},
+    auth::session::Session,
+    data_source::ApiTokenStore,
+    error::{AppResult, Error},
+    user::UserId,
+};
+use axum::{Json, extract::Path, http::StatusCode};
+use time::OffsetDateTime;
+
+/// Users manage their own tokens, admins and board members may list and revoke anyone's.
+/// Tokens can never be used to manage tokens.
+fn token_access(id: &UserId, session: &Session) -> AppResult<()> {
+    if session.is_api_token() {
+        return Err(Error::Unauthorized);
+    }
+    if id == session.user_id() || is_admin_or_board(session).is_ok() {
+        Ok(())
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+pub async fn get_api_tokens(
+    store: ApiTokenStore,
+    session: Session,
+    Path(id): Path<UserId>,
+) -> ApiResult<Vec<ApiToken>> {
+    token_access(&id, &session)?;
+    Ok(Json(store.get_for_user(&id).await?))
+}
+
+pub async fn create_api_token(
+    store: ApiTokenStore,
+    session: Session,
+    Path(id): Path<UserId>,
+    ValidatedJson(new): ValidatedJson<NewApiToken>,
+) -> AppResult<(StatusCode, Json<CreatedApiToken>)> {
+    session.ensure_not_impersonating()?;
+    // Not even admins may create tokens on behalf of someone else
+    if session.is_api_token() || id != *session.user_id() {
+        return Err(Error::Unauthorized);
+    }
+    if new
+        .expiration
+        .is_some_and(|expiration| expiration <= OffsetDateTime::now_utc())
+    {
+        return Err(Error::BadRequest("Expiration must be in the future"));
+    }
+
+    Ok((StatusCode::CREATED, Json(store.create(&id, new).await?)))
+}
+
+pub async fn delete_api_token(
+    store: ApiTokenStore,
+    session: Session,
+    Path((id, token_id)): Path<(UserId, ApiTokenId)>,
+) -> AppResult<()> {
+    token_access(&id, &session)?;
+    store.delete(&id, &token_id).await
+}
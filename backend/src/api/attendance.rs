@@ -0,0 +1,81 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, event::has_registration_access, is_admin_or_board},
+    attendance::{Attendance, AttendanceSummary, CheckInToken, Scan, ScanResult},
+    auth::session::Session,
+    data_source::{AttendanceStore, event::EventStore},
+    error::{AppResult, Error},
+    event::{EventId, RegistrationId},
+    event_organiser::OrganiserRole,
+};
+use axum::{Json, extract::Path};
+use tracing::info;
+
+/// The token shown as a QR code to the participant, scanned by the organisers at the meeting point
+pub async fn get_check_in_token(
+    store: AttendanceStore,
+    events: EventStore,
+    session: Session,
+    Path((event_id, registration_id)): Path<(EventId, RegistrationId)>,
+) -> ApiResult<CheckInToken> {
+    let registration = events.get_registration(&registration_id).await?;
+    if registration.event_id != event_id {
+        return Err(Error::NotFound);
+    }
+    match &registration.user {
+        Some(user) => has_registration_access(&events, &user.id, &session, Some(&event_id)).await?,
+        None => is_admin_or_board(&session)?,
+    }
+    if registration.waiting_list_position.is_some() {
+        return Err(Error::BadRequest(
+            "Registrations on the waiting list cannot check in",
+        ));
+    }
+    Ok(Json(store.check_in_token(&registration_id)))
+}
+
+async fn ensure_organiser(events: &EventStore, session: &Session, id: &EventId) -> AppResult<()> {
+    let event = events.get_event(id, true).await?;
+    events
+        .ensure_event_organiser(session, &event, OrganiserRole::ALL)
+        .await
+}
+
+/// Accepts any number of scans, so scanners that were offline can upload them in one go
+pub async fn check_in(
+    store: AttendanceStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+    ValidatedJson(scans): ValidatedJson<Vec<Scan>>,
+) -> ApiResult<Vec<ScanResult>> {
+    ensure_organiser(&events, &session, &id).await?;
+    let results = store.check_in(&id, scans).await?;
+    info!(
+        event_id = id.to_string(),
+        scans = results.len(),
+        "Processed check-in scans"
+    );
+    Ok(Json(results))
+}
+
+pub async fn update_attendance(
+    store: AttendanceStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+    ValidatedJson(attendance): ValidatedJson<Vec<Attendance>>,
+) -> ApiResult<AttendanceSummary> {
+    ensure_organiser(&events, &session, &id).await?;
+    store.set_attendance(&id, attendance).await?;
+    Ok(Json(store.get_summary(&id).await?))
+}
+
+pub async fn get_attendance(
+    store: AttendanceStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<AttendanceSummary> {
+    ensure_organiser(&events, &session, &id).await?;
+    Ok(Json(store.get_summary(&id).await?))
+}
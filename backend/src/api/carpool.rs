@@ -0,0 +1,70 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, event::has_registration_access, is_admin_or_board},
+    auth::session::Session,
+    carpool::{CarAssignment, Carpool, CarpoolCar},
+    data_source::{CarpoolStore, event::EventStore},
+    error::Error,
+    event::{EventId, RegistrationId},
+    event_organiser::OrganiserRole,
+};
+use axum::{Json, extract::Path};
+use tracing::info;
+
+pub async fn get_carpool(
+    store: CarpoolStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<Carpool> {
+    let event = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(&session, &event, OrganiserRole::ALL)
+        .await?;
+    Ok(Json(store.get_carpool(&id).await?))
+}
+
+/// Returns the carpool after the assignments, including the remaining shortfall
+pub async fn assign_passengers(
+    store: CarpoolStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+    ValidatedJson(assignments): ValidatedJson<Vec<CarAssignment>>,
+) -> ApiResult<Carpool> {
+    let event = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(&session, &event, OrganiserRole::MANAGERS)
+        .await?;
+
+    let count = assignments.len();
+    store.assign_passengers(&id, assignments).await?;
+    info!(
+        event_id = id.to_string(),
+        assignments = count,
+        "Assigned passengers to cars"
+    );
+    Ok(Json(store.get_carpool(&id).await?))
+}
+
+/// The car a participant drives or rides along in
+pub async fn get_registration_car(
+    store: CarpoolStore,
+    events: EventStore,
+    session: Session,
+    Path((event_id, registration_id)): Path<(EventId, RegistrationId)>,
+) -> ApiResult<CarpoolCar> {
+    let registration = events.get_registration(&registration_id).await?;
+    if registration.event_id != event_id {
+        return Err(Error::NotFound);
+    }
+    match &registration.user {
+        Some(user) => has_registration_access(&events, &user.id, &session, Some(&event_id)).await?,
+        None => is_admin_or_board(&session)?,
+    }
+
+    store
+        .get_car(&event_id, &registration_id)
+        .await?
+        .map(Json)
+        .ok_or(Error::NotFound)
+}
@@ -0,0 +1,127 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, is_admin_or_board},
+    auth::session::Session,
+    certification::{
+        Certification, CertificationContent, CertificationId, IssueCertification,
+        UserCertification, UserCertificationId,
+    },
+    data_source::{CertificationStore, event::EventStore},
+    error::{AppResult, Error},
+    event::{ActivityType, EventId},
+    event_organiser::OrganiserRole,
+    user::UserId,
+};
+use axum::{Json, extract::Path};
+use time::OffsetDateTime;
+use tracing::info;
+
+pub async fn get_certifications(store: CertificationStore) -> ApiResult<Vec<Certification>> {
+    Ok(Json(store.get_all().await?))
+}
+
+pub async fn create_certification(
+    store: CertificationStore,
+    session: Session,
+    ValidatedJson(content): ValidatedJson<CertificationContent>,
+) -> ApiResult<Certification> {
+    is_admin_or_board(&session)?;
+    Ok(Json(store.create(content).await?))
+}
+
+pub async fn update_certification(
+    store: CertificationStore,
+    session: Session,
+    Path(id): Path<CertificationId>,
+    ValidatedJson(content): ValidatedJson<CertificationContent>,
+) -> ApiResult<Certification> {
+    is_admin_or_board(&session)?;
+    Ok(Json(store.update(&id, content).await?))
+}
+
+pub async fn delete_certification(
+    store: CertificationStore,
+    session: Session,
+    Path(id): Path<CertificationId>,
+) -> AppResult<()> {
+    is_admin_or_board(&session)?;
+    store.delete(&id).await
+}
+
+pub async fn get_user_certifications(
+    store: CertificationStore,
+    session: Session,
+    Path(id): Path<UserId>,
+) -> ApiResult<Vec<UserCertification>> {
+    if id != *session.user_id() {
+        is_admin_or_board(&session)?;
+    }
+    Ok(Json(store.get_for_user(&id).await?))
+}
+
+/// Certifications issued by mistake are revoked by the board
+pub async fn revoke_user_certification(
+    store: CertificationStore,
+    session: Session,
+    Path((id, user_certification_id)): Path<(UserId, UserCertificationId)>,
+) -> AppResult<()> {
+    is_admin_or_board(&session)?;
+    store.revoke(&id, &user_certification_id).await
+}
+
+pub async fn get_event_certifications(
+    store: CertificationStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<Vec<UserCertification>> {
+    let event = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(&session, &event, OrganiserRole::MANAGERS)
+        .await?;
+    Ok(Json(store.get_for_event(&id).await?))
+}
+
+/// The organisers issue certifications once the course has been completed
+pub async fn issue_certification(
+    store: CertificationStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+    ValidatedJson(issue): ValidatedJson<IssueCertification>,
+) -> ApiResult<Vec<UserCertification>> {
+    let event = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(&session, &event, OrganiserRole::MANAGERS)
+        .await?;
+
+    if !matches!(event.content.event_type, ActivityType::Course) {
+        return Err(Error::BadRequest(
+            "Certifications can only be issued for courses",
+        ));
+    }
+    let completed = event
+        .content
+        .dates
+        .iter()
+        .map(|date| date.end)
+        .max()
+        .is_some_and(|end| end < OffsetDateTime::now_utc());
+    if !completed {
+        return Err(Error::BadRequest("The course has not been completed yet"));
+    }
+
+    store
+        .issue(
+            &id,
+            &issue.certification_id,
+            issue.user_ids,
+            session.user_id(),
+        )
+        .await?;
+    info!(
+        event_id = id.to_string(),
+        certification_id = issue.certification_id.to_string(),
+        "Issued certification"
+    );
+    Ok(Json(store.get_for_event(&id).await?))
+}
@@ -83,6 +83,7 @@ pub async fn delete_committee(
     session: Session,
     Path(id): Path<CommitteeId>,
 ) -> AppResult<()> {
+    session.ensure_not_impersonating()?;
     committee_access(&session, &id, &store).await?;
     store.delete(&id).await
 }
@@ -93,6 +94,7 @@ pub async fn add_user_to_committee(
     session: Session,
     Path((id, user_id)): Path<(CommitteeId, UserId)>,
 ) -> ApiResult<BasicUser> {
+    session.ensure_not_impersonating()?;
     committee_access(&session, &id, &store).await?;
     Ok(Json(store.add_user(&id, &user_id).await?))
 }
@@ -102,6 +104,7 @@ pub async fn remove_user_from_committee(
     session: Session,
     Path((id, user_id)): Path<(CommitteeId, UserId)>,
 ) -> AppResult<()> {
+    session.ensure_not_impersonating()?;
     committee_access(&session, &id, &store).await?;
     store.remove_user(&id, &user_id).await?;
     Ok(())
@@ -140,6 +143,7 @@ pub async fn make_chair(
     session: Session,
     Path((committee_id, user_id)): Path<(CommitteeId, UserId)>,
 ) -> AppResult<()> {
+    session.ensure_not_impersonating()?;
     committee_access(&session, &committee_id, &store).await?;
     store
         .ensure_user_in_committee(&user_id, &committee_id)
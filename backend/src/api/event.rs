@@ -1,26 +1,34 @@
 use crate::{
-    api::{ApiResult, ValidatedJson, conditional_json_response, is_admin_or_board},
+    api::{ApiResult, ValidatedJson, ValidatedQuery, conditional_json_response, is_admin_or_board},
     auth::{
         role::{Membership, Status},
         session::Session,
     },
-    data_source::event::EventStore,
+    data_source::{LocationStore, PaymentStore, event::EventStore},
     error::{AppResult, Error},
     event::{
-        Answer, Date, Event, EventContent, NewRegistration, Question, Registration, RegistrationId,
+        ActivityType, CancelEvent, Date, Event, EventContent, EventCopy, EventFilter, EventStatus,
+        LateCancellation, NON_MEMBER_NAME_QUESTION_ID, NewRegistration, QuestionSummary,
+        QuestionType, Registration, RegistrationId, asked_answers,
     },
+    event_metadata::EventMetadata,
+    event_organiser::OrganiserRole,
     location::{Location, LocationId},
     user::UserId,
     wire::event::EventId,
 };
-use axum::{Json, extract::Path, http::HeaderMap, response::Response};
+use axum::{
+    Json,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use schemars::Schema;
 use time::OffsetDateTime;
 use tracing::{debug, info, trace, warn};
 use uuid::Uuid;
 
-const NON_MEMBER_NAME_QUESTION_ID: &str = "8d3d4e48-4e8f-4e15-a7d9-6ff5e4c8e8ad";
-
-async fn has_registration_access(
+pub(super) async fn has_registration_access(
     store: &EventStore,
     user_id: &UserId,
     session: &Session,
@@ -32,11 +40,7 @@ async fn has_registration_access(
 
     if let Some(event_id) = event_id {
         let event: Event<Location> = store.get_event(event_id, true).await?;
-        if store
-            .ensure_user_is_committee_chair(session, &event.content.created_by)
-            .await
-            .is_ok()
-        {
+        if store.ensure_lead_organiser(session, &event).await.is_ok() {
             return Ok(());
         }
     }
@@ -60,25 +64,10 @@ pub async fn get_event_registrations(
         return conditional_json_response(&headers, HeaderMap::new(), &regs);
     }
 
-    let worga_user = event
-        .content
-        .metadata
-        .get("worga")
-        .and_then(|v| v.as_str())
-        .and_then(|s| Uuid::parse_str(s).ok());
-
-    // worga user → detailed
-    if let (Some(session), Some(worga_uuid)) = (&session, worga_user)
-        && **session.user_id() == worga_uuid
-    {
-        let regs = store.get_registrations_detailed(&id).await?;
-        return conditional_json_response(&headers, HeaderMap::new(), &regs);
-    }
-
-    // Committee member → detailed
+    // Committee member or organiser → detailed
     if let Some(ref session) = session
         && store
-            .ensure_user_in_committee(session, &event.content.created_by)
+            .ensure_event_organiser(session, &event, OrganiserRole::ALL)
             .await
             .is_ok()
     {
@@ -111,6 +100,23 @@ pub async fn get_event_registrations(
     Err(Error::Unauthorized)
 }
 
+/// Aggregated answers of the participants, visible to those that see the detailed registrations
+pub async fn get_question_summary(
+    store: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<Vec<QuestionSummary>> {
+    let event: Event<Location> = store.get_event(&id, true).await?;
+    store
+        .ensure_event_organiser(&session, &event, OrganiserRole::ALL)
+        .await?;
+    Ok(Json(
+        store
+            .get_question_summary(&id, &event.content.questions)
+            .await?,
+    ))
+}
+
 pub async fn get_user_registrations(
     store: EventStore,
     Path(id): Path<UserId>,
@@ -134,38 +140,61 @@ pub async fn get_user_events(
     }
 }
 
+/// Public endpoint, the event editor builds its metadata form from the schema
+pub async fn get_metadata_schema(Path(event_type): Path<ActivityType>) -> ApiResult<Schema> {
+    Ok(Json(EventMetadata::json_schema(event_type)))
+}
+
 /// Partially public endpoint, no login required.
-/// If logged in with sufficient rights, one can see hidden activities.
+/// If logged in with sufficient rights, one can see drafts.
+/// Members of the organising committee and the organisers can see their own drafts.
 pub async fn get_event(
     store: EventStore,
     Path(id): Path<EventId>,
     session: Option<Session>,
 ) -> ApiResult<Event<Location>> {
-    if let Some(session) = session
-        && is_admin_or_board(&session).is_ok()
-    {
-        Ok(Json(store.get_event(&id, true).await?))
-    } else {
-        Ok(Json(store.get_event(&id, false).await?))
+    let event = store.get_event(&id, true).await?;
+    if event.content.status == EventStatus::Draft {
+        let Some(session) = session else {
+            return Err(Error::NotFound);
+        };
+        store
+            .ensure_event_organiser(&session, &event, OrganiserRole::ALL)
+            .await
+            .map_err(|_| Error::NotFound)?;
     }
+    Ok(Json(event))
 }
 
 /// Partially public endpoint, no login required.
-/// If logged in with sufficient rights, one can see hidden activities.
+/// If logged in with sufficient rights, one can see drafts.
+/// Members of the organising committee can see their own drafts.
 pub async fn get_activities(
     store: EventStore,
     session: Option<Session>,
+    ValidatedQuery(filter): ValidatedQuery<EventFilter>,
     headers: HeaderMap,
 ) -> AppResult<Response> {
-    if let Some(session) = session
-        && is_admin_or_board(&session).is_ok()
-    {
-        let events = store.get_events(true).await?;
-        conditional_json_response(&headers, HeaderMap::new(), &events)
-    } else {
-        let events = store.get_events(false).await?;
-        conditional_json_response(&headers, HeaderMap::new(), &events)
+    let display_hidden = session
+        .as_ref()
+        .is_some_and(|session| is_admin_or_board(session).is_ok());
+    let viewer = session.as_ref().map(Session::user_id);
+    let total = store.count_events(display_hidden, viewer, &filter).await?;
+    let events = store.get_events(display_hidden, viewer, &filter).await?;
+    conditional_json_response(&headers, total.as_header(), &events)
+}
+
+/// Events can only be cancelled through [`cancel_event`], so that participants are notified
+pub(super) fn ensure_valid_status_change(
+    current: Option<EventStatus>,
+    new: EventStatus,
+) -> AppResult<()> {
+    if new == EventStatus::Cancelled && current != Some(EventStatus::Cancelled) {
+        return Err(Error::BadRequest(
+            "Events must be cancelled using the cancel endpoint",
+        ));
     }
+    Ok(())
 }
 
 pub async fn create_event(
@@ -176,6 +205,7 @@ pub async fn create_event(
     store
         .ensure_user_in_committee(&session, &new.created_by)
         .await?;
+    ensure_valid_status_change(None, new.status)?;
     Ok(Json(store.create_event(new).await?))
 }
 
@@ -185,22 +215,95 @@ pub async fn update_event(
     Path(id): Path<EventId>,
     ValidatedJson(updated): ValidatedJson<EventContent<LocationId>>,
 ) -> ApiResult<Event<Location>> {
+    let current = store.get_event(&id, true).await?;
     store
-        .ensure_user_in_committee(&session, &updated.created_by)
+        .ensure_event_organiser(&session, &current, OrganiserRole::MANAGERS)
         .await?;
+    // Organisers outside the committee cannot hand the event to another committee
+    if updated.created_by != current.content.created_by {
+        store
+            .ensure_user_in_committee(&session, &updated.created_by)
+            .await?;
+    }
+    ensure_valid_status_change(Some(current.content.status), updated.status)?;
     Ok(Json(store.update_event(&id, updated).await?))
 }
 
-pub async fn delete_event(
+/// Creates a draft copy of `content` with new dates.
+/// One-off locations are copied too, so editing one event leaves the other untouched
+pub(super) async fn create_event_copy<T>(
+    store: &EventStore,
+    locations: &LocationStore,
+    content: EventContent<T>,
+    location_id: &LocationId,
+    copy: EventCopy,
+) -> AppResult<Event<Location>> {
+    let location = locations.get_one(location_id).await?;
+    let location_id = if location.content.reusable {
+        location.id
+    } else {
+        locations.create(location.content).await?.id
+    };
+
+    store
+        .create_event(content.into_copy(copy, location_id))
+        .await
+}
+
+/// Copies an event with new dates, the copy starts out as a draft
+pub async fn clone_event(
     store: EventStore,
+    locations: LocationStore,
     session: Session,
     Path(id): Path<EventId>,
-) -> AppResult<()> {
-    let event: Event<Location> = store.get_event(&id, true).await?;
+    ValidatedJson(copy): ValidatedJson<EventCopy>,
+) -> ApiResult<Event<Location>> {
+    let event = store.get_event(&id, true).await?;
     store
         .ensure_user_in_committee(&session, &event.content.created_by)
         .await?;
-    store.delete_event(&id).await
+
+    let location_id = event.content.location.id.clone();
+    Ok(Json(
+        create_event_copy(&store, &locations, event.content, &location_id, copy).await?,
+    ))
+}
+
+/// Cancels the event and notifies all participants. Registrations are kept.
+pub async fn cancel_event(
+    store: EventStore,
+    payments: PaymentStore,
+    session: Session,
+    Path(id): Path<EventId>,
+    ValidatedJson(cancel): ValidatedJson<CancelEvent>,
+) -> ApiResult<Event<Location>> {
+    let event = store.get_event(&id, true).await?;
+    store
+        .ensure_event_organiser(&session, &event, OrganiserRole::MANAGERS)
+        .await?;
+    if event.content.status == EventStatus::Cancelled {
+        return Err(Error::BadRequest("Event is already cancelled"));
+    }
+
+    info!(
+        event_id = event.id.to_string(),
+        user_id = session.user_id().to_string(),
+        "Cancelling event"
+    );
+    Ok(Json(
+        store.cancel_event(&payments, &id, &cancel.reason).await?,
+    ))
+}
+
+pub async fn delete_event(
+    store: EventStore,
+    payments: PaymentStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> AppResult<()> {
+    let event: Event<Location> = store.get_event(&id, true).await?;
+    store.ensure_lead_organiser(&session, &event).await?;
+    store.delete_event(&payments, &id).await
 }
 
 pub async fn get_registration(
@@ -220,20 +323,59 @@ pub async fn create_registration(
     store: EventStore,
     session: Option<Session>,
     Path(event_id): Path<EventId>,
-    ValidatedJson(mut new): ValidatedJson<NewRegistration>,
+    ValidatedJson(new): ValidatedJson<NewRegistration>,
 ) -> ApiResult<Registration> {
+    Ok(Json(
+        register_for_event(&store, session.as_ref(), &event_id, new).await?,
+    ))
+}
+
+async fn register_for_event(
+    store: &EventStore,
+    session: Option<&Session>,
+    event_id: &EventId,
+    mut new: NewRegistration,
+) -> AppResult<Registration> {
+    check_registration(store, session, event_id, &mut new).await?;
     let user_id = new.user_id.clone();
-    let event = store.get_event(&event_id, true).await?;
+    store.new_registration(event_id, user_id, new).await
+}
+
+/// Checks whether the registration is allowed, and sets its waiting list position and answers
+pub(super) async fn check_registration(
+    store: &EventStore,
+    session: Option<&Session>,
+    event_id: &EventId,
+    new: &mut NewRegistration,
+) -> AppResult<()> {
+    let user_id = new.user_id.clone();
+    let event = store.get_event(event_id, true).await?;
+
+    match event.content.status {
+        EventStatus::Published => {}
+        EventStatus::Cancelled => Err(Error::BadRequest("Event has been cancelled"))?,
+        EventStatus::Archived => Err(Error::BadRequest("Event has been archived"))?,
+        EventStatus::Draft => {
+            // Organisers may already sign people up before publishing
+            let Some(session) = session else {
+                return Err(Error::NotFound);
+            };
+            store
+                .ensure_lead_organiser(session, &event)
+                .await
+                .map_err(|_| Error::NotFound)?;
+        }
+    }
 
     if let Some(ref user_id) = user_id {
-        let Some(ref session) = session else {
+        let Some(session) = session else {
             info!(
                 user_id = user_id.to_string(),
                 "Tried to register with user ID while not logged in"
             );
             return Err(Error::Unauthorized);
         };
-        has_registration_access(&store, user_id, session, Some(&event_id))
+        has_registration_access(store, user_id, session, Some(event_id))
             .await
             .inspect_err(|_| {
                 info!(
@@ -254,18 +396,25 @@ pub async fn create_registration(
         );
         return Err(Error::Unauthorized);
     } else {
+        // The non-member is the first guest, older clients answer a fixed question instead
         let non_member_name_question_id = Uuid::parse_str(NON_MEMBER_NAME_QUESTION_ID)
             .expect("NON_MEMBER_NAME_QUESTION_ID must be a valid UUID");
-        let has_non_member_name = new.answers.iter().any(|answer| {
-            answer.question_id == non_member_name_question_id && !answer.answer.trim().is_empty()
-        });
+        let has_non_member_name = !new.guests.is_empty()
+            || new.answers.iter().any(|answer| {
+                answer.question_id == non_member_name_question_id
+                    && !answer.answer.trim().is_empty()
+            });
         if !has_non_member_name {
             return Err(Error::BadRequest("Missing non-member name"));
         }
     }
 
+    if new.extra_guests() > event.content.max_guests {
+        return Err(Error::BadRequest("Too many guests for this event"));
+    }
+
     if event.content.registration_period.is_none() {
-        let Some(ref session) = session else {
+        let Some(session) = session else {
             // For anonymous registrations
             debug!(
                 event_id = event.id.to_string(),
@@ -283,30 +432,46 @@ pub async fn create_registration(
         }
     }
 
-    if let Some(ref session) = session {
+    if let Some(session) = session {
         if !(is_admin_or_board(session).is_ok()
-            || store
-                .ensure_user_is_committee_chair(session, &event.content.created_by)
-                .await
-                .is_ok())
+            || store.ensure_lead_organiser(session, &event).await.is_ok())
         {
             ensure_signup_has_not_passed(&event)?;
+            ensure_signup_has_opened(&event, session.membership())?;
         }
     } else {
         ensure_signup_has_not_passed(&event)?;
+        ensure_signup_has_opened(&event, Membership::NonMember)?;
     };
 
-    ensure_correct_waiting_list_position(&store, &event, &mut new, session.as_ref(), None).await?;
+    if event.content.lottery && event.lottery_drawn.is_none() {
+        // Everyone enters the pool at the end of the waiting list, the draw assigns the places
+        new.waiting_list_position = Some(event.waiting_list_count as i32);
+    } else {
+        ensure_correct_waiting_list_position(store, &event, new, session, None).await?;
+    }
     trace!(
         event_id = event.id.to_string(),
         "Calculated waiting list position {:?}", new.waiting_list_position
     );
 
-    if !check_required_questions_answered(&event.content.questions, &new.answers) {
-        Err(Error::BadRequest("Missing answer for required question"))?
-    };
+    check_answers(store, &event, new).await?;
 
-    Ok(Json(store.new_registration(&event_id, user_id, new).await?))
+    // Also enforced for organisers, as the certifications are required for safety
+    if !event.content.required_certifications.is_empty() {
+        let missing = store
+            .missing_certifications(event_id, user_id.as_ref())
+            .await?;
+        if !missing.is_empty() {
+            debug!(
+                event_id = event.id.to_string(),
+                "Registration is missing certifications {missing:?}"
+            );
+            return Err(Error::MissingCertifications(missing));
+        }
+    }
+
+    Ok(())
 }
 
 pub async fn update_registration(
@@ -316,6 +481,8 @@ pub async fn update_registration(
     ValidatedJson(mut updated): ValidatedJson<NewRegistration>,
 ) -> ApiResult<Registration> {
     let registration = store.get_registration(&registration_id).await?;
+    // The user of a registration cannot change
+    updated.user_id = registration.user.as_ref().map(|u| u.id.clone());
 
     if is_admin_or_board(&session).is_err() {
         let Some(user_id) = registration.user.as_ref().map(|u| u.id.clone()) else {
@@ -329,15 +496,27 @@ pub async fn update_registration(
 
     let event = store.get_event(&registration.event_id, true).await?;
 
+    if updated.extra_guests() > event.content.max_guests {
+        return Err(Error::BadRequest("Too many guests for this event"));
+    }
+
     if !(is_admin_or_board(&session).is_ok()
-        || store
-            .ensure_user_is_committee_chair(&session, &event.content.created_by)
-            .await
-            .is_ok())
+        || store.ensure_lead_organiser(&session, &event).await.is_ok())
     {
         ensure_signup_has_not_passed(&event)?;
         //ensure users cannot update attendance themselves
         updated.attended = registration.attended;
+
+        let added_places = (updated.places() - registration.places) as i64;
+        if registration.waiting_list_position.is_none()
+            && added_places > 0
+            && event
+                .content
+                .registration_max
+                .is_some_and(|max| event.registration_count + added_places > max as i64)
+        {
+            return Err(Error::BadRequest("Not enough places left for the guests"));
+        }
     }
 
     ensure_correct_waiting_list_position(
@@ -348,47 +527,168 @@ pub async fn update_registration(
         Some(&registration),
     )
     .await?;
+    check_answers(&store, &event, &mut updated).await?;
 
     Ok(Json(
         store.update_registration(&registration_id, updated).await?,
     ))
 }
 
+/// After the cancellation deadline, cancelling a place only requests the cancellation,
+/// which the organisers then approve or reject
 pub async fn delete_registration(
     store: EventStore,
+    payments: PaymentStore,
     session: Session,
     Path((event_id, registration_id)): Path<(EventId, RegistrationId)>,
-) -> AppResult<()> {
+) -> AppResult<StatusCode> {
     if is_admin_or_board(&session).is_ok() {
-        store.delete_registration(&registration_id).await
+        store
+            .delete_registration(&payments, &registration_id)
+            .await?;
+        return Ok(StatusCode::OK);
+    }
+
+    let registration = store.get_registration(&registration_id).await?;
+    if let Some(user_id) = registration.user.as_ref().map(|u| u.id.clone()) {
+        // Normal users can only delete their own registration
+        has_registration_access(&store, &user_id, &session, Some(&event_id)).await?;
     } else {
-        let registration = store.get_registration(&registration_id).await?;
-        if let Some(user_id) = registration.user.as_ref().map(|u| u.id.clone()) {
-            // Normal users can only delete their own registration
-            has_registration_access(&store, &user_id, &session, Some(&event_id)).await?;
-        } else {
-            // Anonymous registrations can only be modified by admins
-            return Err(Error::Unauthorized);
-        };
-        let event = store.get_event(&registration.event_id, true).await?;
-        if store
-            .ensure_user_is_committee_chair(&session, &event.content.created_by)
-            .await
-            .is_err()
-        {
-            ensure_signup_has_not_passed(&event)?;
-        }
-        store.delete_registration(&registration_id).await
+        // Anonymous registrations can only be modified by admins
+        return Err(Error::Unauthorized);
+    };
+    let event = store.get_event(&registration.event_id, true).await?;
+    if store.ensure_lead_organiser(&session, &event).await.is_err()
+        && registration.waiting_list_position.is_none()
+        && event
+            .content
+            .cancellation_deadline()
+            .is_some_and(|deadline| deadline < OffsetDateTime::now_utc())
+    {
+        debug!(
+            event_id = event.id.to_string(),
+            registration_id = registration_id.to_string(),
+            "Cancellation deadline has passed, requesting cancellation"
+        );
+        store.request_cancellation(&registration_id).await?;
+        return Ok(StatusCode::ACCEPTED);
+    }
+
+    store
+        .delete_registration(&payments, &registration_id)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+/// Fetches a registration with a pending cancellation request,
+/// the session must belong to the organisers of the event
+async fn get_cancellation_request(
+    store: &EventStore,
+    session: &Session,
+    event_id: &EventId,
+    registration_id: &RegistrationId,
+) -> AppResult<Registration> {
+    let registration = store.get_registration(registration_id).await?;
+    if registration.event_id != *event_id {
+        return Err(Error::NotFound);
+    }
+    let event = store.get_event(event_id, true).await?;
+    store
+        .ensure_event_organiser(session, &event, OrganiserRole::MANAGERS)
+        .await?;
+    if registration.cancellation_requested.is_none() {
+        return Err(Error::BadRequest("No cancellation was requested"));
     }
+    Ok(registration)
+}
+
+pub async fn approve_cancellation(
+    store: EventStore,
+    session: Session,
+    Path((event_id, registration_id)): Path<(EventId, RegistrationId)>,
+) -> AppResult<()> {
+    get_cancellation_request(&store, &session, &event_id, &registration_id).await?;
+    store
+        .approve_cancellation(&registration_id, session.user_id())
+        .await?;
+    info!(
+        event_id = event_id.to_string(),
+        registration_id = registration_id.to_string(),
+        "Approved late cancellation"
+    );
+    Ok(())
+}
+
+pub async fn reject_cancellation(
+    store: EventStore,
+    session: Session,
+    Path((event_id, registration_id)): Path<(EventId, RegistrationId)>,
+) -> AppResult<()> {
+    get_cancellation_request(&store, &session, &event_id, &registration_id).await?;
+    store.reject_cancellation(&registration_id).await
 }
 
-fn check_required_questions_answered(questions: &[Question], answers: &[Answer]) -> bool {
-    for question in questions {
-        if question.required && !answers.iter().any(|a| a.question_id == question.id) {
-            return false;
+/// Approved late cancellations, for the organisers and the treasurer
+pub async fn get_late_cancellations(
+    store: EventStore,
+    session: Session,
+    Path(event_id): Path<EventId>,
+) -> ApiResult<Vec<LateCancellation>> {
+    let event = store.get_event(&event_id, true).await?;
+    store
+        .ensure_event_organiser(
+            &session,
+            &event,
+            &[
+                OrganiserRole::Lead,
+                OrganiserRole::CoOrganiser,
+                OrganiserRole::Treasurer,
+            ],
+        )
+        .await?;
+    Ok(Json(store.get_late_cancellations(&event_id).await?))
+}
+
+/// Validates the answers against the questions. Answers to questions that were not asked
+/// are dropped. Option capacity is checked when the registration is stored.
+async fn check_answers(
+    store: &EventStore,
+    event: &Event<Location>,
+    registration: &mut NewRegistration,
+) -> AppResult<()> {
+    let questions = &event.content.questions;
+    let mut answers = asked_answers(questions, &registration.answers)?;
+
+    let file_ids: Vec<Uuid> = questions
+        .iter()
+        .filter(|question| matches!(question.question_type, QuestionType::File))
+        .filter_map(|question| {
+            answers
+                .iter()
+                .find(|answer| answer.question_id == question.id)
+        })
+        .filter_map(|answer| Uuid::parse_str(&answer.answer).ok())
+        .collect();
+    if !file_ids.is_empty() {
+        let uploaded = match &registration.user_id {
+            Some(user_id) => store.files_uploaded_by(&file_ids, user_id).await?,
+            None => false,
+        };
+        if !uploaded {
+            return Err(Error::BadRequest(
+                "Files must be uploaded by the participant",
+            ));
         }
     }
-    true
+
+    // Answers to unknown questions are kept, older clients answer a fixed question
+    answers.extend(registration.answers.drain(..).filter(|answer| {
+        !questions
+            .iter()
+            .any(|question| question.id == answer.question_id)
+    }));
+    registration.answers = answers;
+    Ok(())
 }
 
 fn ensure_signup_has_not_passed(event: &Event<Location>) -> Result<(), Error> {
@@ -402,6 +702,24 @@ fn ensure_signup_has_not_passed(event: &Event<Location>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Only memberships with a rule can have a staged opening
+fn ensure_signup_has_opened(event: &Event<Location>, membership: Membership) -> AppResult<()> {
+    if event.content.membership_rule(membership).is_some()
+        && let Some(opens) = event.content.registration_opens(membership)
+        && opens > OffsetDateTime::now_utc()
+    {
+        debug!(
+            event_id = event.id.to_string(),
+            ?membership,
+            "Registrations are not open yet for this membership"
+        );
+        return Err(Error::BadRequest(
+            "Registrations are not open yet for your membership",
+        ));
+    }
+    Ok(())
+}
+
 /// Depending on access rights, it allows overwriting the waiting list position
 /// Additionally, it ensures that only valid positions are accepted.
 async fn ensure_correct_waiting_list_position(
@@ -413,10 +731,7 @@ async fn ensure_correct_waiting_list_position(
 ) -> Result<(), Error> {
     if let Some(session) = session
         && (is_admin_or_board(session).is_ok()
-            || store
-                .ensure_user_is_committee_chair(session, &event.content.created_by)
-                .await
-                .is_ok())
+            || store.ensure_lead_organiser(session, event).await.is_ok())
     {
         trace!("logged in user has admin access to the waiting list");
         if new_registration.waiting_list_position.is_some() {
@@ -450,16 +765,43 @@ async fn ensure_correct_waiting_list_position(
             "No admin access to waiting list, overriding with exising waiting list position"
         );
         new_registration.waiting_list_position = registration.waiting_list_position
-    } else if let Some(registration_max) = event.content.registration_max {
+    } else {
         trace!(
             event_id = event.id.to_string(),
             "New registration without admin access"
         );
-        if registration_max <= event.registration_count as i32 {
-            trace!("Registrations are full, adding to waiting list");
+        let membership = session.map_or(Membership::NonMember, Session::membership);
+        let places = new_registration.places() as i64;
+        let event_full = event
+            .content
+            .registration_max
+            .is_some_and(|registration_max| {
+                event.registration_count + places > registration_max as i64
+            });
+        let quota_full = match event
+            .content
+            .membership_rule(membership)
+            .and_then(|rule| rule.quota)
+        {
+            Some(quota) => {
+                store
+                    .count_registrations_with_membership(&event.id, membership)
+                    .await?
+                    + places
+                    > quota as i64
+            }
+            None => false,
+        };
+
+        if event_full || quota_full {
+            trace!(
+                event_full,
+                quota_full, "Registrations are full, adding to waiting list"
+            );
 
             if let Some(waiting_list_max) = event.content.waiting_list_max
-                && waiting_list_max <= event.waiting_list_count as i32
+                && store.count_waiting_list_places(&event.id).await? + places
+                    > waiting_list_max as i64
             {
                 Err(Error::BadRequest(
                     "Registrations and waiting list are already full",
@@ -471,9 +813,6 @@ async fn ensure_correct_waiting_list_position(
             trace!("Still spots available, setting waiting list position to None");
             new_registration.waiting_list_position = None
         }
-    } else {
-        trace!("No limit to the registrations, setting waiting list position to None");
-        new_registration.waiting_list_position = None
     };
     Ok(())
 }
@@ -0,0 +1,66 @@
+use crate::{
+    api::{ApiResult, ValidatedJson},
+    auth::session::Session,
+    data_source::event::EventStore,
+    error::{AppResult, Error},
+    event::{EventId, EventStatus},
+    event_organiser::{EventOrganiser, OrganiserRole, SetOrganiser},
+    user::UserId,
+};
+use axum::{Json, extract::Path};
+use tracing::info;
+
+/// Public like the event itself, the organisers of drafts are only visible to the organisers
+pub async fn get_event_organisers(
+    store: EventStore,
+    session: Option<Session>,
+    Path(id): Path<EventId>,
+) -> ApiResult<Vec<EventOrganiser>> {
+    let event = store.get_event(&id, true).await?;
+    if event.content.status == EventStatus::Draft {
+        let Some(session) = session else {
+            return Err(Error::NotFound);
+        };
+        store
+            .ensure_event_organiser(&session, &event, OrganiserRole::ALL)
+            .await
+            .map_err(|_| Error::NotFound)?;
+    }
+    Ok(Json(store.get_organisers(&id).await?))
+}
+
+/// Organisers are appointed by the owning committee or the lead organiser
+pub async fn set_event_organiser(
+    store: EventStore,
+    session: Session,
+    Path((id, user_id)): Path<(EventId, UserId)>,
+    ValidatedJson(organiser): ValidatedJson<SetOrganiser>,
+) -> ApiResult<Vec<EventOrganiser>> {
+    session.ensure_not_impersonating()?;
+    let event = store.get_event(&id, true).await?;
+    store
+        .ensure_event_organiser(&session, &event, &[OrganiserRole::Lead])
+        .await?;
+
+    store.set_organiser(&id, &user_id, organiser.role).await?;
+    info!(
+        event_id = id.to_string(),
+        user_id = user_id.to_string(),
+        role = ?organiser.role,
+        "Set event organiser"
+    );
+    Ok(Json(store.get_organisers(&id).await?))
+}
+
+pub async fn remove_event_organiser(
+    store: EventStore,
+    session: Session,
+    Path((id, user_id)): Path<(EventId, UserId)>,
+) -> AppResult<()> {
+    session.ensure_not_impersonating()?;
+    let event = store.get_event(&id, true).await?;
+    store
+        .ensure_event_organiser(&session, &event, &[OrganiserRole::Lead])
+        .await?;
+    store.remove_organiser(&id, &user_id).await
+}
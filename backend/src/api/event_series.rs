@@ -0,0 +1,124 @@
+use crate::{
+    api::{
+        ApiResult, ValidatedJson,
+        event::{check_registration, ensure_valid_status_change},
+    },
+    auth::session::Session,
+    data_source::{EventSeriesStore, PaymentStore, event::EventStore},
+    error::{AppResult, Error},
+    event::{EventStatus, NewRegistration},
+    event_series::{EventSeries, EventSeriesContent, EventSeriesId, SeriesRegistration},
+};
+use axum::{Json, extract::Path};
+use tracing::debug;
+
+/// Partially public endpoint, drafts are only visible to the organising committee
+pub async fn get_event_series(
+    store: EventSeriesStore,
+    events: EventStore,
+    Path(id): Path<EventSeriesId>,
+    session: Option<Session>,
+) -> ApiResult<EventSeries> {
+    let series = store.get_one(&id).await?;
+    if series.content.status == EventStatus::Draft {
+        let Some(session) = session else {
+            return Err(Error::NotFound);
+        };
+        events
+            .ensure_user_in_committee(&session, &series.content.created_by)
+            .await
+            .map_err(|_| Error::NotFound)?;
+    }
+    Ok(Json(series))
+}
+
+pub async fn create_event_series(
+    store: EventSeriesStore,
+    events: EventStore,
+    session: Session,
+    ValidatedJson(new): ValidatedJson<EventSeriesContent>,
+) -> ApiResult<EventSeries> {
+    events
+        .ensure_user_in_committee(&session, &new.content.created_by)
+        .await?;
+    ensure_valid_status_change(None, new.content.status)?;
+    Ok(Json(store.create(new).await?))
+}
+
+/// Series-wide edit, applied to all future occurrences
+pub async fn update_event_series(
+    store: EventSeriesStore,
+    events: EventStore,
+    payments: PaymentStore,
+    session: Session,
+    Path(id): Path<EventSeriesId>,
+    ValidatedJson(updated): ValidatedJson<EventSeriesContent>,
+) -> ApiResult<EventSeries> {
+    let current = store.get_one(&id).await?;
+    events
+        .ensure_user_in_committee(&session, &current.content.created_by)
+        .await?;
+    events
+        .ensure_user_in_committee(&session, &updated.content.created_by)
+        .await?;
+    ensure_valid_status_change(None, updated.content.status)?;
+    Ok(Json(store.update(&payments, &id, updated).await?))
+}
+
+pub async fn delete_event_series(
+    store: EventSeriesStore,
+    events: EventStore,
+    payments: PaymentStore,
+    session: Session,
+    Path(id): Path<EventSeriesId>,
+) -> AppResult<()> {
+    let series = store.get_one(&id).await?;
+    events
+        .ensure_user_in_committee(&session, &series.content.created_by)
+        .await?;
+    store.delete(&payments, &id).await
+}
+
+/// Registers for every future occurrence of the series.
+/// Occurrences one cannot register for, e.g. because they are full, are skipped
+pub async fn create_series_registration(
+    store: EventSeriesStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventSeriesId>,
+    ValidatedJson(new): ValidatedJson<NewRegistration>,
+) -> ApiResult<SeriesRegistration> {
+    let occurrences = store.get_future_occurrences(&id).await?;
+    if occurrences.is_empty() {
+        return Err(Error::BadRequest("The series has no upcoming occurrences"));
+    }
+
+    let mut checked = Vec::with_capacity(occurrences.len());
+    let mut skipped = Vec::new();
+    for event_id in occurrences {
+        let mut registration = new.clone();
+        match check_registration(&events, Some(&session), &event_id, &mut registration).await {
+            Ok(()) => checked.push((event_id, registration)),
+            Err(err) if err.is_refusal() => {
+                debug!(
+                    event_id = event_id.to_string(),
+                    "Skipping occurrence in series registration: {err}"
+                );
+                skipped.push(event_id);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    let (registered, refused) = store.register(new.user_id.clone(), checked).await?;
+    skipped.extend(refused);
+    let mut registrations = Vec::with_capacity(registered.len());
+    for registration_id in registered {
+        registrations.push(events.get_registration(&registration_id).await?);
+    }
+
+    Ok(Json(SeriesRegistration {
+        registrations,
+        skipped,
+    }))
+}
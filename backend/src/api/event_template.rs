@@ -0,0 +1,97 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, event::create_event_copy},
+    auth::session::Session,
+    committee::CommitteeId,
+    data_source::{EventTemplateStore, LocationStore, event::EventStore},
+    error::AppResult,
+    event::{Event, EventCopy},
+    event_template::{EventTemplate, EventTemplateContent, EventTemplateId},
+    location::Location,
+};
+use axum::{Json, extract::Path};
+
+/// Templates never carry dates, those are given when creating an event from a template
+fn normalize(
+    committee_id: &CommitteeId,
+    mut template: EventTemplateContent,
+) -> EventTemplateContent {
+    template.content.created_by = **committee_id;
+    template.content.dates = Vec::new();
+    template.content.registration_period = None;
+    template.content.publish_at = None;
+    template
+}
+
+pub async fn get_event_templates(
+    store: EventTemplateStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<CommitteeId>,
+) -> ApiResult<Vec<EventTemplate>> {
+    events.ensure_user_in_committee(&session, &id).await?;
+    Ok(Json(store.get_for_committee(&id).await?))
+}
+
+pub async fn get_event_template(
+    store: EventTemplateStore,
+    events: EventStore,
+    session: Session,
+    Path((id, template_id)): Path<(CommitteeId, EventTemplateId)>,
+) -> ApiResult<EventTemplate> {
+    events.ensure_user_in_committee(&session, &id).await?;
+    Ok(Json(store.get_one(&id, &template_id).await?))
+}
+
+pub async fn create_event_template(
+    store: EventTemplateStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<CommitteeId>,
+    ValidatedJson(new): ValidatedJson<EventTemplateContent>,
+) -> ApiResult<EventTemplate> {
+    events.ensure_user_in_committee(&session, &id).await?;
+    Ok(Json(store.create(&id, normalize(&id, new)).await?))
+}
+
+pub async fn update_event_template(
+    store: EventTemplateStore,
+    events: EventStore,
+    session: Session,
+    Path((id, template_id)): Path<(CommitteeId, EventTemplateId)>,
+    ValidatedJson(updated): ValidatedJson<EventTemplateContent>,
+) -> ApiResult<EventTemplate> {
+    events.ensure_user_in_committee(&session, &id).await?;
+    Ok(Json(
+        store
+            .update(&id, &template_id, normalize(&id, updated))
+            .await?,
+    ))
+}
+
+pub async fn delete_event_template(
+    store: EventTemplateStore,
+    events: EventStore,
+    session: Session,
+    Path((id, template_id)): Path<(CommitteeId, EventTemplateId)>,
+) -> AppResult<()> {
+    events.ensure_user_in_committee(&session, &id).await?;
+    store.delete(&id, &template_id).await
+}
+
+/// Creates a draft event from the template, with new question ids
+pub async fn create_event_from_template(
+    store: EventTemplateStore,
+    events: EventStore,
+    locations: LocationStore,
+    session: Session,
+    Path((id, template_id)): Path<(CommitteeId, EventTemplateId)>,
+    ValidatedJson(copy): ValidatedJson<EventCopy>,
+) -> ApiResult<Event<Location>> {
+    events.ensure_user_in_committee(&session, &id).await?;
+    let template = store.get_one(&id, &template_id).await?;
+
+    let location_id = template.content.location.clone();
+    Ok(Json(
+        create_event_copy(&events, &locations, template.content, &location_id, copy).await?,
+    ))
+}
@@ -0,0 +1,118 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, is_admin_or_board},
+    auth::session::Session,
+    data_source::{FeedbackStore, event::EventStore},
+    error::{AppResult, Error},
+    event::{Event, EventId, asked_answers},
+    event_organiser::OrganiserRole,
+    feedback::{FeedbackForm, FeedbackFormContent, FeedbackResults, NewFeedbackResponse},
+    location::Location,
+};
+use axum::{Json, extract::Path};
+use tracing::info;
+
+/// Visible to the organisers and to the attendees, who fill it in
+pub async fn get_feedback_form(
+    store: FeedbackStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<FeedbackForm> {
+    let event: Event<Location> = events.get_event(&id, true).await?;
+    if events
+        .ensure_event_organiser(&session, &event, OrganiserRole::ALL)
+        .await
+        .is_err()
+        && !store.is_attendee(&id, session.user_id()).await?
+    {
+        return Err(Error::Unauthorized);
+    }
+    Ok(Json(store.get_form(&id, session.user_id()).await?))
+}
+
+pub async fn set_feedback_form(
+    store: FeedbackStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+    ValidatedJson(content): ValidatedJson<FeedbackFormContent>,
+) -> ApiResult<FeedbackForm> {
+    let event: Event<Location> = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(&session, &event, OrganiserRole::MANAGERS)
+        .await?;
+
+    store.set_form(&id, content).await?;
+    Ok(Json(store.get_form(&id, session.user_id()).await?))
+}
+
+pub async fn delete_feedback_form(
+    store: FeedbackStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> AppResult<()> {
+    let event: Event<Location> = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(&session, &event, OrganiserRole::MANAGERS)
+        .await?;
+    store.delete_form(&id).await
+}
+
+/// Asks the attendees that did not respond yet, so it doubles as a reminder
+pub async fn send_feedback_form(
+    store: FeedbackStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<FeedbackForm> {
+    let event: Event<Location> = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(&session, &event, OrganiserRole::MANAGERS)
+        .await?;
+    // Fails when there is no form
+    store.get_form(&id, session.user_id()).await?;
+
+    let notified = store.send(&id).await?;
+    info!(
+        event_id = id.to_string(),
+        notified, "Asked attendees for feedback"
+    );
+    Ok(Json(store.get_form(&id, session.user_id()).await?))
+}
+
+pub async fn create_feedback_response(
+    store: FeedbackStore,
+    session: Session,
+    Path(id): Path<EventId>,
+    ValidatedJson(response): ValidatedJson<NewFeedbackResponse>,
+) -> AppResult<()> {
+    let form = store.get_form(&id, session.user_id()).await?;
+    if !store.is_attendee(&id, session.user_id()).await? {
+        return Err(Error::Unauthorized);
+    }
+
+    let answers = asked_answers(&form.content.questions, &response.answers)?;
+    store
+        .respond(&id, session.user_id(), form.content.anonymous, answers)
+        .await
+}
+
+/// For the organisers, the board only sees the results when the form shares them
+pub async fn get_feedback_results(
+    store: FeedbackStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<FeedbackResults> {
+    let event: Event<Location> = events.get_event(&id, true).await?;
+    let form = store.get_form(&id, session.user_id()).await?;
+
+    if !(form.content.shared_with_board && is_admin_or_board(&session).is_ok()) {
+        events
+            .ensure_event_organiser(&session, &event, OrganiserRole::ALL)
+            .await?;
+    }
+
+    Ok(Json(store.get_results(&id, &form.content.questions).await?))
+}
@@ -1,9 +1,10 @@
 use crate::{
     Pagination, ValidatedQuery,
-    api::{ApiResult, committee::active_committee_access},
+    api::{ApiResult, committee::active_committee_access, is_admin_or_board},
     auth::session::Session,
-    data_source::{FileStore, committee::CommitteeStore},
+    data_source::{FileStore, committee::CommitteeStore, event::EventStore},
     error::{AppResult, Error},
+    event_organiser::OrganiserRole,
     file::{FileId, FileMetadata},
 };
 use axum::{
@@ -25,17 +26,41 @@ pub async fn upload(
     store: FileStore,
     committee_store: CommitteeStore,
     session: Session,
-    mut multipart: Multipart,
+    multipart: Multipart,
 ) -> ApiResult<Vec<FileMetadata>> {
     active_committee_access(&session, &committee_store).await?;
+    store_files(&store, &session, multipart, false).await
+}
+
+/// Answer files a user may upload per day, each request is limited to 10 MB
+const ANSWER_FILES_PER_DAY: i64 = 20;
+
+/// Any logged-in user can upload files to answer registration questions,
+/// these are never public and only readable by the uploader and the organisers
+pub async fn upload_answer_file(
+    store: FileStore,
+    session: Session,
+    multipart: Multipart,
+) -> ApiResult<Vec<FileMetadata>> {
+    if store.count_recent_answer_files(session.user_id()).await? >= ANSWER_FILES_PER_DAY {
+        return Err(Error::BadRequest("Too many files uploaded today"));
+    }
+    store_files(&store, &session, multipart, true).await
+}
 
+async fn store_files(
+    store: &FileStore,
+    session: &Session,
+    mut multipart: Multipart,
+    is_answer: bool,
+) -> ApiResult<Vec<FileMetadata>> {
     let mut result = vec![];
     let mut is_public = false;
     while let Some(field) = multipart.next_field().await.unwrap() {
         let name = field.name().unwrap().to_string();
         if name == "isPublic" || name == "is_public" {
             if let Ok(value) = field.text().await {
-                is_public = value == "true" || value == "1";
+                is_public = !is_answer && (value == "true" || value == "1");
             }
             continue;
         }
@@ -57,7 +82,7 @@ pub async fn upload(
 
         result.push(
             store
-                .create(&name, content_type, data, is_public, &session)
+                .create(&name, content_type, data, is_public, is_answer, session)
                 .await?,
         );
         info!(
@@ -102,15 +127,54 @@ fn reduce_image_size(bytes: &[u8]) -> AppResult<(Bytes, Mime)> {
     Ok((buf.into(), mime))
 }
 
+/// Public files are readable by anyone and other files by members, files answering
+/// registration questions only by the uploader, the board and the organisers of the event
+async fn ensure_file_access(
+    events: &EventStore,
+    meta: &FileMetadata,
+    session: Option<&Session>,
+) -> AppResult<()> {
+    if meta.is_public {
+        return Ok(());
+    }
+    let Some(session) = session else {
+        return Err(Error::Unauthorized);
+    };
+    if session.user_id() == &meta.created_by {
+        return Ok(());
+    }
+    if !meta.is_answer {
+        return if session.is_member() {
+            Ok(())
+        } else {
+            Err(Error::Unauthorized)
+        };
+    }
+    if is_admin_or_board(session).is_ok() {
+        return Ok(());
+    }
+
+    for event_id in events.events_with_answer_file(&meta.id).await? {
+        let event = events.get_event(&event_id, true).await?;
+        if events
+            .ensure_event_organiser(session, &event, OrganiserRole::ALL)
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+    }
+    Err(Error::Unauthorized)
+}
+
 pub async fn get_file_content(
     store: FileStore,
+    events: EventStore,
     Path(id): Path<FileId>,
     session: Option<Session>,
 ) -> AppResult<impl IntoResponse> {
     let meta = store.get_metadata(&id).await?;
-    if !meta.is_public && !session.is_some_and(|s| s.is_member()) {
-        return Err(Error::Unauthorized);
-    }
+    ensure_file_access(&events, &meta, session.as_ref()).await?;
     let bytes = store.get_bytes(&id).await?;
 
     let mut headers = HeaderMap::new();
@@ -122,13 +186,12 @@ pub async fn get_file_content(
 
 pub async fn get_file_metadata(
     store: FileStore,
+    events: EventStore,
     Path(id): Path<FileId>,
     session: Option<Session>,
 ) -> ApiResult<FileMetadata> {
     let meta = store.get_metadata(&id).await?;
-    if !meta.is_public && !session.is_some_and(|s| s.is_member()) {
-        return Err(Error::Unauthorized);
-    }
+    ensure_file_access(&events, &meta, session.as_ref()).await?;
     Ok(Json(meta))
 }
 
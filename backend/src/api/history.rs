@@ -0,0 +1,85 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, is_admin_or_board},
+    auth::session::Session,
+    data_source::HistoryStore,
+    error::{AppResult, Error},
+    history::{History, LogbookEntry, LogbookEntryContent, LogbookEntryId, LogbookVisibility},
+    user::UserId,
+};
+use axum::{Json, extract::Path};
+
+pub async fn get_user_history(
+    store: HistoryStore,
+    session: Session,
+    Path(id): Path<UserId>,
+) -> ApiResult<History> {
+    if id != *session.user_id() {
+        is_admin_or_board(&session)?;
+    }
+    Ok(Json(store.get_history(&id).await?))
+}
+
+/// Members only see the entries of others that are shared with members or public.
+/// Everyone else only sees public entries, without the partners as they did not share those.
+pub async fn get_logbook(
+    store: HistoryStore,
+    session: Option<Session>,
+    Path(id): Path<UserId>,
+) -> ApiResult<Vec<LogbookEntry>> {
+    let visibility: &[LogbookVisibility] = match &session {
+        Some(session) if *session.user_id() == id => &[
+            LogbookVisibility::Private,
+            LogbookVisibility::Members,
+            LogbookVisibility::Public,
+        ],
+        Some(session) if session.is_member() => {
+            &[LogbookVisibility::Members, LogbookVisibility::Public]
+        }
+        _ => &[LogbookVisibility::Public],
+    };
+    let mut entries = store.get_logbook(&id, visibility).await?;
+    if visibility == [LogbookVisibility::Public] {
+        for entry in &mut entries {
+            entry.partners.clear();
+        }
+    }
+    Ok(Json(entries))
+}
+
+/// Only the members themselves keep their logbook
+fn ensure_own_logbook(session: &Session, id: &UserId) -> AppResult<()> {
+    if session.user_id() == id {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+pub async fn create_logbook_entry(
+    store: HistoryStore,
+    session: Session,
+    Path(id): Path<UserId>,
+    ValidatedJson(content): ValidatedJson<LogbookEntryContent>,
+) -> ApiResult<LogbookEntry> {
+    ensure_own_logbook(&session, &id)?;
+    Ok(Json(store.create_entry(&id, content).await?))
+}
+
+pub async fn update_logbook_entry(
+    store: HistoryStore,
+    session: Session,
+    Path((id, entry_id)): Path<(UserId, LogbookEntryId)>,
+    ValidatedJson(content): ValidatedJson<LogbookEntryContent>,
+) -> ApiResult<LogbookEntry> {
+    ensure_own_logbook(&session, &id)?;
+    Ok(Json(store.update_entry(&id, &entry_id, content).await?))
+}
+
+pub async fn delete_logbook_entry(
+    store: HistoryStore,
+    session: Session,
+    Path((id, entry_id)): Path<(UserId, LogbookEntryId)>,
+) -> AppResult<()> {
+    ensure_own_logbook(&session, &id)?;
+    store.delete_entry(&id, &entry_id).await
+}
@@ -0,0 +1,23 @@
+use crate::{
+    api::ApiResult,
+    auth::session::Session,
+    data_source::{LotteryStore, event::EventStore},
+    event::EventId,
+    event_organiser::OrganiserRole,
+    lottery::LotteryDraw,
+};
+use axum::{Json, extract::Path};
+
+/// The seed and outcome of a draw, so the board and organisers can verify it
+pub async fn get_lottery_draw(
+    store: LotteryStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<LotteryDraw> {
+    let event = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(&session, &event, OrganiserRole::ALL)
+        .await?;
+    Ok(Json(store.get_draw(&id).await?))
+}
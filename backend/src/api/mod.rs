@@ -1,15 +1,30 @@
+mod api_token;
+mod attendance;
+mod carpool;
+mod certification;
 mod committee;
 mod event;
+mod event_organiser;
+mod event_series;
+mod event_template;
+mod feedback;
 mod file;
+mod history;
 mod location;
+mod lottery;
 mod material;
+mod notification;
 mod page;
+mod payment;
+mod sepa;
 mod user;
 
 use crate::{
     auth::{role::Role, session::Session},
     error::{AppResult, Error},
 };
+pub use api_token::*;
+pub use attendance::*;
 use axum::{
     Json,
     extract::{
@@ -23,12 +38,23 @@ use axum::{
     },
     response::{IntoResponse, Response},
 };
+pub use carpool::*;
+pub use certification::*;
 pub use committee::*;
 pub use event::*;
+pub use event_organiser::*;
+pub use event_series::*;
+pub use event_template::*;
+pub use feedback::*;
 pub use file::*;
+pub use history::*;
 pub use location::*;
+pub use lottery::*;
 pub use material::*;
+pub use notification::*;
 pub use page::*;
+pub use payment::*;
+pub use sepa::*;
 use serde::{Deserialize, de::DeserializeOwned};
 use serde_with::{DisplayFromStr, serde_as};
 use std::{
@@ -111,6 +137,30 @@ pub(crate) fn is_admin_or_board(session: &Session) -> AppResult<()> {
     }
 }
 
+pub(crate) fn is_admin(session: &Session) -> AppResult<()> {
+    if session
+        .roles()
+        .iter()
+        .any(|role| matches!(role, Role::Admin))
+    {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+pub(crate) fn is_admin_or_treasurer(session: &Session) -> AppResult<()> {
+    if session
+        .roles()
+        .iter()
+        .any(|role| matches!(role, Role::Admin | Role::Treasurer))
+    {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
 fn compute_etag(bytes: &[u8]) -> String {
     let mut hasher = DefaultHasher::new();
     bytes.hash(&mut hasher);
@@ -0,0 +1,36 @@
+use crate::{
+    api::ApiResult,
+    auth::session::Session,
+    data_source::NotificationStore,
+    error::{AppResult, Error},
+    notification::{Notification, NotificationId},
+    user::UserId,
+};
+use axum::{Json, extract::Path};
+
+/// Notifications are personal, not even admins can read those of others
+fn notification_access(id: &UserId, session: &Session) -> AppResult<()> {
+    if id == session.user_id() {
+        Ok(())
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+pub async fn get_notifications(
+    store: NotificationStore,
+    session: Session,
+    Path(id): Path<UserId>,
+) -> ApiResult<Vec<Notification>> {
+    notification_access(&id, &session)?;
+    Ok(Json(store.get_for_user(&id).await?))
+}
+
+pub async fn mark_notification_read(
+    store: NotificationStore,
+    session: Session,
+    Path((id, notification_id)): Path<(UserId, NotificationId)>,
+) -> AppResult<()> {
+    notification_access(&id, &session)?;
+    store.mark_read(&id, &notification_id).await
+}
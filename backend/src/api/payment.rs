@@ -0,0 +1,107 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, is_admin_or_board, is_admin_or_treasurer},
+    auth::session::Session,
+    data_source::{PaymentStore, event::EventStore},
+    error::{AppResult, Error},
+    event::{EventId, RegistrationId},
+    event_organiser::OrganiserRole,
+    payment::{Checkout, PaymentOverview, PaymentStatus, UpdatePayment},
+    payment_provider::SIGNATURE_HEADER,
+    user::UserId,
+};
+use axum::{Json, body::Bytes, extract::Path, http::HeaderMap};
+use tracing::info;
+
+/// Outstanding balances of an event, for the organisers and the board
+pub async fn get_event_payments(
+    store: PaymentStore,
+    events: EventStore,
+    session: Session,
+    Path(id): Path<EventId>,
+) -> ApiResult<PaymentOverview> {
+    let event = events.get_event(&id, true).await?;
+    events
+        .ensure_event_organiser(
+            &session,
+            &event,
+            &[
+                OrganiserRole::Lead,
+                OrganiserRole::CoOrganiser,
+                OrganiserRole::Treasurer,
+            ],
+        )
+        .await?;
+    Ok(Json(store.get_for_event(&id).await?.into()))
+}
+
+pub async fn get_user_payments(
+    store: PaymentStore,
+    session: Session,
+    Path(id): Path<UserId>,
+) -> ApiResult<PaymentOverview> {
+    if id != *session.user_id() {
+        is_admin_or_board(&session)?;
+    }
+    Ok(Json(store.get_for_user(&id).await?.into()))
+}
+
+/// Payments are recorded by the treasurer
+pub async fn update_payment(
+    store: PaymentStore,
+    events: EventStore,
+    session: Session,
+    Path((event_id, registration_id)): Path<(EventId, RegistrationId)>,
+    ValidatedJson(update): ValidatedJson<UpdatePayment>,
+) -> AppResult<()> {
+    is_admin_or_treasurer(&session)?;
+    let registration = events.get_registration(&registration_id).await?;
+    if registration.event_id != event_id {
+        return Err(Error::NotFound);
+    }
+    store.set_status(&registration_id, update.status).await?;
+    info!(
+        registration_id = registration_id.to_string(),
+        status = ?update.status,
+        "Updated payment status"
+    );
+    Ok(())
+}
+
+/// Starts an online payment for your own registration
+pub async fn create_checkout(
+    store: PaymentStore,
+    events: EventStore,
+    session: Session,
+    Path((event_id, registration_id)): Path<(EventId, RegistrationId)>,
+) -> ApiResult<Checkout> {
+    let registration = events.get_registration(&registration_id).await?;
+    if registration.event_id != event_id {
+        return Err(Error::NotFound);
+    }
+    if registration.user.as_ref().map(|user| &user.id) != Some(session.user_id()) {
+        return Err(Error::Unauthorized);
+    }
+    if registration.waiting_list_position.is_some() {
+        return Err(Error::BadRequest("Cannot pay while on the waiting list"));
+    }
+    if registration.amount_due <= 0 || registration.payment_status != PaymentStatus::Unpaid {
+        return Err(Error::BadRequest("Nothing to pay"));
+    }
+
+    let event = events.get_event(&event_id, true).await?;
+    Ok(Json(
+        store.checkout(&registration, event.content.name.nl).await?,
+    ))
+}
+
+/// Called by the payment provider whenever the status of a payment changes
+pub async fn payment_webhook(
+    store: PaymentStore,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<()> {
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok());
+    store.handle_webhook(signature, &body).await
+}
@@ -0,0 +1,153 @@
+use crate::{
+    api::{ApiResult, ValidatedJson, is_admin_or_treasurer},
+    auth::session::Session,
+    data_source::SepaStore,
+    error::{AppResult, Error},
+    sepa::{
+        CollectionBatch, CollectionBatchId, FeeCollection, FeeCollectionId, MembershipFee,
+        NewCollectionBatch, SepaMandate, SepaMandateContent, UpdateCollection,
+    },
+    user::UserId,
+};
+use axum::{
+    Json,
+    extract::Path,
+    http::{HeaderMap, HeaderValue, header},
+};
+use tracing::info;
+
+/// Members manage their own mandate, the treasurer manages all of them
+fn mandate_access(user_id: &UserId, session: &Session) -> AppResult<()> {
+    if user_id == session.user_id() {
+        Ok(())
+    } else {
+        is_admin_or_treasurer(session)
+    }
+}
+
+pub async fn get_sepa_mandate(
+    store: SepaStore,
+    session: Session,
+    Path(user_id): Path<UserId>,
+) -> ApiResult<SepaMandate> {
+    mandate_access(&user_id, &session)?;
+    Ok(Json(store.get_mandate(&user_id).await?))
+}
+
+pub async fn set_sepa_mandate(
+    store: SepaStore,
+    session: Session,
+    Path(user_id): Path<UserId>,
+    ValidatedJson(mandate): ValidatedJson<SepaMandateContent>,
+) -> ApiResult<SepaMandate> {
+    session.ensure_not_impersonating()?;
+    mandate_access(&user_id, &session)?;
+    Ok(Json(store.set_mandate(&user_id, mandate).await?))
+}
+
+pub async fn delete_sepa_mandate(
+    store: SepaStore,
+    session: Session,
+    Path(user_id): Path<UserId>,
+) -> AppResult<()> {
+    session.ensure_not_impersonating()?;
+    mandate_access(&user_id, &session)?;
+    store.delete_mandate(&user_id).await
+}
+
+pub async fn get_membership_fees(
+    store: SepaStore,
+    session: Session,
+    Path(year): Path<i32>,
+) -> ApiResult<Vec<MembershipFee>> {
+    is_admin_or_treasurer(&session)?;
+    Ok(Json(store.get_fees(year).await?))
+}
+
+pub async fn set_membership_fees(
+    store: SepaStore,
+    session: Session,
+    Path(year): Path<i32>,
+    ValidatedJson(fees): ValidatedJson<Vec<MembershipFee>>,
+) -> ApiResult<Vec<MembershipFee>> {
+    is_admin_or_treasurer(&session)?;
+    store.set_fees(year, fees).await?;
+    Ok(Json(store.get_fees(year).await?))
+}
+
+pub async fn get_fee_collections(
+    store: SepaStore,
+    session: Session,
+    Path(year): Path<i32>,
+) -> ApiResult<Vec<FeeCollection>> {
+    is_admin_or_treasurer(&session)?;
+    Ok(Json(store.get_collections(year).await?))
+}
+
+pub async fn create_collection_batch(
+    store: SepaStore,
+    session: Session,
+    Path(year): Path<i32>,
+    Json(new): Json<NewCollectionBatch>,
+) -> ApiResult<CollectionBatch> {
+    is_admin_or_treasurer(&session)?;
+    // Fail early, instead of after creating a batch that cannot be exported
+    store.creditor()?;
+    Ok(Json(
+        store
+            .create_batch(year, new.collection_date, session.user_id())
+            .await?,
+    ))
+}
+
+pub async fn get_collection_batch(
+    store: SepaStore,
+    session: Session,
+    Path(id): Path<CollectionBatchId>,
+) -> ApiResult<CollectionBatch> {
+    is_admin_or_treasurer(&session)?;
+    Ok(Json(store.get_batch(&id).await?))
+}
+
+/// The pain.008 file to upload to the bank
+pub async fn export_collection_batch(
+    store: SepaStore,
+    session: Session,
+    Path(id): Path<CollectionBatchId>,
+) -> AppResult<(HeaderMap, String)> {
+    is_admin_or_treasurer(&session)?;
+    let batch = store.get_batch(&id).await?;
+    let xml = batch.to_pain008(store.creditor()?);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/xml"),
+    );
+    let disposition = format!(
+        "attachment; filename=\"incasso-{}-{}.xml\"",
+        batch.year,
+        batch.id.simple()
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&disposition).map_err(|err| Error::Internal(err.to_string()))?,
+    );
+    Ok((headers, xml))
+}
+
+pub async fn update_fee_collection(
+    store: SepaStore,
+    session: Session,
+    Path(id): Path<FeeCollectionId>,
+    Json(update): Json<UpdateCollection>,
+) -> AppResult<()> {
+    is_admin_or_treasurer(&session)?;
+    store.set_collection_status(&id, update.status).await?;
+    info!(
+        collection_id = id.to_string(),
+        status = ?update.status,
+        "Updated fee collection"
+    );
+    Ok(())
+}
@@ -1,10 +1,13 @@
 use crate::{
     Pagination,
-    api::{ApiResult, ValidatedJson, ValidatedQuery, conditional_json_response, is_admin_or_board},
-    auth::{role::Status, session::Session},
+    api::{
+        ApiResult, ValidatedJson, ValidatedQuery, conditional_json_response, is_admin,
+        is_admin_or_board,
+    },
+    auth::{password::Params, role::Status, session::Session},
     data_source::UserStore,
     error::{AppResult, Error},
-    user::{Password, RegisterNewUser, User, UserContent, UserId},
+    user::{Password, RegisterNewUser, User, UserContent, UserId, WhoAmI},
 };
 use axum::{
     Json,
@@ -14,6 +17,7 @@ use axum::{
 };
 use axum_extra::extract::CookieJar;
 use sqlx::PgPool;
+use tracing::info;
 
 enum UpdateAccess {
     Anything,
@@ -21,7 +25,14 @@ enum UpdateAccess {
 }
 
 fn update_access(id: &UserId, session: &Session) -> AppResult<UpdateAccess> {
-    if is_admin_or_board(session).is_ok() {
+    if session.impersonated_by().is_some() {
+        // Impersonating admins cannot use the rights of the impersonated user to change roles
+        if id == session.user_id() {
+            Ok(UpdateAccess::SelfUpdate)
+        } else {
+            Err(Error::Forbidden("Not allowed while impersonating a user"))
+        }
+    } else if is_admin_or_board(session).is_ok() {
         Ok(UpdateAccess::Anything)
     } else if id == session.user_id() {
         Ok(UpdateAccess::SelfUpdate)
@@ -50,10 +61,11 @@ fn read_all_access(session: &Session) -> AppResult<ReadAccess> {
 pub async fn register(
     db: PgPool,
     store: UserStore,
+    params: Params,
     jar: CookieJar,
     ValidatedJson(new): ValidatedJson<RegisterNewUser>,
 ) -> AppResult<impl IntoResponse> {
-    let pwd_hash = new.pwd_hash()?;
+    let pwd_hash = new.pwd_hash(&params)?;
     let user = UserContent {
         first_name: new.first_name,
         infix: new.infix,
@@ -91,12 +103,97 @@ pub async fn who_am_i(
 ) -> AppResult<Response> {
     if let Some(session) = session {
         let user = store.get(session.user_id()).await?;
-        conditional_json_response(&headers, HeaderMap::new(), &user)
+        let impersonated_by = match session.impersonated_by() {
+            Some(actor_id) => Some(store.get_basic_info(actor_id).await?),
+            None => None,
+        };
+        conditional_json_response(
+            &headers,
+            HeaderMap::new(),
+            &WhoAmI {
+                user,
+                impersonated_by,
+            },
+        )
     } else {
         Err(Error::Unauthorized)
     }
 }
 
+/// Lets an admin see the website as another user would,
+/// replaces the session cookie of the admin with a session for that user
+pub async fn start_impersonation(
+    db: PgPool,
+    store: UserStore,
+    session: Session,
+    jar: CookieJar,
+    Path(id): Path<UserId>,
+) -> AppResult<impl IntoResponse> {
+    is_admin(&session)?;
+    session.ensure_not_impersonating()?;
+    if session.is_api_token() {
+        return Err(Error::Unauthorized);
+    }
+    if id == *session.user_id() {
+        return Err(Error::BadRequest("Cannot impersonate yourself"));
+    }
+
+    let user = store.get(&id).await?;
+    // Every role grants admin or board rights, which must not be borrowed
+    if !user.content.roles.is_empty() {
+        return Err(Error::Forbidden(
+            "Cannot impersonate users with an admin or board role",
+        ));
+    }
+    let actor_id = session.user_id().clone();
+    info!(
+        actor_id = %actor_id,
+        user_id = %id,
+        "Admin started impersonating user"
+    );
+
+    session.delete(&db).await?;
+    let impersonation = Session::new_impersonation(&db, &id, &actor_id).await?;
+    let impersonated_by = Some(store.get_basic_info(&actor_id).await?);
+
+    Ok((
+        jar.add(impersonation.into_cookie()),
+        Json(WhoAmI {
+            user,
+            impersonated_by,
+        }),
+    ))
+}
+
+/// Ends the impersonation and hands the admin a regular session again
+pub async fn stop_impersonation(
+    db: PgPool,
+    store: UserStore,
+    session: Session,
+    jar: CookieJar,
+) -> AppResult<impl IntoResponse> {
+    let Some(actor_id) = session.impersonated_by().cloned() else {
+        return Err(Error::BadRequest("Not impersonating a user"));
+    };
+    info!(
+        actor_id = %actor_id,
+        user_id = %session.user_id(),
+        "Admin stopped impersonating user"
+    );
+
+    session.delete(&db).await?;
+    let session = Session::new(&db, &actor_id).await?;
+    let user = store.get(&actor_id).await?;
+
+    Ok((
+        jar.add(session.into_cookie()),
+        Json(WhoAmI {
+            user,
+            impersonated_by: None,
+        }),
+    ))
+}
+
 pub async fn get_user(
     store: UserStore,
     Path(id): Path<UserId>,
@@ -146,7 +243,13 @@ pub async fn update_user(
     Path(id): Path<UserId>,
     ValidatedJson(user): ValidatedJson<UserContent>,
 ) -> ApiResult<User> {
-    let res = match update_access(&id, &session)? {
+    let access = update_access(&id, &session)?;
+    // Password resets go to the email address, changing it would hand over the account
+    if session.impersonated_by().is_some() && store.get(&id).await?.content.email != user.email {
+        session.ensure_not_impersonating()?;
+    }
+
+    let res = match access {
         UpdateAccess::Anything => store.update(&id, user).await,
         UpdateAccess::SelfUpdate => store.self_update(&id, user).await,
     }?;
@@ -156,12 +259,14 @@ pub async fn update_user(
 
 pub async fn update_pwd(
     store: UserStore,
+    params: Params,
     session: Session,
     Path(id): Path<UserId>,
     ValidatedJson(pwd): ValidatedJson<Password>,
 ) -> AppResult<()> {
+    session.ensure_not_impersonating()?;
     update_access(&id, &session)?;
-    store.update_pwd(&id, Some(&pwd.pwd_hash()?)).await
+    store.update_pwd(&id, Some(&pwd.pwd_hash(&params)?)).await
 }
 
 pub async fn delete_user(
@@ -169,6 +274,7 @@ pub async fn delete_user(
     session: Session,
     Path(id): Path<UserId>,
 ) -> AppResult<()> {
+    session.ensure_not_impersonating()?;
     is_admin_or_board(&session)?;
     store.delete(&id).await
 }
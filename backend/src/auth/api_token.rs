@@ -0,0 +1,19 @@
+use rand::distr::{Alphanumeric, SampleString};
+use sha2::{Digest, Sha256};
+
+/// Makes personal access tokens easy to recognize, e.g. by secret scanners
+const TOKEN_PREFIX: &str = "nijsac_pat_";
+
+/// Generates a new random personal access token
+pub fn generate_token() -> String {
+    format!(
+        "{TOKEN_PREFIX}{}",
+        Alphanumeric.sample_string(&mut rand::rng(), 40)
+    )
+}
+
+/// Tokens contain enough entropy that a plain SHA-256 suffices,
+/// which keeps the lookup on every request cheap
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
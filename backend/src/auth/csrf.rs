@@ -0,0 +1,71 @@
+use crate::{
+    auth::COOKIE_NAME,
+    error::{AppResult, Error},
+};
+use axum::{
+    extract::Request,
+    http::header::AUTHORIZATION,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::{
+    CookieJar,
+    cookie::{Cookie, SameSite},
+};
+use rand::distr::{Alphanumeric, SampleString};
+use tracing::debug;
+
+/// Readable by the frontend, which echoes it in the [`CSRF_HEADER_NAME`] header
+const CSRF_COOKIE_NAME: &str = "CSRF_TOKEN";
+const CSRF_HEADER_NAME: &str = "X-CSRF-Token";
+
+fn new_csrf_cookie() -> Cookie<'static> {
+    Cookie::build((
+        CSRF_COOKIE_NAME,
+        Alphanumeric.sample_string(&mut rand::rng(), 32),
+    ))
+    .secure(true)
+    .path("/")
+    .same_site(SameSite::Strict)
+    .build()
+}
+
+/// Double-submit CSRF protection for cookie authenticated requests.
+///
+/// Requests with an unsafe method that carry the session cookie must send the value of the
+/// CSRF cookie in the `X-CSRF-Token` header as well. Requests authenticated with a bearer token
+/// are exempt, as the session cookie is ignored for those.
+/// The CSRF cookie is handed out on any response if the client does not have one yet.
+pub async fn csrf_protection(jar: CookieJar, request: Request, next: Next) -> AppResult<Response> {
+    let csrf_cookie = jar.get(CSRF_COOKIE_NAME).map(|c| c.value().to_owned());
+
+    if !request.method().is_safe()
+        && !request.headers().contains_key(AUTHORIZATION)
+        && jar.get(COOKIE_NAME).is_some()
+    {
+        let csrf_header = request
+            .headers()
+            .get(CSRF_HEADER_NAME)
+            .and_then(|value| value.to_str().ok());
+
+        match (&csrf_cookie, csrf_header) {
+            (Some(cookie), Some(header)) if cookie == header => {}
+            _ => {
+                debug!(
+                    method = %request.method(),
+                    uri = %request.uri(),
+                    "Rejected request with missing or invalid CSRF token"
+                );
+                return Err(Error::Forbidden("Missing or invalid CSRF token"));
+            }
+        }
+    }
+
+    let response = next.run(request).await;
+
+    if csrf_cookie.is_none() {
+        Ok((jar.add(new_csrf_cookie()), response).into_response())
+    } else {
+        Ok(response)
+    }
+}
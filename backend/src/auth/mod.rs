@@ -1,11 +1,18 @@
 use crate::{
-    api::ValidatedJson, auth::session::Session, error::Error, wire::user::UserCredentials,
+    api::ValidatedJson,
+    auth::{password::Params, session::Session},
+    error::Error,
+    wire::user::UserCredentials,
 };
 use axum::{Json, response::IntoResponse};
 use axum_extra::extract::{CookieJar, cookie::Cookie};
 use sqlx::PgPool;
 use tracing::trace;
 
+pub mod api_token;
+pub mod check_in;
+pub mod csrf;
+pub mod password;
 pub mod role;
 pub mod session;
 
@@ -13,11 +20,12 @@ const COOKIE_NAME: &str = "SESSION";
 
 pub async fn login(
     db: PgPool,
+    params: Params,
     jar: CookieJar,
     ValidatedJson(credentials): ValidatedJson<UserCredentials>,
 ) -> Result<impl IntoResponse, Error> {
     trace!("Login attempt for user {}", credentials.email);
-    let (session, user) = Session::from_credentials(credentials, &db).await?;
+    let (session, user) = Session::from_credentials(credentials, &db, &params).await?;
     Ok((jar.add(session.into_cookie()), Json(user)))
 }
 
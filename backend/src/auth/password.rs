@@ -0,0 +1,72 @@
+use crate::error::Error;
+use argon2::{
+    Algorithm, Argon2, PasswordHash, PasswordHasher, Version,
+    password_hash::{SaltString, rand_core::OsRng},
+};
+use std::{borrow::Cow, sync::LazyLock};
+use validator::ValidationError;
+
+pub use argon2::Params;
+
+const MIN_PASSWORD_LENGTH: usize = 10;
+
+/// Well known and breached passwords that satisfy the length requirement, one per line
+static COMMON_PASSWORDS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    include_str!("common_passwords.txt")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect()
+});
+
+/// Password policy, used for every password that is set
+pub fn validate_password_policy(password: &str) -> Result<(), ValidationError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(
+            ValidationError::new("password_length").with_message(Cow::Owned(format!(
+                "Password must contain at least {MIN_PASSWORD_LENGTH} characters"
+            ))),
+        );
+    }
+
+    let lowercase = password.to_lowercase();
+    if COMMON_PASSWORDS.contains(&lowercase.as_str()) {
+        return Err(
+            ValidationError::new("password_common").with_message(Cow::Borrowed(
+                "This password is too common, please choose another one",
+            )),
+        );
+    }
+
+    Ok(())
+}
+
+fn argon2(params: &Params) -> Argon2<'static> {
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params.clone())
+}
+
+pub fn hash_password(password: &str, params: &Params) -> Result<String, Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(argon2(params)
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(Error::Argon2)?
+        .to_string())
+}
+
+/// Whether a stored hash was created with a different algorithm
+/// or weaker parameters than currently configured
+pub fn needs_rehash(hash: &PasswordHash, params: &Params) -> bool {
+    if hash.algorithm != Algorithm::Argon2id.ident() || hash.version != Some(Version::V0x13.into())
+    {
+        return true;
+    }
+
+    match Params::try_from(hash) {
+        Ok(current) => {
+            current.m_cost() < params.m_cost()
+                || current.t_cost() < params.t_cost()
+                || current.p_cost() < params.p_cost()
+        }
+        Err(_) => true,
+    }
+}
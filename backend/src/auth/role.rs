@@ -14,7 +14,7 @@ pub enum Role {
     ClimbingCommissar,
 }
 
-#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[sqlx(type_name = "membership", rename_all = "snake_case")]
 #[serde(rename_all = "camelCase")]
 pub enum Membership {
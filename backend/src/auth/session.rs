@@ -1,7 +1,10 @@
 use crate::{
     AppState,
+    api_token::{ApiTokenId, ApiTokenScope},
     auth::{
         COOKIE_NAME,
+        api_token::hash_token,
+        password::{Params, needs_rehash},
         role::{Membership, Roles, Status},
     },
     data_source::UserStore,
@@ -12,30 +15,66 @@ use crate::{
 use argon2::PasswordHash;
 use axum::{
     extract::{FromRequestParts, OptionalFromRequestParts},
-    http::request::Parts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use axum_extra::extract::{
+    CookieJar,
+    cookie::{Cookie, SameSite},
 };
-use axum_extra::extract::{CookieJar, cookie::Cookie};
 use rand::distr::{Alphanumeric, SampleString};
 use sqlx::PgPool;
-use time::OffsetDateTime;
-use tracing::trace;
+use time::{Duration, OffsetDateTime};
+use tracing::{error, info, trace};
 use uuid::Uuid;
 
+/// Sessions of users without any role expire after this period of inactivity
+const SESSION_IDLE_TIMEOUT: Duration = Duration::days(30);
+/// Sessions of users with one or more roles expire after this period of inactivity
+const PRIVILEGED_SESSION_IDLE_TIMEOUT: Duration = Duration::hours(12);
+/// Sessions are never extended beyond this lifetime, measured from login
+const SESSION_MAX_LIFETIME: Duration = Duration::days(90);
+/// The expiration is only extended if the session was last seen longer ago than this,
+/// so that not every request results in a write to the database
+const SESSION_REFRESH_INTERVAL: Duration = Duration::minutes(5);
+/// How often expired sessions are purged from the database
+const SESSION_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
 pub struct Session {
     user_id: UserId,
-    cookie_value: String,
+    /// The admin that is actually using this session, while impersonating `user_id`
+    impersonated_by: Option<UserId>,
     roles: Roles,
     membership: Membership,
     status: Status,
-    expiration: OffsetDateTime,
+    origin: Origin,
+}
+
+/// How the session was authenticated
+enum Origin {
+    /// A browser session, identified by the session cookie
+    Cookie {
+        cookie_value: String,
+        created: OffsetDateTime,
+        last_seen: OffsetDateTime,
+        expiration: OffsetDateTime,
+    },
+    /// A personal access token, sent in the `Authorization: Bearer` header
+    ApiToken {
+        id: ApiTokenId,
+        scope: ApiTokenScope,
+        last_used: Option<OffsetDateTime>,
+    },
 }
 
 struct PgSession {
     user_id: Uuid,
+    impersonated_by: Option<Uuid>,
     cookie_value: String,
     roles: serde_json::Value,
     membership: Membership,
     status: Status,
+    created: OffsetDateTime,
+    last_seen: OffsetDateTime,
     expiration: OffsetDateTime,
 }
 
@@ -45,11 +84,45 @@ impl TryFrom<PgSession> for Session {
     fn try_from(pg: PgSession) -> Result<Self, Self::Error> {
         Ok(Self {
             user_id: pg.user_id.into(),
-            cookie_value: pg.cookie_value,
+            impersonated_by: pg.impersonated_by.map(Into::into),
             roles: serde_json::from_value(pg.roles)?,
             membership: pg.membership,
             status: pg.status,
-            expiration: pg.expiration,
+            origin: Origin::Cookie {
+                cookie_value: pg.cookie_value,
+                created: pg.created,
+                last_seen: pg.last_seen,
+                expiration: pg.expiration,
+            },
+        })
+    }
+}
+
+struct PgApiTokenSession {
+    token_id: Uuid,
+    user_id: Uuid,
+    scope: ApiTokenScope,
+    last_used: Option<OffsetDateTime>,
+    roles: serde_json::Value,
+    membership: Membership,
+    status: Status,
+}
+
+impl TryFrom<PgApiTokenSession> for Session {
+    type Error = Error;
+
+    fn try_from(pg: PgApiTokenSession) -> Result<Self, Self::Error> {
+        Ok(Self {
+            user_id: pg.user_id.into(),
+            impersonated_by: None,
+            roles: serde_json::from_value(pg.roles)?,
+            membership: pg.membership,
+            status: pg.status,
+            origin: Origin::ApiToken {
+                id: pg.token_id.into(),
+                scope: pg.scope,
+                last_used: pg.last_used,
+            },
         })
     }
 }
@@ -75,11 +148,51 @@ impl Session {
         &self.user_id
     }
 
+    /// Whether this session was authenticated using a personal access token
+    pub fn is_api_token(&self) -> bool {
+        matches!(self.origin, Origin::ApiToken { .. })
+    }
+
+    /// The admin that is impersonating the user of this session, if any
+    pub fn impersonated_by(&self) -> Option<&UserId> {
+        self.impersonated_by.as_ref()
+    }
+
+    /// Impersonating admins may not change passwords, roles or other credentials
+    pub fn ensure_not_impersonating(&self) -> AppResult<()> {
+        if self.impersonated_by.is_some() {
+            return Err(Error::Forbidden("Not allowed while impersonating a user"));
+        }
+        Ok(())
+    }
+
+    /// Users with any role, and impersonating admins, get a shorter idle timeout
+    fn idle_timeout(&self) -> Duration {
+        if self.roles.is_empty() && self.impersonated_by.is_none() {
+            SESSION_IDLE_TIMEOUT
+        } else {
+            PRIVILEGED_SESSION_IDLE_TIMEOUT
+        }
+    }
+
     pub fn into_cookie(self) -> Cookie<'static> {
-        Cookie::build((COOKIE_NAME, self.cookie_value))
+        let Origin::Cookie {
+            cookie_value,
+            created,
+            ..
+        } = self.origin
+        else {
+            unreachable!("Sessions created from an API token are never turned into a cookie");
+        };
+
+        // The sliding expiration is enforced server side,
+        // the cookie itself lives as long as the session possibly can
+        Cookie::build((COOKIE_NAME, cookie_value))
             .secure(true)
+            .http_only(true)
+            .same_site(SameSite::Lax)
             .path("/")
-            .expires(Some(self.expiration))
+            .expires(Some(created + SESSION_MAX_LIFETIME))
             .build()
     }
 
@@ -89,16 +202,21 @@ impl Session {
             r#"
             SELECT cookie_value,
                    u.id AS user_id,
+                   s.impersonated_by,
                    roles,
                    membership AS "membership: Membership",
                    status AS "status: Status",
-                   expiration
-            FROM session
+                   s.created,
+                   s.last_seen,
+                   s.expiration
+            FROM session s
                 JOIN "user" u ON user_id = u.id
-            WHERE expiration > now()
+            WHERE s.expiration > now()
+                AND s.created > now() - $2::interval
                 AND cookie_value = $1
             "#,
-            cookie_value
+            cookie_value,
+            SESSION_MAX_LIFETIME as Duration,
         )
         .fetch_one(db)
         .await
@@ -108,12 +226,163 @@ impl Session {
             Err(err) => Err(err.into()),
         }?;
 
-        session.try_into()
+        let mut session: Session = session.try_into()?;
+        session.refresh(db).await?;
+        Ok(session)
+    }
+
+    /// Extends the expiration of an active session,
+    /// throttled by [`SESSION_REFRESH_INTERVAL`] and capped by [`SESSION_MAX_LIFETIME`]
+    async fn refresh(&mut self, db: &PgPool) -> AppResult<()> {
+        let idle_timeout = self.idle_timeout();
+        let Origin::Cookie {
+            cookie_value,
+            last_seen,
+            expiration,
+            ..
+        } = &mut self.origin
+        else {
+            return Ok(());
+        };
+
+        if OffsetDateTime::now_utc() - *last_seen < SESSION_REFRESH_INTERVAL {
+            return Ok(());
+        }
+
+        let refreshed = sqlx::query!(
+            r#"
+            UPDATE session
+            SET last_seen = now(),
+                expiration = least(now() + $2::interval, created + $3::interval)
+            WHERE cookie_value = $1
+            RETURNING last_seen, expiration
+            "#,
+            &*cookie_value,
+            idle_timeout as Duration,
+            SESSION_MAX_LIFETIME as Duration,
+        )
+        .fetch_one(db)
+        .await?;
+
+        trace!(user_id = %self.user_id, "Extended session until {}", refreshed.expiration);
+        *last_seen = refreshed.last_seen;
+        *expiration = refreshed.expiration;
+
+        Ok(())
+    }
+
+    async fn from_api_token(token: &str, db: &PgPool) -> AppResult<Session> {
+        let session: Session = match sqlx::query_as!(
+            PgApiTokenSession,
+            r#"
+            SELECT t.id AS token_id,
+                   u.id AS user_id,
+                   t.scope AS "scope: ApiTokenScope",
+                   t.last_used,
+                   roles,
+                   membership AS "membership: Membership",
+                   status AS "status: Status"
+            FROM api_token t
+                JOIN "user" u ON t.user_id = u.id
+            WHERE t.token_hash = $1
+                AND (t.expiration IS NULL OR t.expiration > now())
+            "#,
+            hash_token(token)
+        )
+        .fetch_one(db)
+        .await
+        {
+            Ok(s) => Ok(s),
+            Err(sqlx::Error::RowNotFound) => Err(Error::Unauthorized),
+            Err(err) => Err(err.into()),
+        }?
+        .try_into()?;
+
+        if let Origin::ApiToken { id, last_used, .. } = &session.origin
+            && last_used.is_none_or(|last_used| {
+                OffsetDateTime::now_utc() - last_used >= SESSION_REFRESH_INTERVAL
+            })
+        {
+            sqlx::query!(
+                r#"
+                UPDATE api_token SET last_used = now() WHERE id = $1
+                "#,
+                **id
+            )
+            .execute(db)
+            .await?;
+        }
+
+        Ok(session)
+    }
+
+    /// Records every mutating request made while impersonating, with both identities
+    async fn audit_impersonation(&self, parts: &Parts, db: &PgPool) -> AppResult<()> {
+        let Some(actor_id) = &self.impersonated_by else {
+            return Ok(());
+        };
+        if parts.method.is_safe() {
+            return Ok(());
+        }
+
+        info!(
+            actor_id = %actor_id,
+            user_id = %self.user_id,
+            "Impersonated request {} {}", parts.method, parts.uri.path()
+        );
+
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_log (id, actor_id, user_id, method, path, created)
+            VALUES ($1, $2, $3, $4, $5, now())
+            "#,
+            Uuid::now_v7(),
+            **actor_id,
+            *self.user_id,
+            parts.method.as_str(),
+            parts.uri.path(),
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Read-only API tokens may only be used for safe requests, such as `GET`
+    fn ensure_scope_allows(&self, parts: &Parts) -> AppResult<()> {
+        if let Origin::ApiToken {
+            scope: ApiTokenScope::ReadOnly,
+            ..
+        } = self.origin
+            && !parts.method.is_safe()
+        {
+            trace!(user_id = %self.user_id, "Read-only API token used for {}", parts.method);
+            return Err(Error::Forbidden(
+                "Read-only API tokens cannot change anything",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Removes all sessions that are either idle for too long or exceeded their maximum lifetime
+    pub async fn delete_expired(db: &PgPool) -> AppResult<u64> {
+        Ok(sqlx::query!(
+            r#"
+            DELETE FROM session
+            WHERE expiration <= now()
+               OR created <= now() - $1::interval
+            "#,
+            SESSION_MAX_LIFETIME as Duration,
+        )
+        .execute(db)
+        .await?
+        .rows_affected())
     }
 
     pub async fn from_credentials(
         credentials: UserCredentials,
         db: &PgPool,
+        params: &Params,
     ) -> AppResult<(Session, User)> {
         let user = match sqlx::query!(
             r#"
@@ -137,9 +406,20 @@ impl Session {
         let parsed_hash = PasswordHash::new(&pw_hash).map_err(Error::Argon2)?;
         credentials.verify_pwd(&parsed_hash)?;
 
+        let store = UserStore::new(db.clone());
+
+        // The password is only available in plain text right now, so this is the moment
+        // to upgrade hashes created with weaker parameters
+        if needs_rehash(&parsed_hash, params) {
+            trace!("Upgrading password hash for user {}", credentials.email);
+            store
+                .update_pwd(&user.id, Some(&credentials.pwd_hash(params)?))
+                .await?;
+        }
+
         let session = Self::new(db, &user.id).await?;
 
-        let user = UserStore::new(db.clone()).get(&user.id).await?;
+        let user = store.get(&user.id).await?;
 
         trace!("Created new session for user {}", credentials.email);
 
@@ -147,6 +427,23 @@ impl Session {
     }
 
     pub async fn new(db: &PgPool, user_id: &UserId) -> AppResult<Session> {
+        Self::create(db, user_id, None).await
+    }
+
+    /// Creates a session for `user_id` that is actually used by the admin `actor_id`
+    pub async fn new_impersonation(
+        db: &PgPool,
+        user_id: &UserId,
+        actor_id: &UserId,
+    ) -> AppResult<Session> {
+        Self::create(db, user_id, Some(actor_id)).await
+    }
+
+    async fn create(
+        db: &PgPool,
+        user_id: &UserId,
+        impersonated_by: Option<&UserId>,
+    ) -> AppResult<Session> {
         let cookie_value = Alphanumeric.sample_string(&mut rand::rng(), 32);
 
         sqlx::query_as!(
@@ -157,32 +454,59 @@ impl Session {
                     (
                      cookie_value,
                      user_id,
+                     impersonated_by,
+                     created,
+                     last_seen,
                      expiration
-                ) VALUES ($1, $2, now() + '1 month')
+                )
+                SELECT $1,
+                       id,
+                       $5::uuid,
+                       now(),
+                       now(),
+                       now() + CASE
+                                   WHEN jsonb_array_length(roles) > 0 OR $5::uuid IS NOT NULL
+                                       THEN $3::interval
+                                   ELSE $4::interval
+                               END
+                FROM "user"
+                WHERE id = $2
                 RETURNING *)
             SELECT cookie_value,
                    u.id AS user_id,
+                   s.impersonated_by,
                    roles,
                    membership AS "membership: Membership",
                    status AS "status: Status",
-                   expiration
-            FROM new_session
+                   s.created,
+                   s.last_seen,
+                   s.expiration
+            FROM new_session s
                 JOIN "user" u ON user_id = u.id
             "#,
             cookie_value,
             **user_id,
+            PRIVILEGED_SESSION_IDLE_TIMEOUT as Duration,
+            SESSION_IDLE_TIMEOUT as Duration,
+            impersonated_by.map(|id| **id),
         )
         .fetch_one(db)
         .await?
         .try_into()
     }
 
+    /// Deletes a browser session. API tokens are left untouched,
+    /// those can only be revoked explicitly.
     pub async fn delete(self, db: &PgPool) -> AppResult<()> {
+        let Origin::Cookie { cookie_value, .. } = self.origin else {
+            return Ok(());
+        };
+
         sqlx::query!(
             r#"
             DELETE FROM session WHERE cookie_value = $1
             "#,
-            self.cookie_value
+            cookie_value
         )
         .execute(db)
         .await?;
@@ -190,6 +514,33 @@ impl Session {
     }
 }
 
+/// Extracts the token from an `Authorization: Bearer <token>` header, if present
+fn bearer_token(parts: &Parts) -> AppResult<Option<&str>> {
+    let Some(header) = parts.headers.get(AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    header
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| Some(token.trim()))
+        .ok_or(Error::BadRequest("Cannot decode authorization header"))
+}
+
+/// Periodically purges expired sessions, runs until the process exits
+pub async fn run_session_cleanup(db: PgPool) {
+    let mut interval = tokio::time::interval(SESSION_CLEANUP_INTERVAL);
+    loop {
+        interval.tick().await;
+        match Session::delete_expired(&db).await {
+            Ok(0) => {}
+            Ok(count) => info!("Purged {count} expired sessions"),
+            Err(err) => error!("Failed to purge expired sessions: {err}"),
+        }
+    }
+}
+
 impl FromRequestParts<AppState> for Session {
     type Rejection = Error;
 
@@ -197,6 +548,12 @@ impl FromRequestParts<AppState> for Session {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
+        if let Some(token) = bearer_token(parts)? {
+            let session = Session::from_api_token(token, state.pool()).await?;
+            session.ensure_scope_allows(parts)?;
+            return Ok(session);
+        }
+
         let jar = CookieJar::from_request_parts(parts, state)
             .await
             .map_err(|_| Self::Rejection::BadRequest("Cannot decode cookies"))?;
@@ -208,7 +565,9 @@ impl FromRequestParts<AppState> for Session {
             .ok_or(Self::Rejection::Unauthorized)?
             .value();
 
-        Session::get(session_cookie, state.pool()).await
+        let session = Session::get(session_cookie, state.pool()).await?;
+        session.audit_impersonation(parts, state.pool()).await?;
+        Ok(session)
     }
 }
 
@@ -219,6 +578,17 @@ impl OptionalFromRequestParts<AppState> for Session {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Option<Self>, Self::Rejection> {
+        if let Some(token) = bearer_token(parts)? {
+            return match Session::from_api_token(token, state.pool()).await {
+                Ok(session) => {
+                    session.ensure_scope_allows(parts)?;
+                    Ok(Some(session))
+                }
+                Err(Error::Unauthorized) => Ok(None),
+                Err(err) => Err(err),
+            };
+        }
+
         let jar = CookieJar::from_request_parts(parts, state)
             .await
             .map_err(|_| Self::Rejection::BadRequest("Cannot decode cookies"))?;
@@ -229,7 +599,10 @@ impl OptionalFromRequestParts<AppState> for Session {
         };
 
         match Session::get(session_cookie, state.pool()).await {
-            Ok(session) => Ok(Some(session)),
+            Ok(session) => {
+                session.audit_impersonation(parts, state.pool()).await?;
+                Ok(Some(session))
+            }
             Err(Error::Unauthorized) => Ok(None),
             Err(err) => Err(err),
         }
@@ -0,0 +1,201 @@
+//! A local stand-in for the Mollie payments API, to try out online payments without an account.
+//!
+//! Run it next to the backend with the same `PAYMENT_WEBHOOK_SECRET`, and point the backend
+//! to it with `PAYMENT_API_URL=http://localhost:3100` and any `PAYMENT_API_KEY`.
+//! Opening the checkout URL of a payment completes it, `?status=failed` (or `canceled`,
+//! `expired`) ends it otherwise. The webhook is called like the real provider would.
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use nijsac_website_backend::payment_provider::{SIGNATURE_HEADER, sign};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+struct MockPayment {
+    amount: Value,
+    status: String,
+    redirect_url: String,
+    webhook_url: String,
+}
+
+#[derive(Clone)]
+struct MockState {
+    payments: Arc<Mutex<HashMap<String, MockPayment>>>,
+    base_url: String,
+    webhook_secret: String,
+}
+
+impl MockState {
+    fn payment_json(&self, id: &str, payment: &MockPayment) -> Value {
+        json!({
+            "resource": "payment",
+            "id": id,
+            "status": payment.status,
+            "amount": payment.amount,
+            "method": "ideal",
+            "_links": {
+                "checkout": { "href": format!("{}/checkout/{id}", self.base_url) }
+            }
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatePayment {
+    amount: Value,
+    redirect_url: String,
+    webhook_url: String,
+}
+
+#[derive(Deserialize)]
+struct CheckoutQuery {
+    status: Option<String>,
+}
+
+fn authorized(headers: &HeaderMap) -> bool {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("Bearer "))
+}
+
+async fn create_payment(
+    State(state): State<MockState>,
+    headers: HeaderMap,
+    Json(new): Json<CreatePayment>,
+) -> Response {
+    if !authorized(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let id = format!("tr_{}", uuid::Uuid::now_v7().simple());
+    let payment = MockPayment {
+        amount: new.amount,
+        status: "open".to_string(),
+        redirect_url: new.redirect_url,
+        webhook_url: new.webhook_url,
+    };
+    let body = state.payment_json(&id, &payment);
+    state.payments.lock().unwrap().insert(id.clone(), payment);
+    info!(id, "Created payment");
+    (StatusCode::CREATED, Json(body)).into_response()
+}
+
+async fn get_payment(
+    State(state): State<MockState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if !authorized(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    match state.payments.lock().unwrap().get(&id) {
+        Some(payment) => Json(state.payment_json(&id, payment)).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn create_refund(
+    State(state): State<MockState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(refund): Json<Value>,
+) -> Response {
+    if !authorized(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    match state.payments.lock().unwrap().get(&id) {
+        Some(payment) if payment.status == "paid" => {
+            info!(id, "Refunded payment");
+            (
+                StatusCode::CREATED,
+                Json(json!({
+                    "resource": "refund",
+                    "id": format!("re_{}", uuid::Uuid::now_v7().simple()),
+                    "paymentId": id,
+                    "amount": refund["amount"],
+                    "status": "pending"
+                })),
+            )
+                .into_response()
+        }
+        Some(_) => StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Stands in for the bank: completes the payment, notifies the webhook and returns the member
+async fn checkout(
+    State(state): State<MockState>,
+    Path(id): Path<String>,
+    Query(query): Query<CheckoutQuery>,
+) -> Response {
+    let status = query.status.unwrap_or_else(|| "paid".to_string());
+    let (redirect_url, webhook_url) = {
+        let mut payments = state.payments.lock().unwrap();
+        let Some(payment) = payments.get_mut(&id) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        payment.status = status;
+        (payment.redirect_url.clone(), payment.webhook_url.clone())
+    };
+
+    let body = json!({ "resource": "payment", "id": id }).to_string();
+    let result = reqwest::Client::new()
+        .post(&webhook_url)
+        .header(
+            SIGNATURE_HEADER,
+            sign(&state.webhook_secret, body.as_bytes()),
+        )
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await;
+    match result {
+        Ok(response) => info!(id, status = %response.status(), "Called webhook"),
+        Err(err) => warn!(id, "Webhook failed: {err}"),
+    }
+
+    Redirect::to(&redirect_url).into_response()
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().init();
+    dotenvy::dotenv().ok();
+
+    let port: u16 = std::env::var("MOCK_PAYMENT_PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(3100);
+    let state = MockState {
+        payments: Arc::default(),
+        base_url: format!("http://localhost:{port}"),
+        webhook_secret: std::env::var("PAYMENT_WEBHOOK_SECRET")
+            .expect("PAYMENT_WEBHOOK_SECRET env var must be set"),
+    };
+
+    let app = Router::new()
+        .route("/v2/payments", post(create_payment))
+        .route("/v2/payments/{:id}", get(get_payment))
+        .route("/v2/payments/{:id}/refunds", post(create_refund))
+        .route("/checkout/{:id}", get(checkout))
+        .with_state(state);
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    info!("Mock payment provider listening on {addr}");
+    let listener = TcpListener::bind(addr).await.unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
@@ -0,0 +1,121 @@
+use crate::{
+    AppState,
+    api_token::{ApiToken, ApiTokenId, ApiTokenScope, CreatedApiToken, NewApiToken},
+    auth::api_token::{generate_token, hash_token},
+    error::{AppResult, Error},
+    user::UserId,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub struct ApiTokenStore {
+    db: PgPool,
+}
+
+impl FromRequestParts<AppState> for ApiTokenStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgApiToken {
+    id: Uuid,
+    name: String,
+    scope: ApiTokenScope,
+    expiration: Option<OffsetDateTime>,
+    last_used: Option<OffsetDateTime>,
+    created: OffsetDateTime,
+}
+
+impl From<PgApiToken> for ApiToken {
+    fn from(pg: PgApiToken) -> Self {
+        Self {
+            id: pg.id.into(),
+            name: pg.name,
+            scope: pg.scope,
+            expiration: pg.expiration,
+            last_used: pg.last_used,
+            created: pg.created,
+        }
+    }
+}
+
+impl ApiTokenStore {
+    pub async fn get_for_user(&self, user_id: &UserId) -> AppResult<Vec<ApiToken>> {
+        Ok(sqlx::query_as!(
+            PgApiToken,
+            r#"
+            SELECT id,
+                   name,
+                   scope AS "scope: ApiTokenScope",
+                   expiration,
+                   last_used,
+                   created
+            FROM api_token
+            WHERE user_id = $1
+            ORDER BY created
+            "#,
+            **user_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    pub async fn create(&self, user_id: &UserId, new: NewApiToken) -> AppResult<CreatedApiToken> {
+        let secret = generate_token();
+
+        let token = sqlx::query_as!(
+            PgApiToken,
+            r#"
+            INSERT INTO api_token (id, user_id, name, token_hash, scope, expiration, created)
+            VALUES ($1, $2, $3, $4, $5, $6, now())
+            RETURNING id,
+                      name,
+                      scope AS "scope: ApiTokenScope",
+                      expiration,
+                      last_used,
+                      created
+            "#,
+            Uuid::now_v7(),
+            **user_id,
+            new.name,
+            hash_token(&secret),
+            new.scope as ApiTokenScope,
+            new.expiration,
+        )
+        .fetch_one(&self.db)
+        .await?
+        .into();
+
+        Ok(CreatedApiToken { token, secret })
+    }
+
+    pub async fn delete(&self, user_id: &UserId, id: &ApiTokenId) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM api_token WHERE id = $1 AND user_id = $2
+            "#,
+            **id,
+            **user_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{
+    AppState,
+    attendance::{Attendance, AttendanceSummary, CheckInToken, Scan, ScanResult, ScanStatus},
+    auth::check_in::CheckInSigner,
+    error::{AppResult, Error},
+    event::{EventId, RegistrationId},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub struct AttendanceStore {
+    db: PgPool,
+    signer: CheckInSigner,
+}
+
+impl FromRequestParts<AppState> for AttendanceStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+            signer: state.check_in_signer(),
+        })
+    }
+}
+
+impl AttendanceStore {
+    pub fn check_in_token(&self, registration_id: &RegistrationId) -> CheckInToken {
+        CheckInToken {
+            token: self.signer.sign(registration_id),
+        }
+    }
+
+    /// Checks in the scanned registrations of the event and marks them as attended.
+    ///
+    /// Scans can be uploaded more than once, e.g. when a scanner lost its connection,
+    /// the earliest scan of a registration is kept as the check-in time.
+    pub async fn check_in(
+        &self,
+        event_id: &EventId,
+        scans: Vec<Scan>,
+    ) -> AppResult<Vec<ScanResult>> {
+        let now = OffsetDateTime::now_utc();
+        let verified: Vec<_> = scans
+            .into_iter()
+            .map(|scan| {
+                let registration_id = self.signer.verify(&scan.token);
+                // Scanners with a wrong clock cannot check people in ahead of time
+                let scanned = scan.scanned.unwrap_or(now).min(now);
+                (scan.token, registration_id, scanned)
+            })
+            .collect();
+
+        let mut earliest: HashMap<Uuid, OffsetDateTime> = HashMap::new();
+        for (_, registration_id, scanned) in &verified {
+            if let Some(registration_id) = registration_id {
+                earliest
+                    .entry(**registration_id)
+                    .and_modify(|time| *time = (*time).min(*scanned))
+                    .or_insert(*scanned);
+            }
+        }
+        let (registration_ids, times): (Vec<Uuid>, Vec<OffsetDateTime>) =
+            earliest.into_iter().unzip();
+
+        let mut tx = self.db.begin().await?;
+        let registrations = sqlx::query!(
+            r#"
+            SELECT registration_id,
+                   checked_in,
+                   waiting_list_position IS NULL AND cancelled IS NULL AS "placed!"
+            FROM event_registration
+            WHERE event_id = $1 AND registration_id = ANY($2)
+            FOR UPDATE
+            "#,
+            **event_id,
+            &registration_ids
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE event_registration r
+            SET checked_in = least(r.checked_in, s.scanned),
+                attended = true,
+                updated = now()
+            FROM unnest($2::uuid[], $3::timestamptz[]) AS s(registration_id, scanned)
+            WHERE r.event_id = $1
+              AND r.registration_id = s.registration_id
+              AND r.waiting_list_position IS NULL
+              AND r.cancelled IS NULL
+            "#,
+            **event_id,
+            &registration_ids,
+            &times
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let mut checked_in: HashSet<Uuid> = registrations
+            .iter()
+            .filter(|r| r.checked_in.is_some())
+            .map(|r| r.registration_id)
+            .collect();
+        let of_event: HashSet<Uuid> = registrations.iter().map(|r| r.registration_id).collect();
+        let placed: HashSet<Uuid> = registrations
+            .iter()
+            .filter(|r| r.placed)
+            .map(|r| r.registration_id)
+            .collect();
+
+        Ok(verified
+            .into_iter()
+            .map(|(token, registration_id, _)| {
+                let registration_id = registration_id.filter(|id| of_event.contains(id));
+                let status = match &registration_id {
+                    None => ScanStatus::Invalid,
+                    Some(id) if !placed.contains(id) => ScanStatus::Rejected,
+                    Some(id) if checked_in.insert(**id) => ScanStatus::CheckedIn,
+                    Some(_) => ScanStatus::AlreadyCheckedIn,
+                };
+                ScanResult {
+                    token,
+                    registration_id,
+                    status,
+                }
+            })
+            .collect())
+    }
+
+    /// Registrations that do not belong to the event are ignored
+    pub async fn set_attendance(
+        &self,
+        event_id: &EventId,
+        attendance: Vec<Attendance>,
+    ) -> AppResult<()> {
+        let (registration_ids, attended): (Vec<Uuid>, Vec<Option<bool>>) = attendance
+            .into_iter()
+            .map(|a| (*a.registration_id, a.attended))
+            .unzip();
+
+        sqlx::query!(
+            r#"
+            UPDATE event_registration r
+            SET attended = a.attended,
+                updated = now()
+            FROM unnest($2::uuid[], $3::bool[]) AS a(registration_id, attended)
+            WHERE r.event_id = $1 AND r.registration_id = a.registration_id
+              AND r.cancelled IS NULL
+            "#,
+            **event_id,
+            &registration_ids,
+            &attended as &[Option<bool>]
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_summary(&self, event_id: &EventId) -> AppResult<AttendanceSummary> {
+        Ok(sqlx::query_as!(
+            AttendanceSummary,
+            r#"
+            SELECT count(*) as "participants!",
+                   count(checked_in) as "checked_in!",
+                   count(*) FILTER (WHERE attended) as "attended!",
+                   count(*) FILTER (WHERE NOT attended) as "absent!",
+                   count(*) FILTER (WHERE attended IS NULL) as "unknown!"
+            FROM event_registration
+            WHERE event_id = $1 AND waiting_list_position IS NULL AND cancelled IS NULL
+            "#,
+            **event_id
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{
+    AppState,
+    carpool::{CarAssignment, Carpool, CarpoolCar, Passenger},
+    error::{AppResult, Error},
+    event::{Car, EventId, Guest, RegistrationId},
+    user::BasicUser,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub struct CarpoolStore {
+    db: PgPool,
+}
+
+impl FromRequestParts<AppState> for CarpoolStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgTraveller {
+    registration_id: Uuid,
+    user_id: Option<Uuid>,
+    first_name: Option<String>,
+    infix: Option<String>,
+    last_name: Option<String>,
+    guests: Value,
+    places: i32,
+    car_seats: Option<i32>,
+    car_departure: Option<String>,
+    driver_registration_id: Option<Uuid>,
+}
+
+impl TryFrom<PgTraveller> for Passenger {
+    type Error = Error;
+
+    fn try_from(pg: PgTraveller) -> Result<Self, Self::Error> {
+        let guests: Vec<Guest> = serde_json::from_value(pg.guests)?;
+        Ok(Self {
+            registration_id: pg.registration_id.into(),
+            user: pg.user_id.zip(pg.first_name).zip(pg.last_name).map(
+                |((id, first_name), last_name)| BasicUser {
+                    id: id.into(),
+                    first_name,
+                    infix: pg.infix,
+                    last_name,
+                },
+            ),
+            guests: guests.into_iter().map(|guest| guest.name).collect(),
+            places: pg.places,
+        })
+    }
+}
+
+impl CarpoolStore {
+    /// Cars and passengers among the placed registrations, the waiting list does not travel
+    pub async fn get_carpool(&self, event_id: &EventId) -> AppResult<Carpool> {
+        let travellers = sqlx::query_as!(
+            PgTraveller,
+            r#"
+            SELECT r.registration_id,
+                   u.id AS "user_id?",
+                   u.first_name AS "first_name?",
+                   u.infix,
+                   u.last_name AS "last_name?",
+                   r.guests,
+                   r.places,
+                   r.car_seats,
+                   r.car_departure,
+                   r.driver_registration_id
+            FROM event_registration r
+                LEFT JOIN "user" u ON r.user_id = u.id
+            WHERE r.event_id = $1
+              AND r.waiting_list_position IS NULL
+              AND r.cancelled IS NULL
+            ORDER BY r.created
+            "#,
+            **event_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let (drivers, passengers): (Vec<_>, Vec<_>) = travellers
+            .into_iter()
+            .partition(|traveller| traveller.car_seats.is_some());
+
+        let mut cars = Vec::with_capacity(drivers.len());
+        let mut index = HashMap::new();
+        for mut driver in drivers {
+            let car = Car {
+                seats: driver.car_seats.unwrap_or_default(),
+                departure: driver.car_departure.take().unwrap_or_default(),
+            };
+            index.insert(driver.registration_id, cars.len());
+            cars.push(CarpoolCar {
+                driver: driver.try_into()?,
+                free_seats: car.seats,
+                car,
+                passengers: Vec::new(),
+            });
+        }
+
+        let mut unassigned = Vec::new();
+        let mut seats_needed = 0;
+        for passenger in passengers {
+            let car = passenger
+                .driver_registration_id
+                .and_then(|driver| index.get(&driver).copied());
+            let passenger = Passenger::try_from(passenger)?;
+            seats_needed += passenger.places as i64;
+            match car {
+                Some(car) => {
+                    cars[car].free_seats -= passenger.places;
+                    cars[car].passengers.push(passenger);
+                }
+                None => unassigned.push(passenger),
+            }
+        }
+
+        let seats_offered = cars.iter().map(|car| car.car.seats as i64).sum();
+        Ok(Carpool {
+            cars,
+            unassigned,
+            seats_offered,
+            seats_needed,
+            shortfall: (seats_needed - seats_offered).max(0),
+        })
+    }
+
+    /// The car the registration drives or rides along in
+    pub async fn get_car(
+        &self,
+        event_id: &EventId,
+        registration_id: &RegistrationId,
+    ) -> AppResult<Option<CarpoolCar>> {
+        Ok(self
+            .get_carpool(event_id)
+            .await?
+            .cars
+            .into_iter()
+            .find(|car| {
+                car.driver.registration_id == *registration_id
+                    || car
+                        .passengers
+                        .iter()
+                        .any(|passenger| passenger.registration_id == *registration_id)
+            }))
+    }
+
+    /// Assigns passengers to drivers, either all assignments are applied or none
+    pub async fn assign_passengers(
+        &self,
+        event_id: &EventId,
+        assignments: Vec<CarAssignment>,
+    ) -> AppResult<()> {
+        let (registration_ids, drivers): (Vec<Uuid>, Vec<Option<Uuid>>) = assignments
+            .into_iter()
+            .map(|a| (*a.registration_id, a.driver.map(|driver| *driver)))
+            .unzip();
+
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE event_registration r
+            SET driver_registration_id = a.driver_registration_id,
+                updated = now()
+            FROM unnest($2::uuid[], $3::uuid[]) AS a(registration_id, driver_registration_id)
+            WHERE r.event_id = $1 AND r.registration_id = a.registration_id
+            "#,
+            **event_id,
+            &registration_ids,
+            &drivers as &[Option<Uuid>]
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let invalid = sqlx::query_scalar!(
+            r#"
+            SELECT count(*) AS "count!"
+            FROM event_registration p
+                JOIN event_registration d ON d.registration_id = p.driver_registration_id
+            WHERE p.event_id = $1
+              AND p.registration_id = ANY ($2)
+              AND (p.waiting_list_position IS NOT NULL
+                OR p.cancelled IS NOT NULL
+                OR p.car_seats IS NOT NULL
+                OR d.event_id <> $1
+                OR d.waiting_list_position IS NOT NULL
+                OR d.cancelled IS NOT NULL
+                OR d.car_seats IS NULL)
+            "#,
+            **event_id,
+            &registration_ids
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if invalid > 0 {
+            return Err(Error::BadRequest(
+                "Only placed participants without a car can ride with a placed driver",
+            ));
+        }
+
+        let overbooked = sqlx::query_scalar!(
+            r#"
+            SELECT count(*) AS "count!"
+            FROM (SELECT d.registration_id
+                  FROM event_registration d
+                      JOIN event_registration p ON p.driver_registration_id = d.registration_id
+                  WHERE d.registration_id = ANY ($1)
+                    AND p.waiting_list_position IS NULL
+                    AND p.cancelled IS NULL
+                  GROUP BY d.registration_id, d.car_seats
+                  HAVING sum(p.places) > d.car_seats) c
+            "#,
+            &drivers.into_iter().flatten().collect::<Vec<_>>()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if overbooked > 0 {
+            return Err(Error::BadRequest("Not enough seats in the car"));
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,349 @@
+use crate::{
+    AppState, Language,
+    certification::{
+        Certification, CertificationContent, CertificationId, UserCertification,
+        UserCertificationId,
+    },
+    error::{AppResult, Error},
+    event::EventId,
+    user::{BasicUser, UserId},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use sqlx::PgPool;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+pub struct CertificationStore {
+    db: PgPool,
+}
+
+impl FromRequestParts<AppState> for CertificationStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgCertification {
+    id: Uuid,
+    name_nl: String,
+    name_en: String,
+    description_nl: String,
+    description_en: String,
+    validity_months: Option<i32>,
+    created: OffsetDateTime,
+    updated: OffsetDateTime,
+}
+
+impl From<PgCertification> for Certification {
+    fn from(pg: PgCertification) -> Self {
+        Self {
+            id: pg.id.into(),
+            content: CertificationContent {
+                name: Language {
+                    en: pg.name_en,
+                    nl: pg.name_nl,
+                },
+                description: Language {
+                    en: pg.description_en,
+                    nl: pg.description_nl,
+                },
+                validity_months: pg.validity_months,
+            },
+            created: pg.created,
+            updated: pg.updated,
+        }
+    }
+}
+
+struct PgUserCertification {
+    id: Uuid,
+    certification_id: Uuid,
+    name_nl: String,
+    name_en: String,
+    user_id: Uuid,
+    first_name: String,
+    infix: Option<String>,
+    last_name: String,
+    event_id: Option<Uuid>,
+    issued: Date,
+    expires: Option<Date>,
+    issued_by: Option<Uuid>,
+}
+
+impl From<PgUserCertification> for UserCertification {
+    fn from(pg: PgUserCertification) -> Self {
+        Self {
+            id: pg.id.into(),
+            certification_id: pg.certification_id.into(),
+            name: Language {
+                en: pg.name_en,
+                nl: pg.name_nl,
+            },
+            user: BasicUser {
+                id: pg.user_id.into(),
+                first_name: pg.first_name,
+                infix: pg.infix,
+                last_name: pg.last_name,
+            },
+            event_id: pg.event_id.map(Into::into),
+            issued: pg.issued,
+            expires: pg.expires,
+            issued_by: pg.issued_by.map(Into::into),
+        }
+    }
+}
+
+impl CertificationStore {
+    pub async fn get_all(&self) -> AppResult<Vec<Certification>> {
+        Ok(sqlx::query_as!(
+            PgCertification,
+            r#"
+            SELECT id, name_nl, name_en, description_nl, description_en, validity_months, created, updated
+            FROM certification
+            ORDER BY name_en
+            "#
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    pub async fn create(&self, content: CertificationContent) -> AppResult<Certification> {
+        Ok(sqlx::query_as!(
+            PgCertification,
+            r#"
+            INSERT INTO certification (id, name_nl, name_en, description_nl, description_en, validity_months)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, name_nl, name_en, description_nl, description_en, validity_months, created, updated
+            "#,
+            Uuid::now_v7(),
+            content.name.nl,
+            content.name.en,
+            content.description.nl,
+            content.description.en,
+            content.validity_months
+        )
+        .fetch_one(&self.db)
+        .await?
+        .into())
+    }
+
+    /// Changing the validity does not affect certifications that were already issued
+    pub async fn update(
+        &self,
+        id: &CertificationId,
+        content: CertificationContent,
+    ) -> AppResult<Certification> {
+        Ok(sqlx::query_as!(
+            PgCertification,
+            r#"
+            UPDATE certification
+            SET name_nl = $2,
+                name_en = $3,
+                description_nl = $4,
+                description_en = $5,
+                validity_months = $6,
+                updated = now()
+            WHERE id = $1
+            RETURNING id, name_nl, name_en, description_nl, description_en, validity_months, created, updated
+            "#,
+            **id,
+            content.name.nl,
+            content.name.en,
+            content.description.nl,
+            content.description.en,
+            content.validity_months
+        )
+        .fetch_one(&self.db)
+        .await?
+        .into())
+    }
+
+    /// Also revokes the issued certifications,
+    /// events that required it no longer do
+    pub async fn delete(&self, id: &CertificationId) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE event
+            SET required_certifications = array_remove(required_certifications, $1)
+            WHERE $1 = ANY(required_certifications)
+            "#,
+            **id
+        )
+        .execute(&mut *tx)
+        .await?;
+        let result = sqlx::query!(r#"DELETE FROM certification WHERE id = $1"#, **id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn get_for_user(&self, user_id: &UserId) -> AppResult<Vec<UserCertification>> {
+        Ok(sqlx::query_as!(
+            PgUserCertification,
+            r#"
+            SELECT uc.id,
+                   uc.certification_id,
+                   c.name_nl,
+                   c.name_en,
+                   uc.user_id,
+                   u.first_name,
+                   u.infix,
+                   u.last_name,
+                   uc.event_id,
+                   uc.issued,
+                   uc.expires,
+                   uc.issued_by
+            FROM user_certification uc
+                JOIN certification c ON uc.certification_id = c.id
+                JOIN "user" u ON uc.user_id = u.id
+            WHERE uc.user_id = $1
+            ORDER BY uc.issued DESC
+            "#,
+            **user_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    pub async fn get_for_event(&self, event_id: &EventId) -> AppResult<Vec<UserCertification>> {
+        Ok(sqlx::query_as!(
+            PgUserCertification,
+            r#"
+            SELECT uc.id,
+                   uc.certification_id,
+                   c.name_nl,
+                   c.name_en,
+                   uc.user_id,
+                   u.first_name,
+                   u.infix,
+                   u.last_name,
+                   uc.event_id,
+                   uc.issued,
+                   uc.expires,
+                   uc.issued_by
+            FROM user_certification uc
+                JOIN certification c ON uc.certification_id = c.id
+                JOIN "user" u ON uc.user_id = u.id
+            WHERE uc.event_id = $1
+            ORDER BY c.name_en, u.first_name, u.last_name
+            "#,
+            **event_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Issues the certification to attendees of the course, dated at the end of the course.
+    /// Attendees that already received it for this course are skipped.
+    pub async fn issue(
+        &self,
+        event_id: &EventId,
+        certification_id: &CertificationId,
+        user_ids: Option<Vec<UserId>>,
+        issued_by: &UserId,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query_scalar!(
+            r#"SELECT id FROM certification WHERE id = $1"#,
+            **certification_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let attendees = sqlx::query_scalar!(
+            r#"
+            SELECT user_id as "user_id!"
+            FROM event_registration
+            WHERE event_id = $1
+              AND attended
+              AND user_id IS NOT NULL
+              AND waiting_list_position IS NULL
+              AND cancelled IS NULL
+            "#,
+            **event_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let recipients: Vec<Uuid> = match user_ids {
+            Some(user_ids) => {
+                let user_ids: Vec<Uuid> = user_ids.iter().map(|id| **id).collect();
+                if !user_ids.iter().all(|id| attendees.contains(id)) {
+                    return Err(Error::BadRequest(
+                        "Certifications can only be issued to attendees of the course",
+                    ));
+                }
+                user_ids
+            }
+            None => attendees,
+        };
+        let ids: Vec<Uuid> = recipients.iter().map(|_| Uuid::now_v7()).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO user_certification (id, certification_id, user_id, event_id, issued, expires, issued_by)
+            SELECT r.id,
+                   c.id,
+                   r.user_id,
+                   e.id,
+                   e.issued,
+                   e.issued + make_interval(months => c.validity_months),
+                   $5
+            FROM unnest($1::uuid[], $2::uuid[]) AS r(id, user_id),
+                 certification c,
+                 (SELECT id, (SELECT max(d) FROM unnest(end_dates) d)::date AS issued
+                  FROM event
+                  WHERE id = $4) e
+            WHERE c.id = $3
+            ON CONFLICT (certification_id, user_id, event_id) DO NOTHING
+            "#,
+            &ids,
+            &recipients,
+            **certification_id,
+            **event_id,
+            **issued_by
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke(&self, user_id: &UserId, id: &UserCertificationId) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"DELETE FROM user_certification WHERE id = $1 AND user_id = $2"#,
+            **id,
+            **user_id
+        )
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+}
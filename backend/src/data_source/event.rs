@@ -1,20 +1,27 @@
 use crate::{
     AppState, Language,
+    data_source::{Count, PaymentStore},
     error::Error,
-    wire::event::{Event, EventContent, EventId},
+    wire::event::{Event, EventContent, EventFilter, EventId, EventPeriod, EventStatus},
 };
 
 use crate::{
     auth::{role::Membership, session::Session},
     error::AppResult,
-    event::{Date, NewRegistration, Registration, RegistrationId},
+    event::{
+        Answer, AnswerSummary, Car, Date, LateCancellation, MembershipRule, NewRegistration,
+        OptionUsage, Question, QuestionSummary, QuestionType, Registration, RegistrationId,
+    },
+    event_organiser::{EventOrganiser, OrganiserRole},
+    file::FileId,
     location::{Location, LocationContent, LocationId},
+    payment::{PaymentStatus, Pricing},
     user::{BasicUser, UserId},
 };
 use axum::{extract::FromRequestParts, http::request::Parts};
-use sqlx::{PgConnection, PgPool};
+use sqlx::{FromRow, PgConnection, PgPool, Postgres, QueryBuilder};
 use time::OffsetDateTime;
-use tracing::error;
+use tracing::{debug, error, info};
 use uuid::Uuid;
 use validator::Validate;
 
@@ -22,6 +29,12 @@ pub struct EventStore {
     db: PgPool,
 }
 
+impl EventStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
 impl FromRequestParts<AppState> for EventStore {
     type Rejection = Error;
 
@@ -35,7 +48,7 @@ impl FromRequestParts<AppState> for EventStore {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, FromRow)]
 struct PgEvent {
     id: Uuid,
     location_id: Uuid,
@@ -55,10 +68,20 @@ struct PgEvent {
     end_dates: Vec<OffsetDateTime>,
     registration_start: Option<OffsetDateTime>,
     registration_end: Option<OffsetDateTime>,
+    cancellation_deadline: Option<OffsetDateTime>,
     registration_max: Option<i32>,
     waiting_list_max: Option<i32>,
-    is_published: bool,
+    max_guests: i32,
+    lottery: bool,
+    lottery_drawn: Option<OffsetDateTime>,
+    status: EventStatus,
+    publish_at: Option<OffsetDateTime>,
+    cancellation_reason: Option<String>,
+    series_id: Option<Uuid>,
     required_membership: Vec<Membership>,
+    required_certifications: Vec<Uuid>,
+    membership_rules: serde_json::Value,
+    pricing: serde_json::Value,
     event_type: String,
     questions: serde_json::Value,
     metadata: serde_json::Value,
@@ -117,34 +140,51 @@ where
             .map(|(start, end)| Date { start, end })
             .collect();
 
+        let content = EventContent {
+            name: Language {
+                en: pg.name_en,
+                nl: pg.name_nl,
+            },
+            image: pg.image.map(Into::into),
+            description: Language {
+                en: pg.description_en,
+                nl: pg.description_nl,
+            },
+            registration_period: pg.registration_start.map(|start| Date { start, end: pg.registration_end.expect("If a registration start exists in the DB, there must also be an registration end") }),
+            cancellation_deadline: pg.cancellation_deadline,
+            registration_max: pg.registration_max,
+            waiting_list_max: pg.waiting_list_max,
+            max_guests: pg.max_guests,
+            lottery: pg.lottery,
+            status: pg.status,
+            publish_at: pg.publish_at,
+            required_membership: pg.required_membership,
+            required_certifications: pg
+                .required_certifications
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            membership_rules: serde_json::from_value(pg.membership_rules)?,
+            pricing: serde_json::from_value(pg.pricing)?,
+            event_type: pg.event_type.parse()?,
+            dates,
+            questions: serde_json::from_value(pg.questions)?,
+            metadata: serde_json::from_value(pg.metadata)?,
+            location,
+            created_by: pg.created_by,
+        };
+
         Ok(Self {
             id: pg.id.into(),
             created: pg.created,
             updated: pg.updated,
             registration_count: pg.registration_count,
             waiting_list_count: pg.waiting_list_count,
-            content: EventContent {
-                name: Language {
-                    en: pg.name_en,
-                    nl: pg.name_nl,
-                },
-                image: pg.image.map(Into::into),
-                description: Language {
-                    en: pg.description_en,
-                    nl: pg.description_nl,
-                },
-                registration_period: pg.registration_start.map(|start| Date { start, end: pg.registration_end.expect("If a registration start exists in the DB, there must also be an registration end") }),
-                registration_max: pg.registration_max,
-                waiting_list_max: pg.waiting_list_max,
-                is_published: pg.is_published,
-                required_membership: pg.required_membership,
-                event_type: pg.event_type.parse()?,
-                dates,
-                questions: serde_json::from_value(pg.questions)?,
-                metadata: pg.metadata,
-                location,
-                created_by: pg.created_by,
-            },
+            cancellation_reason: pg.cancellation_reason,
+            series_id: pg.series_id.map(Into::into),
+            registration_windows: content.registration_windows(),
+            lottery_drawn: pg.lottery_drawn,
+            content,
         })
     }
 }
@@ -159,6 +199,15 @@ struct PgRegistration {
     attended: Option<bool>,
     waiting_list_position: Option<i32>,
     answers: serde_json::Value,
+    guests: serde_json::Value,
+    places: i32,
+    amount_due: i32,
+    payment_status: PaymentStatus,
+    cancellation_requested: Option<OffsetDateTime>,
+    checked_in: Option<OffsetDateTime>,
+    car_seats: Option<i32>,
+    car_departure: Option<String>,
+    driver_registration_id: Option<Uuid>,
     created: OffsetDateTime,
     updated: OffsetDateTime,
 }
@@ -184,23 +233,83 @@ impl TryFrom<PgRegistration> for Registration {
             attended: pg.attended,
             waiting_list_position: pg.waiting_list_position,
             answers: serde_json::from_value(pg.answers)?,
+            guests: serde_json::from_value(pg.guests)?,
+            places: pg.places,
+            amount_due: pg.amount_due,
+            payment_status: pg.payment_status,
+            cancellation_requested: pg.cancellation_requested,
+            checked_in: pg.checked_in,
+            car: pg
+                .car_seats
+                .zip(pg.car_departure)
+                .map(|(seats, departure)| Car { seats, departure }),
+            driver: pg.driver_registration_id.map(Into::into),
             created: pg.created,
             updated: pg.updated,
         })
     }
 }
 
+struct PgLateCancellation {
+    id: Uuid,
+    event_id: Uuid,
+    registration_id: Uuid,
+    user_id: Option<Uuid>,
+    first_name: Option<String>,
+    infix: Option<String>,
+    last_name: Option<String>,
+    amount_due: i32,
+    payment_status: PaymentStatus,
+    cancellation_requested: OffsetDateTime,
+    approved: OffsetDateTime,
+    approved_by: Option<Uuid>,
+}
+
+impl From<PgLateCancellation> for LateCancellation {
+    fn from(pg: PgLateCancellation) -> Self {
+        Self {
+            id: pg.id.into(),
+            event_id: pg.event_id.into(),
+            registration_id: pg.registration_id.into(),
+            user: pg.user_id.map(|user_id| BasicUser {
+                id: user_id.into(),
+                first_name: pg.first_name.unwrap_or_default(),
+                infix: pg.infix,
+                last_name: pg.last_name.unwrap_or_default(),
+            }),
+            amount_due: pg.amount_due,
+            payment_status: pg.payment_status,
+            cancellation_requested: pg.cancellation_requested,
+            approved: pg.approved,
+            approved_by: pg.approved_by.map(Into::into),
+        }
+    }
+}
+
 impl EventStore {
     pub async fn ensure_user_in_committee(
         &self,
         session: &Session,
         committee_id: &Uuid,
     ) -> AppResult<()> {
-        if crate::api::is_admin_or_board(session).is_ok() {
-            return Ok(());
+        if crate::api::is_admin_or_board(session).is_ok()
+            || self
+                .is_committee_member(session.user_id(), committee_id)
+                .await?
+        {
+            Ok(())
+        } else {
+            Err(Error::Unauthorized)
         }
+    }
 
-        let in_committee = sqlx::query_scalar!(
+    /// Current membership only, unlike ensure_user_in_committee the board is not included
+    pub async fn is_committee_member(
+        &self,
+        user_id: &UserId,
+        committee_id: &Uuid,
+    ) -> AppResult<bool> {
+        Ok(sqlx::query_scalar!(
             r#"
             SELECT EXISTS(
                 SELECT 1
@@ -210,17 +319,12 @@ impl EventStore {
                   AND "left" IS NULL
             )
             "#,
-            **session.user_id(),
+            **user_id,
             *committee_id
         )
         .fetch_one(&self.db)
         .await?
-        .unwrap_or(false);
-
-        if !in_committee {
-            return Err(Error::Unauthorized);
-        }
-        Ok(())
+        .unwrap_or(false))
     }
 
     pub async fn ensure_user_is_committee_chair(
@@ -256,10 +360,152 @@ impl EventStore {
         Ok(())
     }
 
+    /// Members of the owning committee, or organisers with one of the given roles
+    pub async fn ensure_event_organiser<T: Validate>(
+        &self,
+        session: &Session,
+        event: &Event<T>,
+        roles: &[OrganiserRole],
+    ) -> AppResult<()> {
+        if self
+            .ensure_user_in_committee(session, &event.content.created_by)
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+
+        match self
+            .get_organiser_role(&event.id, session.user_id())
+            .await?
+        {
+            Some(role) if roles.contains(&role) => Ok(()),
+            _ => Err(Error::Unauthorized),
+        }
+    }
+
+    /// The chair of the owning committee, or the lead organiser
+    pub async fn ensure_lead_organiser<T: Validate>(
+        &self,
+        session: &Session,
+        event: &Event<T>,
+    ) -> AppResult<()> {
+        if self
+            .ensure_user_is_committee_chair(session, &event.content.created_by)
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+
+        match self
+            .get_organiser_role(&event.id, session.user_id())
+            .await?
+        {
+            Some(OrganiserRole::Lead) => Ok(()),
+            _ => Err(Error::Unauthorized),
+        }
+    }
+
+    pub async fn get_organiser_role(
+        &self,
+        event_id: &EventId,
+        user_id: &UserId,
+    ) -> AppResult<Option<OrganiserRole>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT role as "role: OrganiserRole"
+            FROM event_organiser
+            WHERE event_id = $1 AND user_id = $2
+            "#,
+            **event_id,
+            **user_id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    pub async fn get_organisers(&self, event_id: &EventId) -> AppResult<Vec<EventOrganiser>> {
+        let organisers = sqlx::query!(
+            r#"
+            SELECT u.id, u.first_name, u.infix, u.last_name, o.role as "role: OrganiserRole"
+            FROM event_organiser o
+                JOIN "user" u ON o.user_id = u.id
+            WHERE o.event_id = $1
+            ORDER BY o.role, u.first_name, u.last_name
+            "#,
+            **event_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(organisers
+            .into_iter()
+            .map(|organiser| EventOrganiser {
+                user: BasicUser {
+                    id: organiser.id.into(),
+                    first_name: organiser.first_name,
+                    infix: organiser.infix,
+                    last_name: organiser.last_name,
+                },
+                role: organiser.role,
+            })
+            .collect())
+    }
+
+    /// Adds the organiser, or changes the role of an existing one
+    pub async fn set_organiser(
+        &self,
+        event_id: &EventId,
+        user_id: &UserId,
+        role: OrganiserRole,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO event_organiser (event_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (event_id, user_id) DO UPDATE SET role = excluded.role
+            "#,
+            **event_id,
+            **user_id,
+            role as OrganiserRole
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_organiser(&self, event_id: &EventId, user_id: &UserId) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"DELETE FROM event_organiser WHERE event_id = $1 AND user_id = $2"#,
+            **event_id,
+            **user_id
+        )
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+
     pub async fn create_event(
         &self,
-        mut event: EventContent<LocationId>,
+        event: EventContent<LocationId>,
     ) -> AppResult<Event<Location>> {
+        let mut conn = self.db.acquire().await?;
+        let event_id = Self::insert_event(&mut conn, event, None).await?;
+
+        self.get_event(&event_id, true).await
+    }
+
+    pub(super) async fn insert_event(
+        conn: &mut PgConnection,
+        mut event: EventContent<LocationId>,
+        series_id: Option<&Uuid>,
+    ) -> AppResult<EventId> {
         let event_id = Uuid::now_v7();
 
         event.dates.sort_by_key(|date| date.start);
@@ -288,15 +534,23 @@ impl EventStore {
                                registration_end,
                                registration_max,
                                waiting_list_max,
-                               is_published,
+                               status,
+                               publish_at,
                                required_membership,
                                event_type,
                                questions,
                                metadata,
                                created_by,
+                               series_id,
+                               membership_rules,
+                               lottery,
+                               cancellation_deadline,
+                               pricing,
+                               required_certifications,
+                               max_guests,
                                created,
                                updated)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15::membership[], $16, $17, $18, $19, now(), now())
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16::membership[], $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, now(), now())
             "#,
             event_id,
             *event.location,
@@ -311,15 +565,23 @@ impl EventStore {
             event.registration_period.as_ref().map(|r| r.end),
             event.registration_max,
             event.waiting_list_max,
-            event.is_published,
+            event.status as EventStatus,
+            event.publish_at,
             event.required_membership as Vec<Membership>,
             Into::<&str>::into(event.event_type),
             serde_json::to_value(event.questions)?,
-            event.metadata,
-            event.created_by
-        ).execute(&self.db).await?;
+            serde_json::to_value(event.metadata)?,
+            event.created_by,
+            series_id,
+            serde_json::to_value(event.membership_rules)?,
+            event.lottery,
+            event.cancellation_deadline,
+            serde_json::to_value(event.pricing)?,
+            &event.required_certifications.iter().map(|id| **id).collect::<Vec<_>>(),
+            event.max_guests
+        ).execute(conn).await?;
 
-        self.get_event(&event_id.into(), true).await
+        Ok(event_id.into())
     }
 
     pub async fn get_event(
@@ -348,23 +610,33 @@ impl EventStore {
                    e.end_dates,
                    e.registration_start,
                    e.registration_end,
+                   e.cancellation_deadline,
                    e.registration_max,
                    e.waiting_list_max,
-                   e.is_published,
+                   e.max_guests,
+                   e.lottery,
+                   (SELECT d.drawn FROM lottery_draw d WHERE d.event_id = e.id) AS lottery_drawn,
+                   e.status as "status: EventStatus",
+                   e.publish_at,
+                   e.cancellation_reason,
+                   e.series_id,
                    e.required_membership as "required_membership:Vec<Membership>",
+                   e.required_certifications,
+                   e.membership_rules,
+                   e.pricing,
                    e.event_type,
                    e.questions,
                    e.metadata,
-                   count(r.registration_id) FILTER ( WHERE r.waiting_list_position IS NULL ) as "registration_count!",
+                   coalesce(sum(r.places) FILTER ( WHERE r.waiting_list_position IS NULL ), 0) as "registration_count!",
                    count(r.registration_id) FILTER ( WHERE r.waiting_list_position IS NOT NULL ) as "waiting_list_count!",
                    e.created_by,
                    e.created,
                    e.updated
             FROM event e
                 JOIN location l ON e.location_id = l.id
-                LEFT JOIN event_registration r ON r.event_id = e.id
+                LEFT JOIN event_registration r ON r.event_id = e.id AND r.cancelled IS NULL
             WHERE e.id = $1 AND
-                  (e.is_published OR $2)
+                  (e.status <> 'draft' OR $2)
             GROUP BY e.id, l.id
             "#,
             **id,
@@ -375,10 +647,113 @@ impl EventStore {
             .try_into()
     }
 
-    // TODO filter for events in the past
-    pub async fn get_events(&self, display_hidden: bool) -> AppResult<Vec<Event<Location>>> {
-        sqlx::query_as!(
-            PgEvent,
+    /// Drafts are only included if `display_hidden` is set,
+    /// or if `viewer` is a member of the committee that created them or organises them
+    fn push_event_filter(
+        query: &mut QueryBuilder<'_, Postgres>,
+        display_hidden: bool,
+        viewer: Option<&UserId>,
+        filter: &EventFilter,
+    ) {
+        match filter.status {
+            Some(status) => query.push(" WHERE e.status = ").push_bind(status),
+            None => query.push(" WHERE e.status <> 'archived'"),
+        };
+        if !display_hidden {
+            let viewer = viewer.map(|id| **id);
+            query
+                .push(
+                    r#" AND (e.status <> 'draft'
+                        OR e.created_by IN (SELECT committee_id
+                                            FROM user_committee
+                                            WHERE "left" IS NULL AND user_id = "#,
+                )
+                .push_bind(viewer)
+                .push(") OR e.id IN (SELECT event_id FROM event_organiser WHERE user_id = ")
+                .push_bind(viewer)
+                .push("))");
+        }
+        if let Some(period) = filter.period {
+            query
+                .push(" AND (e.end_dates[array_upper(e.end_dates, 1)] >= now()) = ")
+                .push_bind(period == EventPeriod::Upcoming);
+        }
+        if let Some(from) = filter.from {
+            query
+                .push(" AND e.end_dates[array_upper(e.end_dates, 1)] >= ")
+                .push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            query.push(" AND e.start_dates[1] <= ").push_bind(to);
+        }
+        if let Some(event_type) = filter.event_type {
+            query
+                .push(" AND lower(e.event_type) = lower(")
+                .push_bind(Into::<&str>::into(event_type))
+                .push(")");
+        }
+        if let Some(committee) = &filter.committee {
+            query.push(" AND e.created_by = ").push_bind(**committee);
+        }
+        if let Some(location) = &filter.location {
+            query.push(" AND e.location_id = ").push_bind(**location);
+        }
+        if let Some(membership) = filter.membership {
+            query
+                .push(" AND ")
+                .push_bind(membership)
+                .push(" = ANY(e.required_membership)");
+        }
+        if let Some(pattern) = filter.search_pattern() {
+            query.push(" AND (");
+            let mut fields = query.separated(" OR ");
+            for field in [
+                "e.name_nl",
+                "e.name_en",
+                "e.description_nl",
+                "e.description_en",
+            ] {
+                fields
+                    .push(field)
+                    .push_unseparated(" ILIKE ")
+                    .push_bind_unseparated(pattern.clone());
+            }
+            query.push(")");
+        }
+        if let Some(has_free_spots) = filter.has_free_spots {
+            query
+                .push(
+                    r#" AND (e.registration_max IS NULL OR e.registration_max > (
+                        SELECT coalesce(sum(fr.places), 0) FROM event_registration fr
+                        WHERE fr.event_id = e.id AND fr.waiting_list_position IS NULL
+                          AND fr.cancelled IS NULL)) = "#,
+                )
+                .push_bind(has_free_spots);
+        }
+    }
+
+    pub async fn count_events(
+        &self,
+        display_hidden: bool,
+        viewer: Option<&UserId>,
+        filter: &EventFilter,
+    ) -> AppResult<Count> {
+        let mut query = QueryBuilder::new("SELECT count(*) FROM event e");
+        Self::push_event_filter(&mut query, display_hidden, viewer, filter);
+
+        Ok(Count {
+            count: query.build_query_scalar().fetch_one(&self.db).await?,
+        })
+    }
+
+    /// Only paged if the filter has a limit
+    pub async fn get_events(
+        &self,
+        display_hidden: bool,
+        viewer: Option<&UserId>,
+        filter: &EventFilter,
+    ) -> AppResult<Vec<Event<Location>>> {
+        let mut query = QueryBuilder::new(
             r#"
             SELECT e.id,
                    l.id as location_id,
@@ -398,27 +773,50 @@ impl EventStore {
                    e.end_dates,
                    e.registration_start,
                    e.registration_end,
+                   e.cancellation_deadline,
                    e.registration_max,
                    e.waiting_list_max,
-                   e.is_published,
-                   e.required_membership as "required_membership:Vec<Membership>",
+                   e.max_guests,
+                   e.lottery,
+                   (SELECT d.drawn FROM lottery_draw d WHERE d.event_id = e.id) AS lottery_drawn,
+                   e.status,
+                   e.publish_at,
+                   e.cancellation_reason,
+                   e.series_id,
+                   e.required_membership,
+                   e.required_certifications,
+                   e.membership_rules,
+                   e.pricing,
                    e.event_type,
                    e.questions,
                    e.metadata,
-                   count(r.registration_id) FILTER ( WHERE r.waiting_list_position IS NULL ) as "registration_count!",
-                   count(r.registration_id) FILTER ( WHERE r.waiting_list_position IS NOT NULL ) as "waiting_list_count!",
+                   coalesce(sum(r.places) FILTER ( WHERE r.waiting_list_position IS NULL ), 0) as registration_count,
+                   count(r.registration_id) FILTER ( WHERE r.waiting_list_position IS NOT NULL ) as waiting_list_count,
                    e.created_by,
                    e.created,
                    e.updated
             FROM event e
                 JOIN location l ON e.location_id = l.id
-                LEFT JOIN event_registration r ON r.event_id = e.id
-            WHERE e.is_published OR $1
-            GROUP BY e.id, l.id
-            ORDER BY start_dates[1]
-            "#,
-            display_hidden
-        )
+                LEFT JOIN event_registration r ON r.event_id = e.id AND r.cancelled IS NULL"#,
+        );
+        Self::push_event_filter(&mut query, display_hidden, viewer, filter);
+        query.push(" GROUP BY e.id, l.id");
+        // Past events are listed most recent first
+        if filter.period == Some(EventPeriod::Past) {
+            query.push(" ORDER BY e.start_dates[1] DESC");
+        } else {
+            query.push(" ORDER BY e.start_dates[1]");
+        }
+        if let Some(limit) = filter.limit {
+            query
+                .push(" LIMIT ")
+                .push_bind(limit)
+                .push(" OFFSET ")
+                .push_bind(filter.offset);
+        }
+
+        query
+            .build_query_as::<PgEvent>()
             .fetch_all(&self.db)
             .await?
             .into_iter()
@@ -429,8 +827,19 @@ impl EventStore {
     pub async fn update_event(
         &self,
         id: &EventId,
-        mut updated: EventContent<LocationId>,
+        updated: EventContent<LocationId>,
     ) -> AppResult<Event<Location>> {
+        let mut conn = self.db.acquire().await?;
+        Self::write_event(&mut conn, id, updated).await?;
+
+        self.get_event(id, true).await
+    }
+
+    pub(super) async fn write_event(
+        conn: &mut PgConnection,
+        id: &EventId,
+        mut updated: EventContent<LocationId>,
+    ) -> AppResult<()> {
         updated.dates.sort_by_key(|date| date.start);
         let (start_dates, end_dates) = updated.dates.into_iter().fold(
             (Vec::new(), Vec::new()),
@@ -456,12 +865,20 @@ impl EventStore {
                 registration_end = $11,
                 registration_max = $12,
                 waiting_list_max = $13,
-                is_published = $14,
-                required_membership = $15::membership[],
-                event_type = $16,
-                questions = $17,
-                metadata = $18,
-                created_by = $19,
+                status = $14::event_status,
+                publish_at = $15,
+                cancellation_reason = CASE WHEN $14::event_status = 'cancelled' THEN cancellation_reason END,
+                required_membership = $16::membership[],
+                event_type = $17,
+                questions = $18,
+                metadata = $19,
+                created_by = $20,
+                membership_rules = $21,
+                lottery = $22,
+                cancellation_deadline = $23,
+                pricing = $24,
+                required_certifications = $25,
+                max_guests = $26,
                 updated = now()
             WHERE id = $1
             "#,
@@ -478,23 +895,116 @@ impl EventStore {
             updated.registration_period.as_ref().map(|r| r.end),
             updated.registration_max,
             updated.waiting_list_max,
-            updated.is_published,
+            updated.status as EventStatus,
+            updated.publish_at,
             updated.required_membership as Vec<Membership>,
             Into::<&str>::into(updated.event_type),
             serde_json::to_value(updated.questions)?,
-            updated.metadata,
+            serde_json::to_value(updated.metadata)?,
             updated.created_by,
+            serde_json::to_value(updated.membership_rules)?,
+            updated.lottery,
+            updated.cancellation_deadline,
+            serde_json::to_value(updated.pricing)?,
+            &updated
+                .required_certifications
+                .iter()
+                .map(|id| **id)
+                .collect::<Vec<_>>(),
+            updated.max_guests,
         )
-        .execute(&self.db)
+        .execute(&mut *conn)
         .await?;
 
+        Self::update_amounts_due(conn, id).await?;
+
+        Ok(())
+    }
+
+    /// Cancels the event and notifies everyone that signed up, including the waiting list.
+    /// Registrations are kept, their online payments are refunded.
+    pub async fn cancel_event(
+        &self,
+        payments: &PaymentStore,
+        id: &EventId,
+        reason: &str,
+    ) -> AppResult<Event<Location>> {
+        let mut tx = self.db.begin().await?;
+        Self::cancel(&mut tx, payments, id, reason).await?;
+        tx.commit().await?;
+
         self.get_event(id, true).await
     }
 
-    pub async fn delete_event(&self, id: &EventId) -> AppResult<()> {
+    pub(super) async fn cancel(
+        tx: &mut PgConnection,
+        payments: &PaymentStore,
+        id: &EventId,
+        reason: &str,
+    ) -> AppResult<()> {
+        payments.refund_event(tx, id).await?;
+        sqlx::query!(
+            r#"
+            UPDATE event
+            SET status = 'cancelled',
+                cancellation_reason = $2,
+                updated = now()
+            WHERE id = $1
+            "#,
+            **id,
+            reason
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO notification (id, user_id, event_id, message_nl, message_en, created)
+            SELECT gen_random_uuid(),
+                   r.user_id,
+                   e.id,
+                   format('%s is geannuleerd: %s', e.name_nl, $2::text),
+                   format('%s has been cancelled: %s', e.name_en, $2::text),
+                   now()
+            FROM event_registration r
+                JOIN event e ON r.event_id = e.id
+            WHERE e.id = $1
+              AND r.user_id IS NOT NULL
+              AND r.cancelled IS NULL
+            "#,
+            **id,
+            reason
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Publishes all drafts whose `publish_at` has passed
+    pub async fn publish_scheduled(&self) -> AppResult<u64> {
+        Ok(sqlx::query!(
+            r#"
+            UPDATE event
+            SET status = 'published',
+                updated = now()
+            WHERE status = 'draft'
+              AND publish_at <= now()
+            "#
+        )
+        .execute(&self.db)
+        .await?
+        .rows_affected())
+    }
+
+    /// Deletes the event with its registrations, their online payments are refunded
+    pub async fn delete_event(&self, payments: &PaymentStore, id: &EventId) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        payments.refund_event(&mut tx, id).await?;
         sqlx::query!(r#"DELETE FROM event WHERE id = $1"#, **id)
-            .execute(&self.db)
+            .execute(&mut *tx)
             .await?;
+        tx.commit().await?;
         Ok(())
     }
 
@@ -545,6 +1055,139 @@ impl EventStore {
         }
     }
 
+    /// The first registration on the waiting list that fits in the free places,
+    /// whose membership quota is not yet reached and whose chosen options have room,
+    /// with the places it takes
+    async fn next_in_line(
+        tx: &mut PgConnection,
+        event_id: &EventId,
+    ) -> AppResult<Option<(RegistrationId, i32)>> {
+        struct Waiting {
+            registration_id: Uuid,
+            places: i32,
+            membership: Membership,
+            registered: i64,
+            answers: serde_json::Value,
+        }
+
+        let event = sqlx::query!(
+            r#"
+            SELECT e.membership_rules,
+                   e.questions,
+                   e.registration_max,
+                   (SELECT coalesce(sum(r.places), 0)
+                    FROM event_registration r
+                    WHERE r.event_id = e.id
+                      AND r.waiting_list_position IS NULL
+                      AND r.cancelled IS NULL) AS "registered!",
+                   e.lottery AND NOT EXISTS(
+                       SELECT 1 FROM lottery_draw d WHERE d.event_id = e.id
+                   ) AS "lottery_pending!"
+            FROM event e
+            WHERE e.id = $1
+            "#,
+            **event_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        // Before the draw, everyone on the waiting list is in the lottery pool
+        if event.lottery_pending {
+            return Ok(None);
+        }
+        let rules: Vec<MembershipRule> = serde_json::from_value(event.membership_rules)?;
+        let questions: Vec<Question> = serde_json::from_value(event.questions)?;
+        let usage = Self::option_usage(tx, event_id, &questions, None).await?;
+
+        let waiting = sqlx::query_as!(
+            Waiting,
+            r#"
+            WITH registrant AS (
+                SELECT r.registration_id,
+                       r.waiting_list_position,
+                       r.places,
+                       r.answers,
+                       coalesce(u.membership, 'non_member') AS membership
+                FROM event_registration r
+                    LEFT JOIN "user" u ON r.user_id = u.id
+                WHERE r.event_id = $1
+                  AND r.cancelled IS NULL
+            )
+            SELECT w.registration_id,
+                   w.places,
+                   w.answers,
+                   w.membership as "membership!: Membership",
+                   (SELECT coalesce(sum(o.places), 0)
+                    FROM registrant o
+                    WHERE o.membership = w.membership
+                      AND o.waiting_list_position IS NULL) AS "registered!"
+            FROM registrant w
+            WHERE w.waiting_list_position IS NOT NULL
+            ORDER BY w.waiting_list_position
+            "#,
+            **event_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for w in waiting {
+            let places = w.places as i64;
+            let fits = event
+                .registration_max
+                .is_none_or(|max| event.registered + places <= max as i64)
+                && rules
+                    .iter()
+                    .find(|rule| rule.membership == w.membership)
+                    .and_then(|rule| rule.quota)
+                    .is_none_or(|quota| w.registered + places <= quota as i64);
+            if !fits {
+                continue;
+            }
+            let answers: Vec<Answer> = serde_json::from_value(w.answers)?;
+            // Registrations whose chosen option is full keep waiting
+            if usage.has_room(&questions, &answers) {
+                return Ok(Some((w.registration_id.into(), w.places)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Places taken, excluding the waiting list, by registrations of people with the given membership
+    pub async fn count_registrations_with_membership(
+        &self,
+        id: &EventId,
+        membership: Membership,
+    ) -> AppResult<i64> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT coalesce(sum(r.places), 0) AS "count!"
+            FROM event_registration r
+                LEFT JOIN "user" u ON r.user_id = u.id
+            WHERE r.event_id = $1
+              AND r.waiting_list_position IS NULL
+              AND r.cancelled IS NULL
+              AND coalesce(u.membership, 'non_member') = $2
+            "#,
+            **id,
+            membership as Membership
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    /// Places taken by registrations on the waiting list
+    pub async fn count_waiting_list_places(&self, id: &EventId) -> AppResult<i64> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT coalesce(sum(places), 0) AS "count!"
+            FROM event_registration
+            WHERE event_id = $1 AND waiting_list_position IS NOT NULL
+            "#,
+            **id
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
     async fn update_waiting_list_position(
         tx: &mut PgConnection,
         registration_id: &RegistrationId,
@@ -625,6 +1268,7 @@ impl EventStore {
                 JOIN "user" u ON ar.user_id = u.id
             WHERE ar.event_id = $1
               AND ar.waiting_list_position IS NULL
+              AND ar.cancelled IS NULL
             "#,
             **id
         )
@@ -643,13 +1287,23 @@ impl EventStore {
                    u.infix,
                    u.last_name,
                    answers,
+                   guests,
+                   places,
                    attended,
                    waiting_list_position,
+                   amount_due,
+                   payment_status as "payment_status: PaymentStatus",
+                   cancellation_requested,
+                   checked_in,
+                   car_seats,
+                   car_departure,
+                   driver_registration_id,
                    u.created,
                    u.updated
             FROM event_registration r
                 JOIN "user" u ON r.user_id = u.id
             WHERE user_id = $1
+              AND cancelled IS NULL
             "#,
             **user_id
         )
@@ -682,14 +1336,24 @@ impl EventStore {
                e.end_dates,
                e.registration_start,
                e.registration_end,
+               e.cancellation_deadline,
                e.registration_max,
                e.waiting_list_max,
-               e.is_published,
+               e.max_guests,
+               e.lottery,
+               (SELECT d.drawn FROM lottery_draw d WHERE d.event_id = e.id) AS lottery_drawn,
+               e.status as "status: EventStatus",
+               e.publish_at,
+               e.cancellation_reason,
+               e.series_id,
                e.required_membership as "required_membership:Vec<Membership>",
+               e.required_certifications,
+               e.membership_rules,
+               e.pricing,
                e.event_type,
                e.questions,
                e.metadata,
-               count(r2.registration_id) FILTER (WHERE r2.waiting_list_position IS NULL) as "registration_count!",
+               coalesce(sum(r2.places) FILTER (WHERE r2.waiting_list_position IS NULL), 0) as "registration_count!",
                count(r2.registration_id) FILTER (WHERE r2.waiting_list_position IS NOT NULL) as "waiting_list_count!",
                e.created_by,
                e.created,
@@ -697,9 +1361,10 @@ impl EventStore {
         FROM event e
             JOIN location l ON e.location_id = l.id
             JOIN event_registration r ON r.event_id = e.id
-            LEFT JOIN event_registration r2 ON r2.event_id = e.id
+            LEFT JOIN event_registration r2 ON r2.event_id = e.id AND r2.cancelled IS NULL
         WHERE r.user_id = $1
-          AND e.is_published
+          AND r.cancelled IS NULL
+          AND e.status <> 'draft'
         GROUP BY e.id, l.id
         "#,
         **user_id
@@ -722,13 +1387,23 @@ impl EventStore {
                    u.infix,
                    u.last_name as "last_name?",
                    answers,
+                   guests,
+                   places,
                    attended,
                    waiting_list_position,
+                   r.amount_due,
+                   r.payment_status as "payment_status: PaymentStatus",
+                   r.cancellation_requested,
+                   r.checked_in,
+                   r.car_seats,
+                   r.car_departure,
+                   r.driver_registration_id,
                    r.created,
                    r.updated
             FROM event_registration r
                 LEFT JOIN "user" u ON r.user_id = u.id
             WHERE r.event_id = $1
+              AND r.cancelled IS NULL
             "#,
             **id
         )
@@ -756,13 +1431,23 @@ impl EventStore {
                    u.infix,
                    u.last_name as "last_name?",
                    answers,
+                   guests,
+                   places,
                    attended,
                    waiting_list_position,
+                   r.amount_due,
+                   r.payment_status as "payment_status: PaymentStatus",
+                   r.cancellation_requested,
+                   r.checked_in,
+                   r.car_seats,
+                   r.car_departure,
+                   r.driver_registration_id,
                    r.created,
                    r.updated
             FROM event_registration r
                 LEFT JOIN "user" u ON r.user_id = u.id
             WHERE registration_id = $1
+              AND r.cancelled IS NULL
             "#,
             **registration_id,
         )
@@ -772,27 +1457,187 @@ impl EventStore {
         .try_into()
     }
 
-    pub async fn new_registration(
+    /// Aggregates the answers of registrations that have a place, per question
+    pub async fn get_question_summary(
         &self,
         event_id: &EventId,
-        user_id: Option<UserId>,
-        new: NewRegistration,
+        questions: &[Question],
+    ) -> AppResult<Vec<QuestionSummary>> {
+        summarise_answers(
+            &self.db,
+            r#"
+            SELECT answers, created AS sort
+            FROM event_registration
+            WHERE event_id = $1
+              AND waiting_list_position IS NULL
+              AND cancelled IS NULL
+            "#,
+            event_id,
+            questions,
+        )
+        .await
+    }
+
+    /// Events with a registration that answers a question with the file
+    pub async fn events_with_answer_file(&self, file_id: &FileId) -> AppResult<Vec<EventId>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT r.event_id
+            FROM event_registration r
+                CROSS JOIN jsonb_array_elements(r.answers) a
+            WHERE a ->> 'answer' = $1
+            "#,
+            file_id.to_string()
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Whether all files were uploaded by the user
+    pub async fn files_uploaded_by(&self, file_ids: &[Uuid], user_id: &UserId) -> AppResult<bool> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT count(*) as "count!" FROM file WHERE id = ANY($1) AND created_by = $2"#,
+            file_ids,
+            **user_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+        Ok(count as usize == file_ids.len())
+    }
+
+    /// Names of the certifications the event requires that the user does not hold,
+    /// or that expire before the event starts
+    pub async fn missing_certifications(
+        &self,
+        event_id: &EventId,
+        user_id: Option<&UserId>,
+    ) -> AppResult<Vec<String>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT c.name_en
+            FROM event e
+                JOIN certification c ON c.id = ANY(e.required_certifications)
+            WHERE e.id = $1
+              AND NOT EXISTS (
+                SELECT 1
+                FROM user_certification uc
+                WHERE uc.certification_id = c.id
+                  AND uc.user_id = $2
+                  AND (uc.expires IS NULL
+                    OR uc.expires >= coalesce((SELECT min(d) FROM unnest(e.start_dates) d), now())::date)
+              )
+            ORDER BY c.name_en
+            "#,
+            **event_id,
+            user_id.map(|id| **id)
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    pub async fn new_registration(
+        &self,
+        event_id: &EventId,
+        user_id: Option<UserId>,
+        new: NewRegistration,
     ) -> AppResult<Registration> {
+        let mut tx = self.db.begin().await?;
+        let registration_id = Self::insert_registration(&mut tx, event_id, user_id, new).await?;
+        tx.commit().await?;
+
+        self.get_registration(&registration_id).await
+    }
+
+    pub(super) async fn insert_registration(
+        tx: &mut PgConnection,
+        event_id: &EventId,
+        user_id: Option<UserId>,
+        new: NewRegistration,
+    ) -> AppResult<RegistrationId> {
+        Self::lock_event(tx, event_id).await?;
+        if new.waiting_list_position.is_none() {
+            Self::ensure_option_room(tx, event_id, &new.answers, None).await?;
+        }
         let registration_id = sqlx::query_scalar!(
             r#"
-            INSERT INTO event_registration (registration_id, event_id, user_id, waiting_list_position, answers, created, updated)
-            VALUES  ($1, $2, $3, $4, $5, now(), now())
+            INSERT INTO event_registration (registration_id, event_id, user_id, waiting_list_position, answers, guests, car_seats, car_departure, created, updated)
+            VALUES  ($1, $2, $3, $4, $5, $6, $7, $8, now(), now())
             RETURNING registration_id
             "#,
             Uuid::now_v7(),
             **event_id,
             user_id.map(|u| *u),
             new.waiting_list_position,
-            serde_json::to_value(new.answers)?
+            serde_json::to_value(new.answers)?,
+            serde_json::to_value(new.guests)?,
+            new.car.as_ref().map(|car| car.seats),
+            new.car.map(|car| car.departure)
         )
-            .fetch_one(&self.db)
+            .fetch_one(&mut *tx)
             .await?;
-        self.get_registration(&registration_id.into()).await
+        Self::update_amounts_due(tx, event_id).await?;
+
+        Ok(registration_id.into())
+    }
+
+    /// Recalculates what the unpaid registrations of the event owe,
+    /// after the pricing or the answers changed
+    async fn update_amounts_due(conn: &mut PgConnection, event_id: &EventId) -> AppResult<()> {
+        let event = sqlx::query!(
+            r#"SELECT pricing, questions FROM event WHERE id = $1"#,
+            **event_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        let pricing: Pricing = serde_json::from_value(event.pricing)?;
+        let questions: Vec<Question> = serde_json::from_value(event.questions)?;
+
+        let registrations = sqlx::query!(
+            r#"
+            SELECT r.registration_id,
+                   r.answers,
+                   r.places,
+                   coalesce(u.membership, 'non_member') as "membership!: Membership"
+            FROM event_registration r
+                LEFT JOIN "user" u ON r.user_id = u.id
+            WHERE r.event_id = $1 AND r.payment_status = 'unpaid' AND r.cancelled IS NULL
+            "#,
+            **event_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut registration_ids = Vec::with_capacity(registrations.len());
+        let mut amounts = Vec::with_capacity(registrations.len());
+        for registration in registrations {
+            let answers: Vec<Answer> = serde_json::from_value(registration.answers)?;
+            registration_ids.push(registration.registration_id);
+            amounts.push(pricing.amount_due(
+                &questions,
+                registration.membership,
+                &answers,
+                registration.places - 1,
+            ));
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE event_registration r
+            SET amount_due = a.amount
+            FROM unnest($1::uuid[], $2::int[]) AS a(registration_id, amount)
+            WHERE r.registration_id = a.registration_id
+              AND r.amount_due <> a.amount
+            "#,
+            &registration_ids,
+            &amounts
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
     }
 
     pub async fn update_registration(
@@ -801,78 +1646,536 @@ impl EventStore {
         updated: NewRegistration,
     ) -> AppResult<Registration> {
         let mut tx = self.db.begin().await?;
-        sqlx::query!(
+        let event_id = Self::lock_event_of(&mut tx, registration_id).await?;
+        if updated.waiting_list_position.is_none() {
+            Self::ensure_option_room(&mut tx, &event_id, &updated.answers, Some(registration_id))
+                .await?;
+        }
+        let registration = sqlx::query!(
             r#"
-            UPDATE event_registration
+            UPDATE event_registration r
             SET answers = $1,
                 attended = $2,
+                guests = $4,
+                car_seats = $5,
+                car_departure = $6,
+                -- drivers do not ride along with someone else
+                driver_registration_id = CASE WHEN $5::int IS NULL THEN r.driver_registration_id END,
                 updated = now()
-            WHERE registration_id = $3
+            FROM (SELECT places FROM event_registration WHERE registration_id = $3 FOR UPDATE) old
+            WHERE r.registration_id = $3
+            RETURNING r.waiting_list_position, old.places AS old_places, r.places
             "#,
             serde_json::to_value(updated.answers)?,
             updated.attended,
             **registration_id,
+            serde_json::to_value(updated.guests)?,
+            updated.car.as_ref().map(|car| car.seats),
+            updated.car.as_ref().map(|car| car.departure.clone()),
         )
-        .execute(&mut *tx)
+        .fetch_one(&mut *tx)
         .await?;
+        if updated.car.is_none() {
+            // Passengers of someone that no longer drives need another car
+            sqlx::query!(
+                r#"
+                UPDATE event_registration
+                SET driver_registration_id = NULL
+                WHERE driver_registration_id = $1
+                "#,
+                **registration_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
 
         Self::update_waiting_list_position(&mut tx, registration_id, updated.waiting_list_position)
             .await?;
+        // Guests that no longer join free their places
+        if registration.waiting_list_position.is_none() && updated.waiting_list_position.is_none() {
+            Self::fill_freed_places(
+                &mut tx,
+                &event_id,
+                registration.old_places - registration.places,
+            )
+            .await?;
+        }
+        Self::update_amounts_due(&mut tx, &event_id).await?;
 
         tx.commit().await?;
 
         self.get_registration(registration_id).await
     }
 
-    pub async fn delete_registration(&self, registration_id: &RegistrationId) -> AppResult<()> {
+    /// Deletes the registration and refunds its online payments
+    pub async fn delete_registration(
+        &self,
+        payments: &PaymentStore,
+        registration_id: &RegistrationId,
+    ) -> AppResult<()> {
         let mut tx = self.db.begin().await?;
+        // Refunded before the delete unlinks the payments from the registration
+        Self::lock_event_of(&mut tx, registration_id).await?;
+        payments
+            .refund_registration(&mut tx, registration_id)
+            .await?;
+        Self::remove_registration(&mut tx, registration_id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
 
-        if Self::remove_from_waiting_list(&mut tx, registration_id)
+    /// Deletes the registration, the waiting list takes the freed places
+    async fn remove_registration(
+        tx: &mut PgConnection,
+        registration_id: &RegistrationId,
+    ) -> AppResult<()> {
+        let event_id = Self::lock_event_of(tx, registration_id).await?;
+        let was_waiting = Self::remove_from_waiting_list(tx, registration_id)
             .await?
-            .is_none()
-        {
-            let event_id = sqlx::query_scalar!(
-                r#"SELECT event_id FROM event_registration WHERE registration_id = $1"#,
-                **registration_id,
-            )
-            .fetch_one(&mut *tx)
-            .await?;
+            .is_some();
 
-            sqlx::query!(
-                r#"
-                UPDATE event_registration
-                SET waiting_list_position = null
-                WHERE event_id = $1
-                  AND waiting_list_position = 0
-                "#,
-                event_id
-            )
-            .execute(&mut *tx)
-            .await?;
+        let removed = sqlx::query!(
+            r#"
+            DELETE FROM event_registration WHERE registration_id = $1 RETURNING places
+            "#,
+            **registration_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
 
-            sqlx::query!(
-                r#"
-                UPDATE event_registration
-                SET waiting_list_position = waiting_list_position - 1
-                WHERE event_id = $1
-                "#,
-                event_id
-            )
-            .execute(&mut *tx)
-            .await?;
+        if !was_waiting {
+            Self::fill_freed_places(tx, &event_id, removed.places).await?;
         }
 
+        Ok(())
+    }
+
+    /// Cancels the registration but keeps the row, the waiting list takes the freed places
+    async fn cancel_registration(
+        tx: &mut PgConnection,
+        registration_id: &RegistrationId,
+    ) -> AppResult<()> {
+        let event_id = Self::lock_event_of(tx, registration_id).await?;
+        let was_waiting = Self::remove_from_waiting_list(tx, registration_id)
+            .await?
+            .is_some();
+
+        let cancelled = sqlx::query!(
+            r#"
+            UPDATE event_registration
+            SET cancelled = now(),
+                driver_registration_id = NULL,
+                updated = now()
+            WHERE registration_id = $1
+            RETURNING places
+            "#,
+            **registration_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        // Passengers of a cancelled driver need another car
         sqlx::query!(
             r#"
-            DELETE FROM event_registration WHERE registration_id = $1
+            UPDATE event_registration
+            SET driver_registration_id = NULL
+            WHERE driver_registration_id = $1
             "#,
             **registration_id
         )
         .execute(&mut *tx)
         .await?;
 
+        if !was_waiting {
+            Self::fill_freed_places(tx, &event_id, cancelled.places).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Serialises changes to the registrations of the event, so places and option capacities
+    /// are checked against a consistent state. Taken before any registration is touched,
+    /// to avoid deadlocks with other changes of the same event.
+    async fn lock_event(tx: &mut PgConnection, event_id: &EventId) -> AppResult<()> {
+        sqlx::query!("SELECT id FROM event WHERE id = $1 FOR UPDATE", **event_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(Error::NotFound)?;
+        Ok(())
+    }
+
+    async fn lock_event_of(
+        tx: &mut PgConnection,
+        registration_id: &RegistrationId,
+    ) -> AppResult<EventId> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT e.id
+            FROM event e
+                JOIN event_registration r ON r.event_id = e.id
+            WHERE r.registration_id = $1
+            FOR UPDATE OF e
+            "#,
+            **registration_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::NotFound)?
+        .into())
+    }
+
+    /// Placed answers of the event, except those of the given registration
+    async fn option_usage(
+        tx: &mut PgConnection,
+        event_id: &EventId,
+        questions: &[Question],
+        exclude: Option<&RegistrationId>,
+    ) -> AppResult<OptionUsage> {
+        let placed = sqlx::query_scalar!(
+            r#"
+            SELECT answers
+            FROM event_registration
+            WHERE event_id = $1
+              AND waiting_list_position IS NULL
+              AND cancelled IS NULL
+              AND ($2::uuid IS NULL OR registration_id <> $2)
+            "#,
+            **event_id,
+            exclude.map(|id| **id)
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<Vec<Vec<Answer>>, _>>()?;
+
+        Ok(OptionUsage::new(
+            questions,
+            placed.iter().map(Vec::as_slice),
+        ))
+    }
+
+    /// Chosen options with a capacity must still have room, requires the event lock
+    async fn ensure_option_room(
+        tx: &mut PgConnection,
+        event_id: &EventId,
+        answers: &[Answer],
+        current: Option<&RegistrationId>,
+    ) -> AppResult<()> {
+        let questions =
+            sqlx::query_scalar!("SELECT questions FROM event WHERE id = $1", **event_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        let questions: Vec<Question> = serde_json::from_value(questions)?;
+        if !OptionUsage::is_limited(&questions, answers) {
+            return Ok(());
+        }
+
+        let usage = Self::option_usage(tx, event_id, &questions, current).await?;
+        if !usage.has_room(&questions, answers) {
+            debug!(
+                event_id = event_id.to_string(),
+                "A chosen option is fully booked"
+            );
+            return Err(Error::BadRequest("A chosen option is fully booked"));
+        }
+        Ok(())
+    }
+
+    /// Moves waiting registrations that fit into the freed places
+    async fn fill_freed_places(
+        tx: &mut PgConnection,
+        event_id: &EventId,
+        mut freed: i32,
+    ) -> AppResult<()> {
+        while freed > 0
+            && let Some((next, places)) = Self::next_in_line(tx, event_id).await?
+        {
+            Self::remove_from_waiting_list(tx, &next).await?;
+            freed -= places;
+        }
+
+        Ok(())
+    }
+
+    pub async fn request_cancellation(&self, registration_id: &RegistrationId) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE event_registration
+            SET cancellation_requested = coalesce(cancellation_requested, now()),
+                updated = now()
+            WHERE registration_id = $1
+            "#,
+            **registration_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn reject_cancellation(&self, registration_id: &RegistrationId) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE event_registration
+            SET cancellation_requested = NULL,
+                updated = now()
+            WHERE registration_id = $1
+              AND cancellation_requested IS NOT NULL
+              AND cancelled IS NULL
+            "#,
+            **registration_id
+        )
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Marks the registration as cancelled and records who approved it,
+    /// the row is kept with its amount due and payment status so the treasurer can charge the costs
+    pub async fn approve_cancellation(
+        &self,
+        registration_id: &RegistrationId,
+        approved_by: &UserId,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO late_cancellation (id, event_id, registration_id, user_id, cancellation_requested, approved_by)
+            SELECT $1, event_id, registration_id, user_id, cancellation_requested, $3
+            FROM event_registration
+            WHERE registration_id = $2
+              AND cancellation_requested IS NOT NULL
+              AND cancelled IS NULL
+            RETURNING id
+            "#,
+            Uuid::now_v7(),
+            **registration_id,
+            **approved_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::cancel_registration(&mut tx, registration_id).await?;
+
         tx.commit().await?;
 
         Ok(())
     }
+
+    pub async fn get_late_cancellations(
+        &self,
+        event_id: &EventId,
+    ) -> AppResult<Vec<LateCancellation>> {
+        Ok(sqlx::query_as!(
+            PgLateCancellation,
+            r#"
+            SELECT c.id,
+                   c.event_id,
+                   c.registration_id,
+                   c.user_id,
+                   u.first_name as "first_name?",
+                   u.infix,
+                   u.last_name as "last_name?",
+                   r.amount_due,
+                   r.payment_status as "payment_status: PaymentStatus",
+                   c.cancellation_requested,
+                   c.approved,
+                   c.approved_by
+            FROM late_cancellation c
+                JOIN event_registration r ON c.registration_id = r.registration_id
+                LEFT JOIN "user" u ON c.user_id = u.id
+            WHERE c.event_id = $1
+            ORDER BY c.approved
+            "#,
+            **event_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+}
+
+/// How often drafts with a `publish_at` are checked for publication
+const SCHEDULED_PUBLISH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Periodically publishes scheduled drafts, runs until the process exits
+pub async fn run_scheduled_publishing(db: PgPool) {
+    let store = EventStore::new(db);
+    let mut interval = tokio::time::interval(SCHEDULED_PUBLISH_INTERVAL);
+    loop {
+        interval.tick().await;
+        match store.publish_scheduled().await {
+            Ok(0) => {}
+            Ok(count) => info!("Published {count} scheduled events"),
+            Err(err) => error!("Failed to publish scheduled events: {err}"),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct AnswerCount {
+    question_id: String,
+    value: String,
+    count: i64,
+}
+
+#[derive(FromRow)]
+struct AnswerStats {
+    question_id: String,
+    answered: i64,
+    min: Option<f64>,
+    max: Option<f64>,
+    average: Option<f64>,
+    answers: Vec<String>,
+}
+
+/// The answers to summarise with the kind of their question, `{responses}` selects the `answers`
+/// of the responses and the `sort` order to list them in, for the event bound as $1
+const SUMMARISED_ANSWERS: &str = r#"
+    WITH answer AS (
+        SELECT q.kind, q.id AS question_id, a -> 'answer' AS answer, a ->> 'answer' AS text, r.sort
+        FROM ({responses}) r
+            CROSS JOIN jsonb_array_elements(CASE jsonb_typeof(r.answers) WHEN 'array' THEN r.answers END) a
+            JOIN unnest($2::text[], $3::text[]) AS q(id, kind) ON q.id = a ->> 'questionId'
+        WHERE a ->> 'answer' <> ''
+    )
+"#;
+
+/// A JSON array of strings, checkbox answers given before the question changed type are skipped
+const CHECKBOXES_PATTERN: &str = r#"^\s*\[\s*("([^"\\[:cntrl:]]|\\["\\/bfnrt])*"\s*(,\s*"([^"\\[:cntrl:]]|\\["\\/bfnrt])*"\s*)*)?\]\s*$"#;
+
+/// The numbers `f64::from_str` accepts, apart from infinity and NaN
+const NUMBER_PATTERN: &str = r"^[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)([eE][+-]?0*[0-9]{1,3})?$";
+
+/// Aggregates the answers of the responses per question, see [`SUMMARISED_ANSWERS`]
+pub(crate) async fn summarise_answers(
+    db: &PgPool,
+    responses: &str,
+    event_id: &EventId,
+    questions: &[Question],
+) -> AppResult<Vec<QuestionSummary>> {
+    let (ids, kinds): (Vec<String>, Vec<&str>) = questions
+        .iter()
+        .map(|question| {
+            let kind = match question.question_type {
+                QuestionType::MultipleChoice { .. } | QuestionType::Boolean => "options",
+                QuestionType::Checkboxes { .. } => "checkboxes",
+                QuestionType::Number | QuestionType::Range { .. } => "number",
+                QuestionType::Text | QuestionType::Date | QuestionType::File => "list",
+            };
+            (question.id.to_string(), kind)
+        })
+        .unzip();
+    let answers = SUMMARISED_ANSWERS.replace("{responses}", responses);
+
+    // Checkbox answers are a JSON array of the chosen options, each is counted separately
+    let counts = sqlx::query_as::<_, AnswerCount>(&format!(
+        r#"
+        {answers}
+        SELECT question_id, chosen.value, count(*) AS count
+        FROM answer
+            CROSS JOIN LATERAL (
+                SELECT jsonb_array_elements_text(text::jsonb)
+                WHERE kind = 'checkboxes' AND text ~ $4
+                UNION ALL
+                SELECT text
+                WHERE kind = 'options'
+            ) AS chosen(value)
+        GROUP BY 1, 2
+        "#
+    ))
+    .bind(**event_id)
+    .bind(&ids)
+    .bind(&kinds)
+    .bind(CHECKBOXES_PATTERN)
+    .fetch_all(db)
+    .await?;
+
+    // Numbers are rounded and capped, so they fit a float8 without failing the summary
+    let stats = sqlx::query_as::<_, AnswerStats>(&format!(
+        r#"
+        {answers}
+        SELECT question_id,
+               count(*) AS answered,
+               min(number.value)::float8 AS min,
+               max(number.value)::float8 AS max,
+               avg(number.value)::float8 AS average,
+               coalesce(array_agg(text ORDER BY sort) FILTER ( WHERE kind = 'list' ), '{{}}') AS answers
+        FROM answer
+            CROSS JOIN LATERAL (
+                SELECT CASE
+                           WHEN kind = 'number' AND jsonb_typeof(answer) IN ('string', 'number') AND text ~ $4
+                               THEN CASE WHEN abs(text::numeric) < 1e308 THEN round(text::numeric, 300) END
+                       END
+            ) AS number(value)
+        GROUP BY 1
+        "#
+    ))
+    .bind(**event_id)
+    .bind(&ids)
+    .bind(&kinds)
+    .bind(NUMBER_PATTERN)
+    .fetch_all(db)
+    .await?;
+
+    Ok(questions
+        .iter()
+        .map(|question| {
+            let id = question.id.to_string();
+            let stats = stats.iter().find(|stats| stats.question_id == id);
+            let count_of = |value: &str| {
+                counts
+                    .iter()
+                    .filter(|count| count.question_id == id && count.value == value)
+                    .map(|count| count.count)
+                    .sum::<i64>()
+            };
+
+            let answers = match &question.question_type {
+                QuestionType::MultipleChoice { options } | QuestionType::Checkboxes { options } => {
+                    AnswerSummary::Options {
+                        counts: options
+                            .iter()
+                            .map(|option| {
+                                // Both languages were counted if the labels are the same
+                                if option.label.en == option.label.nl {
+                                    count_of(&option.label.en)
+                                } else {
+                                    count_of(&option.label.en) + count_of(&option.label.nl)
+                                }
+                            })
+                            .collect(),
+                    }
+                }
+                QuestionType::Boolean => AnswerSummary::Boolean {
+                    yes: count_of("true"),
+                    no: count_of("false"),
+                },
+                QuestionType::Number | QuestionType::Range { .. } => AnswerSummary::Number {
+                    min: stats.and_then(|stats| stats.min),
+                    max: stats.and_then(|stats| stats.max),
+                    average: stats.and_then(|stats| stats.average),
+                },
+                QuestionType::Text | QuestionType::Date | QuestionType::File => {
+                    AnswerSummary::List {
+                        answers: stats.map(|stats| stats.answers.clone()).unwrap_or_default(),
+                    }
+                }
+            };
+
+            QuestionSummary {
+                question_id: question.id,
+                answered: stats.map_or(0, |stats| stats.answered),
+                answers,
+            }
+        })
+        .collect())
 }
@@ -0,0 +1,423 @@
+use crate::{
+    AppState,
+    data_source::{PaymentStore, event::EventStore},
+    error::{AppResult, Error},
+    event::{Date, EventContent, EventId, EventStatus, NewRegistration, RegistrationId},
+    event_series::{
+        EventSeries, EventSeriesContent, EventSeriesId, RecurrenceFrequency, RecurrenceRule,
+        SkippedDate,
+    },
+    location::LocationId,
+    user::UserId,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use sqlx::{Connection, PgConnection, PgPool};
+use time::OffsetDateTime;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+/// Occurrences are calculated in local time, so they keep their time of day across DST changes
+const TIME_ZONE: &str = "Europe/Amsterdam";
+
+/// Roughly two years of weekly occurrences
+const MAX_OCCURRENCES: i64 = 104;
+
+const REMOVED_FROM_SERIES_REASON: &str = "Removed from the series";
+const SERIES_DELETED_REASON: &str = "The series was deleted";
+
+pub struct EventSeriesStore {
+    db: PgPool,
+}
+
+impl FromRequestParts<AppState> for EventSeriesStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgEventSeries {
+    id: Uuid,
+    frequency: RecurrenceFrequency,
+    until: time::Date,
+    exceptions: Vec<time::Date>,
+    content: serde_json::Value,
+    occurrences: Vec<Uuid>,
+    created: OffsetDateTime,
+    updated: OffsetDateTime,
+}
+
+impl TryFrom<PgEventSeries> for EventSeries {
+    type Error = Error;
+
+    fn try_from(pg: PgEventSeries) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: pg.id.into(),
+            rule: RecurrenceRule {
+                frequency: pg.frequency,
+                until: pg.until,
+                exceptions: pg.exceptions.into_iter().map(SkippedDate).collect(),
+            },
+            content: serde_json::from_value(pg.content)?,
+            occurrences: pg.occurrences.into_iter().map(Into::into).collect(),
+            created: pg.created,
+            updated: pg.updated,
+        })
+    }
+}
+
+struct Occurrence {
+    day: time::Date,
+    start_dates: Vec<OffsetDateTime>,
+    end_dates: Vec<OffsetDateTime>,
+}
+
+impl Occurrence {
+    fn content(&self, series: &EventContent<LocationId>) -> EventContent<LocationId> {
+        EventContent {
+            dates: self
+                .start_dates
+                .iter()
+                .zip(&self.end_dates)
+                .map(|(start, end)| Date {
+                    start: *start,
+                    end: *end,
+                })
+                .collect(),
+            ..series.clone()
+        }
+    }
+
+    fn is_future(&self) -> bool {
+        self.start_dates
+            .first()
+            .is_some_and(|start| *start > OffsetDateTime::now_utc())
+    }
+}
+
+struct ExistingOccurrence {
+    id: Uuid,
+    day: time::Date,
+    status: EventStatus,
+    has_registrations: bool,
+}
+
+impl EventSeriesStore {
+    /// Shifts the dates of the first occurrence by the recurrence interval,
+    /// skipping exceptions and stopping at the end of the series
+    async fn occurrences(
+        conn: &mut PgConnection,
+        rule: &RecurrenceRule,
+        first: &[Date],
+    ) -> AppResult<Vec<Occurrence>> {
+        let Some(first_start) = first.iter().map(|date| date.start).min() else {
+            return Ok(Vec::new());
+        };
+        let interval = rule.frequency.interval_days();
+        // One extra, as the local date of the first start may differ from the UTC date
+        let count = (rule.until - first_start.date()).whole_days() / interval as i64 + 1;
+        if count > MAX_OCCURRENCES {
+            return Err(Error::BadRequest("Too many occurrences in the series"));
+        }
+
+        let (start_dates, end_dates): (Vec<_>, Vec<_>) =
+            first.iter().map(|date| (date.start, date.end)).unzip();
+        let exceptions: Vec<time::Date> = rule.exceptions.iter().map(|date| date.0).collect();
+
+        Ok(sqlx::query_as!(
+            Occurrence,
+            r#"
+            SELECT ((min(d.s) AT TIME ZONE $4) + make_interval(days => o.n * $3))::date AS "day!",
+                   array_agg(((d.s AT TIME ZONE $4) + make_interval(days => o.n * $3)) AT TIME ZONE $4
+                             ORDER BY d.s) AS "start_dates!",
+                   array_agg(((d.e AT TIME ZONE $4) + make_interval(days => o.n * $3)) AT TIME ZONE $4
+                             ORDER BY d.s) AS "end_dates!"
+            FROM generate_series(0, $5::int) AS o(n),
+                 unnest($1::timestamptz[], $2::timestamptz[]) AS d(s, e)
+            GROUP BY o.n
+            HAVING ((min(d.s) AT TIME ZONE $4) + make_interval(days => o.n * $3))::date <= $6
+               AND ((min(d.s) AT TIME ZONE $4) + make_interval(days => o.n * $3))::date <> ALL($7::date[])
+            ORDER BY o.n
+            "#,
+            &start_dates,
+            &end_dates,
+            interval,
+            TIME_ZONE,
+            count as i32,
+            rule.until,
+            &exceptions
+        )
+        .fetch_all(conn)
+        .await?)
+    }
+
+    pub async fn get_one(&self, id: &EventSeriesId) -> AppResult<EventSeries> {
+        sqlx::query_as!(
+            PgEventSeries,
+            r#"
+            SELECT s.id,
+                   s.frequency as "frequency: RecurrenceFrequency",
+                   s.until,
+                   s.exceptions,
+                   s.content,
+                   array(
+                       SELECT e.id FROM event e WHERE e.series_id = s.id ORDER BY e.start_dates[1]
+                   ) AS "occurrences!",
+                   s.created,
+                   s.updated
+            FROM event_series s
+            WHERE s.id = $1
+            "#,
+            **id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .try_into()
+    }
+
+    /// Occurrences that have not started yet, in chronological order
+    pub async fn get_future_occurrences(&self, id: &EventSeriesId) -> AppResult<Vec<EventId>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT id FROM event
+            WHERE series_id = $1 AND start_dates[1] > now()
+            ORDER BY start_dates[1]
+            "#,
+            **id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Stores the checked registrations for the occurrences in one transaction.
+    /// Occurrences that refuse the registration, e.g. because a chosen option is fully booked,
+    /// are returned as skipped, any other error aborts all registrations.
+    pub async fn register(
+        &self,
+        user_id: Option<UserId>,
+        registrations: Vec<(EventId, NewRegistration)>,
+    ) -> AppResult<(Vec<RegistrationId>, Vec<EventId>)> {
+        let mut tx = self.db.begin().await?;
+        let mut registered = Vec::with_capacity(registrations.len());
+        let mut skipped = Vec::new();
+
+        for (event_id, new) in registrations {
+            // A failed statement aborts the transaction, so each insert gets a savepoint
+            let mut savepoint = Connection::begin(&mut *tx).await?;
+            match EventStore::insert_registration(&mut savepoint, &event_id, user_id.clone(), new)
+                .await
+            {
+                Ok(registration_id) => {
+                    savepoint.commit().await?;
+                    registered.push(registration_id);
+                }
+                Err(err) if err.is_refusal() => {
+                    debug!(
+                        event_id = event_id.to_string(),
+                        "Skipping occurrence in series registration: {err}"
+                    );
+                    savepoint.rollback().await?;
+                    skipped.push(event_id);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        tx.commit().await?;
+        Ok((registered, skipped))
+    }
+
+    pub async fn create(&self, new: EventSeriesContent) -> AppResult<EventSeries> {
+        let id = Uuid::now_v7();
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO event_series (id, frequency, until, exceptions, content, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            id,
+            new.rule.frequency as RecurrenceFrequency,
+            new.rule.until,
+            &new.rule
+                .exceptions
+                .iter()
+                .map(|date| date.0)
+                .collect::<Vec<_>>(),
+            serde_json::to_value(&new.content)?,
+            new.content.created_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for occurrence in Self::occurrences(&mut tx, &new.rule, &new.content.dates).await? {
+            EventStore::insert_event(&mut tx, occurrence.content(&new.content), Some(&id)).await?;
+        }
+
+        tx.commit().await?;
+
+        self.get_one(&id.into()).await
+    }
+
+    /// Applies the changes to all future occurrences. Occurrences that no longer fit
+    /// the rule are removed, or cancelled if people already registered.
+    /// Past occurrences are left untouched.
+    pub async fn update(
+        &self,
+        payments: &PaymentStore,
+        id: &EventSeriesId,
+        updated: EventSeriesContent,
+    ) -> AppResult<EventSeries> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE event_series
+            SET frequency = $2,
+                until = $3,
+                exceptions = $4,
+                content = $5,
+                created_by = $6,
+                updated = now()
+            WHERE id = $1
+            RETURNING id
+            "#,
+            **id,
+            updated.rule.frequency as RecurrenceFrequency,
+            updated.rule.until,
+            &updated
+                .rule
+                .exceptions
+                .iter()
+                .map(|date| date.0)
+                .collect::<Vec<_>>(),
+            serde_json::to_value(&updated.content)?,
+            updated.content.created_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut existing = sqlx::query_as!(
+            ExistingOccurrence,
+            r#"
+            SELECT e.id,
+                   (e.start_dates[1] AT TIME ZONE $2)::date AS "day!",
+                   e.status as "status: EventStatus",
+                   EXISTS(
+                       SELECT 1 FROM event_registration r WHERE r.event_id = e.id
+                   ) AS "has_registrations!"
+            FROM event e
+            WHERE e.series_id = $1 AND e.start_dates[1] > now()
+            "#,
+            **id,
+            TIME_ZONE
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let occurrences = Self::occurrences(&mut tx, &updated.rule, &updated.content.dates).await?;
+        for occurrence in occurrences.iter().filter(|o| o.is_future()) {
+            let mut content = occurrence.content(&updated.content);
+
+            match existing.iter().position(|e| e.day == occurrence.day) {
+                Some(index) => {
+                    let current = existing.swap_remove(index);
+                    // Individually cancelled occurrences stay cancelled
+                    if current.status == EventStatus::Cancelled {
+                        content.status = EventStatus::Cancelled;
+                    }
+                    EventStore::write_event(&mut tx, &current.id.into(), content).await?;
+                }
+                None => {
+                    EventStore::insert_event(&mut tx, content, Some(id)).await?;
+                }
+            }
+        }
+
+        for removed in existing {
+            if removed.has_registrations {
+                if removed.status != EventStatus::Cancelled {
+                    EventStore::cancel(
+                        &mut tx,
+                        payments,
+                        &removed.id.into(),
+                        REMOVED_FROM_SERIES_REASON,
+                    )
+                    .await?;
+                }
+            } else {
+                sqlx::query!(r#"DELETE FROM event WHERE id = $1"#, removed.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        info!(series_id = id.to_string(), "Updated event series");
+        self.get_one(id).await
+    }
+
+    /// Deletes the series and its future occurrences, past occurrences are kept.
+    /// Future occurrences with registrations are cancelled instead, which notifies
+    /// the participants and refunds their online payments.
+    pub async fn delete(&self, payments: &PaymentStore, id: &EventSeriesId) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let future = sqlx::query_as!(
+            ExistingOccurrence,
+            r#"
+            SELECT e.id,
+                   (e.start_dates[1] AT TIME ZONE $2)::date AS "day!",
+                   e.status as "status: EventStatus",
+                   EXISTS(
+                       SELECT 1 FROM event_registration r WHERE r.event_id = e.id
+                   ) AS "has_registrations!"
+            FROM event e
+            WHERE e.series_id = $1 AND e.start_dates[1] > now()
+            FOR UPDATE
+            "#,
+            **id,
+            TIME_ZONE
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for occurrence in future {
+            if occurrence.has_registrations {
+                if occurrence.status != EventStatus::Cancelled {
+                    EventStore::cancel(
+                        &mut tx,
+                        payments,
+                        &occurrence.id.into(),
+                        SERIES_DELETED_REASON,
+                    )
+                    .await?;
+                }
+            } else {
+                sqlx::query!(r#"DELETE FROM event WHERE id = $1"#, occurrence.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        let result = sqlx::query!(r#"DELETE FROM event_series WHERE id = $1"#, **id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    AppState,
+    committee::CommitteeId,
+    error::{AppResult, Error},
+    event_template::{EventTemplate, EventTemplateContent, EventTemplateId},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub struct EventTemplateStore {
+    db: PgPool,
+}
+
+impl FromRequestParts<AppState> for EventTemplateStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgEventTemplate {
+    id: Uuid,
+    committee_id: Uuid,
+    name: String,
+    content: serde_json::Value,
+    created: OffsetDateTime,
+    updated: OffsetDateTime,
+}
+
+impl TryFrom<PgEventTemplate> for EventTemplate {
+    type Error = Error;
+
+    fn try_from(pg: PgEventTemplate) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: pg.id.into(),
+            committee_id: pg.committee_id.into(),
+            name: pg.name,
+            content: serde_json::from_value(pg.content)?,
+            created: pg.created,
+            updated: pg.updated,
+        })
+    }
+}
+
+impl EventTemplateStore {
+    pub async fn get_for_committee(
+        &self,
+        committee_id: &CommitteeId,
+    ) -> AppResult<Vec<EventTemplate>> {
+        sqlx::query_as!(
+            PgEventTemplate,
+            r#"
+            SELECT id, committee_id, name, content, created, updated
+            FROM event_template
+            WHERE committee_id = $1
+            ORDER BY name
+            "#,
+            **committee_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect()
+    }
+
+    pub async fn get_one(
+        &self,
+        committee_id: &CommitteeId,
+        id: &EventTemplateId,
+    ) -> AppResult<EventTemplate> {
+        sqlx::query_as!(
+            PgEventTemplate,
+            r#"
+            SELECT id, committee_id, name, content, created, updated
+            FROM event_template
+            WHERE id = $1 AND committee_id = $2
+            "#,
+            **id,
+            **committee_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .try_into()
+    }
+
+    pub async fn create(
+        &self,
+        committee_id: &CommitteeId,
+        new: EventTemplateContent,
+    ) -> AppResult<EventTemplate> {
+        sqlx::query_as!(
+            PgEventTemplate,
+            r#"
+            INSERT INTO event_template (id, committee_id, name, content)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, committee_id, name, content, created, updated
+            "#,
+            Uuid::now_v7(),
+            **committee_id,
+            new.name,
+            serde_json::to_value(new.content)?
+        )
+        .fetch_one(&self.db)
+        .await?
+        .try_into()
+    }
+
+    pub async fn update(
+        &self,
+        committee_id: &CommitteeId,
+        id: &EventTemplateId,
+        updated: EventTemplateContent,
+    ) -> AppResult<EventTemplate> {
+        sqlx::query_as!(
+            PgEventTemplate,
+            r#"
+            UPDATE event_template
+            SET name = $3, content = $4, updated = now()
+            WHERE id = $1 AND committee_id = $2
+            RETURNING id, committee_id, name, content, created, updated
+            "#,
+            **id,
+            **committee_id,
+            updated.name,
+            serde_json::to_value(updated.content)?
+        )
+        .fetch_one(&self.db)
+        .await?
+        .try_into()
+    }
+
+    pub async fn delete(&self, committee_id: &CommitteeId, id: &EventTemplateId) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM event_template WHERE id = $1 AND committee_id = $2
+            "#,
+            **id,
+            **committee_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+}
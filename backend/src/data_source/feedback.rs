@@ -0,0 +1,343 @@
+use crate::{
+    AppState,
+    data_source::event::summarise_answers,
+    error::{AppResult, Error},
+    event::{Answer, EventId, Question},
+    feedback::{FeedbackForm, FeedbackFormContent, FeedbackResponse, FeedbackResults},
+    user::{BasicUser, UserId},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde_json::Value;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub struct FeedbackStore {
+    db: PgPool,
+}
+
+impl FromRequestParts<AppState> for FeedbackStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgFeedbackForm {
+    event_id: Uuid,
+    questions: Value,
+    anonymous: bool,
+    shared_with_board: bool,
+    sent: Option<OffsetDateTime>,
+    responded: bool,
+    created: OffsetDateTime,
+    updated: OffsetDateTime,
+}
+
+impl TryFrom<PgFeedbackForm> for FeedbackForm {
+    type Error = Error;
+
+    fn try_from(pg: PgFeedbackForm) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event_id: pg.event_id.into(),
+            content: FeedbackFormContent {
+                questions: serde_json::from_value(pg.questions)?,
+                anonymous: pg.anonymous,
+                shared_with_board: pg.shared_with_board,
+            },
+            sent: pg.sent,
+            responded: pg.responded,
+            created: pg.created,
+            updated: pg.updated,
+        })
+    }
+}
+
+struct PgFeedbackResponse {
+    id: Uuid,
+    user_id: Option<Uuid>,
+    first_name: Option<String>,
+    infix: Option<String>,
+    last_name: Option<String>,
+    answers: Value,
+}
+
+impl TryFrom<PgFeedbackResponse> for FeedbackResponse {
+    type Error = Error;
+
+    fn try_from(pg: PgFeedbackResponse) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: pg.id,
+            user: pg.user_id.zip(pg.first_name).zip(pg.last_name).map(
+                |((id, first_name), last_name)| BasicUser {
+                    id: id.into(),
+                    first_name,
+                    infix: pg.infix,
+                    last_name,
+                },
+            ),
+            answers: serde_json::from_value(pg.answers)?,
+        })
+    }
+}
+
+impl FeedbackStore {
+    pub async fn get_form(&self, event_id: &EventId, user_id: &UserId) -> AppResult<FeedbackForm> {
+        sqlx::query_as!(
+            PgFeedbackForm,
+            r#"
+            SELECT f.event_id,
+                   f.questions,
+                   f.anonymous,
+                   f.shared_with_board,
+                   f.sent,
+                   EXISTS(SELECT 1
+                          FROM feedback_respondent fr
+                          WHERE fr.event_id = f.event_id
+                            AND fr.user_id = $2) AS "responded!",
+                   f.created,
+                   f.updated
+            FROM feedback_form f
+            WHERE f.event_id = $1
+            "#,
+            **event_id,
+            **user_id
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .ok_or(Error::NotFound)?
+        .try_into()
+    }
+
+    /// Creates or replaces the form, anonymity is fixed once someone responded
+    pub async fn set_form(
+        &self,
+        event_id: &EventId,
+        content: FeedbackFormContent,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        let anonymity_changed = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1
+                          FROM feedback_form f
+                              JOIN feedback_respondent fr ON fr.event_id = f.event_id
+                          WHERE f.event_id = $1
+                            AND f.anonymous <> $2) AS "changed!"
+            "#,
+            **event_id,
+            content.anonymous
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if anonymity_changed {
+            return Err(Error::BadRequest(
+                "Anonymity cannot change once attendees responded",
+            ));
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO feedback_form (event_id, questions, anonymous, shared_with_board)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (event_id) DO UPDATE
+                SET questions = excluded.questions,
+                    anonymous = excluded.anonymous,
+                    shared_with_board = excluded.shared_with_board,
+                    updated = now()
+            "#,
+            **event_id,
+            serde_json::to_value(content.questions)?,
+            content.anonymous,
+            content.shared_with_board
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Removes the form together with its responses
+    pub async fn delete_form(&self, event_id: &EventId) -> AppResult<()> {
+        let result = sqlx::query!("DELETE FROM feedback_form WHERE event_id = $1", **event_id)
+            .execute(&self.db)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Attendees are placed participants whose attendance was confirmed
+    pub async fn is_attendee(&self, event_id: &EventId, user_id: &UserId) -> AppResult<bool> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(SELECT 1
+                          FROM event_registration
+                          WHERE event_id = $1
+                            AND user_id = $2
+                            AND attended
+                            AND waiting_list_position IS NULL
+                            AND cancelled IS NULL) AS "attendee!"
+            "#,
+            **event_id,
+            **user_id
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    /// Notifies the attendees that did not respond yet, returns how many were notified
+    pub async fn send(&self, event_id: &EventId) -> AppResult<u64> {
+        let mut tx = self.db.begin().await?;
+        let notified = sqlx::query!(
+            r#"
+            INSERT INTO notification (id, user_id, event_id, message_nl, message_en, created)
+            SELECT gen_random_uuid(),
+                   r.user_id,
+                   e.id,
+                   format('Hoe was %s? Laat je feedback achter', e.name_nl),
+                   format('How was %s? Leave your feedback', e.name_en),
+                   now()
+            FROM event_registration r
+                JOIN event e ON r.event_id = e.id
+            WHERE e.id = $1
+              AND r.user_id IS NOT NULL
+              AND r.attended
+              AND r.waiting_list_position IS NULL
+              AND r.cancelled IS NULL
+              AND NOT EXISTS(SELECT 1
+                             FROM feedback_respondent fr
+                             WHERE fr.event_id = e.id
+                               AND fr.user_id = r.user_id)
+            "#,
+            **event_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query!(
+            "UPDATE feedback_form SET sent = now() WHERE event_id = $1",
+            **event_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(notified)
+    }
+
+    /// Attendees respond once, anonymous responses are not linked to the respondent
+    pub async fn respond(
+        &self,
+        event_id: &EventId,
+        user_id: &UserId,
+        anonymous: bool,
+        answers: Vec<Answer>,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO feedback_respondent (event_id, user_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            **event_id,
+            **user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::BadRequest("Feedback was already given"));
+        }
+
+        // A random id and the day only, so the order of responses does not give anyone away
+        sqlx::query!(
+            r#"
+            INSERT INTO feedback_response (id, event_id, user_id, answers, created)
+            VALUES (coalesce($1, gen_random_uuid()), $2, $3, $4,
+                    CASE WHEN $5 THEN date_trunc('day', now()) ELSE now() END)
+            "#,
+            (!anonymous).then(Uuid::now_v7) as Option<Uuid>,
+            **event_id,
+            (!anonymous).then_some(**user_id),
+            serde_json::to_value(answers)?,
+            anonymous
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn get_results(
+        &self,
+        event_id: &EventId,
+        questions: &[Question],
+    ) -> AppResult<FeedbackResults> {
+        let responses = sqlx::query_as!(
+            PgFeedbackResponse,
+            r#"
+            SELECT r.id,
+                   u.id AS "user_id?",
+                   u.first_name AS "first_name?",
+                   u.infix,
+                   u.last_name AS "last_name?",
+                   r.answers
+            FROM feedback_response r
+                LEFT JOIN "user" u ON r.user_id = u.id
+            WHERE r.event_id = $1
+            ORDER BY r.id
+            "#,
+            **event_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<AppResult<Vec<FeedbackResponse>>>()?;
+
+        let attendees = sqlx::query_scalar!(
+            r#"
+            SELECT count(*) AS "count!"
+            FROM event_registration
+            WHERE event_id = $1
+              AND user_id IS NOT NULL
+              AND attended
+              AND waiting_list_position IS NULL
+              AND cancelled IS NULL
+            "#,
+            **event_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        let respondents = sqlx::query_scalar!(
+            r#"SELECT count(*) AS "count!" FROM feedback_respondent WHERE event_id = $1"#,
+            **event_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(FeedbackResults {
+            attendees,
+            respondents,
+            questions: summarise_answers(
+                &self.db,
+                r#"SELECT answers, id AS sort FROM feedback_response WHERE event_id = $1"#,
+                event_id,
+                questions,
+            )
+            .await?,
+            responses,
+        })
+    }
+}
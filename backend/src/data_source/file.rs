@@ -41,6 +41,7 @@ struct PgFileMetadata {
     pub mime_type: Option<String>,
     pub size: i32,
     pub is_public: bool,
+    pub is_answer: bool,
     pub created_by: UserId,
     pub created: OffsetDateTime,
 }
@@ -59,6 +60,7 @@ impl TryFrom<PgFileMetadata> for FileMetadata {
                 .map_err(|_| Error::Internal("Failed to parse MIME string".to_string()))?,
             size: pg.size,
             is_public: pg.is_public,
+            is_answer: pg.is_answer,
             created_by: pg.created_by,
             created: pg.created,
         })
@@ -106,15 +108,16 @@ impl FileStore {
         .await?)
     }
 
+    /// Access is checked by the caller, anyone may upload files to answer registration questions
     pub async fn create(
         &self,
         original_filename: &str,
         mime_type: Option<Mime>,
         payload: Bytes,
         is_public: bool,
+        is_answer: bool,
         session: &Session,
     ) -> AppResult<FileMetadata> {
-        self.upload_access(session).await?;
         let file_id: FileId = Uuid::now_v7().into();
 
         let size = payload.len();
@@ -124,9 +127,9 @@ impl FileStore {
 
         sqlx::query_as::<_, PgFileMetadata>(
             r#"
-            INSERT INTO file (id, original_filename, mime_type, size, is_public, created_by, created)
-            VALUES ($1, $2, $3, $4, $5, $6, now())
-            RETURNING id, original_filename, mime_type, size, is_public, created_by, created
+            INSERT INTO file (id, original_filename, mime_type, size, is_public, is_answer, created_by, created)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, now())
+            RETURNING id, original_filename, mime_type, size, is_public, is_answer, created_by, created
             "#
         )
         .bind(*file_id)
@@ -134,6 +137,7 @@ impl FileStore {
         .bind(mime_type.map(|mime| mime.to_string()))
         .bind(size as i32)
         .bind(is_public)
+        .bind(is_answer)
         .bind(**session.user_id())
         .fetch_one(&self.db)
         .await?
@@ -143,7 +147,7 @@ impl FileStore {
     pub async fn get_metadata(&self, id: &FileId) -> AppResult<FileMetadata> {
         sqlx::query_as::<_, PgFileMetadata>(
             r#"
-            SELECT id, original_filename, mime_type, size, is_public, created_by, created
+            SELECT id, original_filename, mime_type, size, is_public, is_answer, created_by, created
             FROM file
             WHERE id = $1
             "#,
@@ -154,6 +158,22 @@ impl FileStore {
         .try_into()
     }
 
+    /// Files the user uploaded to answer registration questions in the last day
+    pub async fn count_recent_answer_files(&self, user_id: &UserId) -> AppResult<i64> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT count(*) AS "count!"
+            FROM file
+            WHERE created_by = $1
+              AND is_answer
+              AND created > now() - interval '1 day'
+            "#,
+            **user_id
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
     pub async fn get_bytes(&self, id: &FileId) -> AppResult<Bytes> {
         Ok(self.object_store.get(&id.into()).await?.bytes().await?)
     }
@@ -0,0 +1,326 @@
+use crate::{
+    AppState, Language,
+    error::{AppResult, Error},
+    event::ActivityType,
+    history::{
+        History, HistoryEntry, LogbookEntry, LogbookEntryContent, LogbookEntryId, LogbookVisibility,
+    },
+    user::{BasicUser, UserId},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+pub struct HistoryStore {
+    db: PgPool,
+}
+
+impl FromRequestParts<AppState> for HistoryStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgLogbookEntry {
+    id: Uuid,
+    user_id: Uuid,
+    date: Date,
+    area: String,
+    route: String,
+    grade: Option<String>,
+    description: String,
+    visibility: LogbookVisibility,
+    created: OffsetDateTime,
+    updated: OffsetDateTime,
+}
+
+impl PgLogbookEntry {
+    fn into_entry(self, partners: Vec<BasicUser>) -> LogbookEntry {
+        LogbookEntry {
+            id: self.id.into(),
+            user_id: self.user_id.into(),
+            date: self.date,
+            area: self.area,
+            route: self.route,
+            grade: self.grade,
+            description: self.description,
+            partners,
+            visibility: self.visibility,
+            created: self.created,
+            updated: self.updated,
+        }
+    }
+}
+
+impl HistoryStore {
+    /// Past events the user attended, the attendance is registered by the organisers
+    pub async fn get_history(&self, user_id: &UserId) -> AppResult<History> {
+        let events = sqlx::query!(
+            r#"
+            SELECT e.id,
+                   e.name_nl,
+                   e.name_en,
+                   e.event_type,
+                   l.name_nl as location_name_nl,
+                   l.name_en as location_name_en,
+                   (SELECT min(d) FROM unnest(e.start_dates) d) as "start!",
+                   (SELECT max(d) FROM unnest(e.end_dates) d) as "end!"
+            FROM event_registration r
+                JOIN event e ON r.event_id = e.id
+                JOIN location l ON e.location_id = l.id
+            WHERE r.user_id = $1
+              AND r.attended
+              AND r.waiting_list_position IS NULL
+              AND r.cancelled IS NULL
+              AND e.status <> 'draft'
+              AND (SELECT max(d) FROM unnest(e.end_dates) d) < now()
+            ORDER BY 7 DESC
+            "#,
+            **user_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut history = History::default();
+        for event in events {
+            let group = match event.event_type.parse()? {
+                ActivityType::Activity => &mut history.activity,
+                ActivityType::Course => &mut history.course,
+                ActivityType::Weekend => &mut history.weekend,
+                ActivityType::Training => &mut history.training,
+            };
+            group.push(HistoryEntry {
+                event_id: event.id.into(),
+                name: Language {
+                    en: event.name_en,
+                    nl: event.name_nl,
+                },
+                location: Language {
+                    en: event.location_name_en,
+                    nl: event.location_name_nl,
+                },
+                start: event.start,
+                end: event.end,
+            });
+        }
+
+        Ok(history)
+    }
+
+    async fn get_partners(&self, entry_ids: &[Uuid]) -> AppResult<HashMap<Uuid, Vec<BasicUser>>> {
+        let partners = sqlx::query!(
+            r#"
+            SELECT p.entry_id, u.id, u.first_name, u.infix, u.last_name
+            FROM logbook_partner p
+                JOIN "user" u ON p.user_id = u.id
+            WHERE p.entry_id = ANY($1)
+            ORDER BY u.first_name, u.last_name
+            "#,
+            entry_ids
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut by_entry: HashMap<Uuid, Vec<BasicUser>> = HashMap::new();
+        for partner in partners {
+            by_entry
+                .entry(partner.entry_id)
+                .or_default()
+                .push(BasicUser {
+                    id: partner.id.into(),
+                    first_name: partner.first_name,
+                    infix: partner.infix,
+                    last_name: partner.last_name,
+                });
+        }
+        Ok(by_entry)
+    }
+
+    /// The entries of the logbook with one of the given visibilities, most recent first
+    pub async fn get_logbook(
+        &self,
+        user_id: &UserId,
+        visibility: &[LogbookVisibility],
+    ) -> AppResult<Vec<LogbookEntry>> {
+        let entries = sqlx::query_as!(
+            PgLogbookEntry,
+            r#"
+            SELECT id,
+                   user_id,
+                   date,
+                   area,
+                   route,
+                   grade,
+                   description,
+                   visibility as "visibility: LogbookVisibility",
+                   created,
+                   updated
+            FROM logbook_entry
+            WHERE user_id = $1 AND visibility = ANY($2)
+            ORDER BY date DESC, created DESC
+            "#,
+            **user_id,
+            visibility as &[LogbookVisibility]
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let ids: Vec<Uuid> = entries.iter().map(|entry| entry.id).collect();
+        let mut partners = self.get_partners(&ids).await?;
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                let partners = partners.remove(&entry.id).unwrap_or_default();
+                entry.into_entry(partners)
+            })
+            .collect())
+    }
+
+    async fn get_entry(&self, id: &LogbookEntryId) -> AppResult<LogbookEntry> {
+        let entry = sqlx::query_as!(
+            PgLogbookEntry,
+            r#"
+            SELECT id,
+                   user_id,
+                   date,
+                   area,
+                   route,
+                   grade,
+                   description,
+                   visibility as "visibility: LogbookVisibility",
+                   created,
+                   updated
+            FROM logbook_entry
+            WHERE id = $1
+            "#,
+            **id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        let partners = self
+            .get_partners(&[entry.id])
+            .await?
+            .remove(&entry.id)
+            .unwrap_or_default();
+        Ok(entry.into_entry(partners))
+    }
+
+    async fn set_partners(
+        conn: &mut PgConnection,
+        id: &LogbookEntryId,
+        partners: &[UserId],
+    ) -> AppResult<()> {
+        sqlx::query!(r#"DELETE FROM logbook_partner WHERE entry_id = $1"#, **id)
+            .execute(&mut *conn)
+            .await?;
+
+        let partners: Vec<Uuid> = partners.iter().map(|partner| **partner).collect();
+        sqlx::query!(
+            r#"
+            INSERT INTO logbook_partner (entry_id, user_id)
+            SELECT $1, unnest($2::uuid[])
+            ON CONFLICT DO NOTHING
+            "#,
+            **id,
+            &partners
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_entry(
+        &self,
+        user_id: &UserId,
+        content: LogbookEntryContent,
+    ) -> AppResult<LogbookEntry> {
+        let id: LogbookEntryId = Uuid::now_v7().into();
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO logbook_entry (id, user_id, date, area, route, grade, description, visibility)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            *id,
+            **user_id,
+            content.date,
+            content.area,
+            content.route,
+            content.grade,
+            content.description,
+            content.visibility as LogbookVisibility
+        )
+        .execute(&mut *tx)
+        .await?;
+        Self::set_partners(&mut tx, &id, &content.partners).await?;
+        tx.commit().await?;
+
+        self.get_entry(&id).await
+    }
+
+    pub async fn update_entry(
+        &self,
+        user_id: &UserId,
+        id: &LogbookEntryId,
+        content: LogbookEntryContent,
+    ) -> AppResult<LogbookEntry> {
+        let mut tx = self.db.begin().await?;
+        let result = sqlx::query!(
+            r#"
+            UPDATE logbook_entry
+            SET date = $3,
+                area = $4,
+                route = $5,
+                grade = $6,
+                description = $7,
+                visibility = $8,
+                updated = now()
+            WHERE id = $1 AND user_id = $2
+            "#,
+            **id,
+            **user_id,
+            content.date,
+            content.area,
+            content.route,
+            content.grade,
+            content.description,
+            content.visibility as LogbookVisibility
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+        Self::set_partners(&mut tx, id, &content.partners).await?;
+        tx.commit().await?;
+
+        self.get_entry(id).await
+    }
+
+    pub async fn delete_entry(&self, user_id: &UserId, id: &LogbookEntryId) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"DELETE FROM logbook_entry WHERE id = $1 AND user_id = $2"#,
+            **id,
+            **user_id
+        )
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+}
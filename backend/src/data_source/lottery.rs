@@ -0,0 +1,410 @@
+use crate::{
+    AppState,
+    auth::role::Membership,
+    data_source::PaymentStore,
+    error::{AppResult, Error},
+    event::{Answer, EventId, MembershipRule, OptionUsage, Question},
+    lottery::{LotteryDraw, LotteryEntry, LotteryOutcome},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use rand::distr::{Alphanumeric, SampleString};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::{collections::HashMap, time::Duration};
+use tracing::{error, info};
+use uuid::Uuid;
+
+const LOTTERY_DRAW_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct LotteryStore {
+    db: PgPool,
+}
+
+impl LotteryStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+impl FromRequestParts<AppState> for LotteryStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgLotteryEntry {
+    registration_id: Uuid,
+    user_id: Option<Uuid>,
+    weight: f64,
+    ticket: f64,
+    rank: i32,
+    outcome: LotteryOutcome,
+}
+
+impl From<PgLotteryEntry> for LotteryEntry {
+    fn from(pg: PgLotteryEntry) -> Self {
+        Self {
+            registration_id: pg.registration_id.into(),
+            user_id: pg.user_id.map(Into::into),
+            weight: pg.weight,
+            ticket: pg.ticket,
+            rank: pg.rank,
+            outcome: pg.outcome,
+        }
+    }
+}
+
+struct Candidate {
+    registration_id: Uuid,
+    user_id: Option<Uuid>,
+    membership: Membership,
+    waiting_list_position: Option<i32>,
+    places: i32,
+    answers: Value,
+    weight: f64,
+}
+
+/// Weighted random sampling (Efraimidis-Spirakis): the ticket is `ln(u) / weight`,
+/// with `u` derived from the seed and the registration id.
+/// Anyone with the seed can recompute the tickets, regardless of the order of the entries.
+fn ticket(seed: &str, registration_id: &Uuid, weight: f64) -> f64 {
+    let hash = Sha256::digest(format!("{seed}:{registration_id}"));
+    let bits = u64::from_be_bytes(hash[..8].try_into().expect("SHA-256 has 32 bytes"));
+    // Uniform in (0, 1]
+    let u = ((bits >> 11) + 1) as f64 / (1u64 << 53) as f64;
+    u.ln() / weight
+}
+
+impl LotteryStore {
+    pub async fn get_draw(&self, event_id: &EventId) -> AppResult<LotteryDraw> {
+        let draw = sqlx::query!(
+            r#"SELECT seed, drawn FROM lottery_draw WHERE event_id = $1"#,
+            **event_id
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        let entries = sqlx::query_as!(
+            PgLotteryEntry,
+            r#"
+            SELECT registration_id,
+                   user_id,
+                   weight,
+                   ticket,
+                   rank,
+                   outcome as "outcome: LotteryOutcome"
+            FROM lottery_result
+            WHERE event_id = $1
+            ORDER BY rank
+            "#,
+            **event_id
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(LotteryDraw {
+            event_id: event_id.clone(),
+            seed: draw.seed,
+            drawn: draw.drawn,
+            entries: entries.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Lottery events whose registration period has closed, but have not been drawn yet
+    async fn get_due(&self) -> AppResult<Vec<EventId>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT e.id
+            FROM event e
+            WHERE e.lottery
+              AND e.status = 'published'
+              AND e.registration_end <= now()
+              AND NOT EXISTS(SELECT 1 FROM lottery_draw d WHERE d.event_id = e.id)
+            "#
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Draws the places for everyone in the pool, i.e. on the waiting list.
+    /// Registrations placed by organisers keep their place.
+    /// People that lost a lottery in the past year get an extra weight for each loss.
+    /// Everyone is notified of the outcome, rejected registrations are refunded and removed.
+    pub async fn draw(
+        &self,
+        payments: &PaymentStore,
+        event_id: &EventId,
+    ) -> AppResult<LotteryDraw> {
+        let mut tx = self.db.begin().await?;
+
+        let event = sqlx::query!(
+            r#"
+            SELECT registration_max, waiting_list_max, membership_rules, questions
+            FROM event
+            WHERE id = $1 AND lottery
+            FOR UPDATE
+            "#,
+            **event_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let rules: Vec<MembershipRule> = serde_json::from_value(event.membership_rules)?;
+        let questions: Vec<Question> = serde_json::from_value(event.questions)?;
+
+        let seed = Alphanumeric.sample_string(&mut rand::rng(), 32);
+        sqlx::query!(
+            r#"INSERT INTO lottery_draw (event_id, seed) VALUES ($1, $2)"#,
+            **event_id,
+            seed
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let candidates = sqlx::query_as!(
+            Candidate,
+            r#"
+            SELECT r.registration_id,
+                   r.user_id,
+                   coalesce(u.membership, 'non_member') as "membership!: Membership",
+                   r.waiting_list_position,
+                   r.places,
+                   r.answers,
+                   1 + (SELECT count(*)
+                        FROM lottery_result lr
+                            JOIN lottery_draw ld ON ld.event_id = lr.event_id
+                        WHERE lr.user_id = r.user_id
+                          AND lr.outcome <> 'placed'
+                          AND ld.drawn > now() - interval '1 year')::float8 AS "weight!"
+            FROM event_registration r
+                LEFT JOIN "user" u ON r.user_id = u.id
+            WHERE r.event_id = $1
+              AND r.cancelled IS NULL
+            "#,
+            **event_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let (placed, pool): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|c| c.waiting_list_position.is_none());
+
+        let mut places_left = event
+            .registration_max
+            .map(|max| max as i64 - placed.iter().map(|c| c.places as i64).sum::<i64>());
+        let mut per_membership: HashMap<Membership, i64> = HashMap::new();
+        for candidate in &placed {
+            *per_membership.entry(candidate.membership).or_default() += candidate.places as i64;
+        }
+        let answers_of = |candidate: &Candidate| -> AppResult<Vec<Answer>> {
+            Ok(serde_json::from_value(candidate.answers.clone())?)
+        };
+        let mut usage = OptionUsage::default();
+        for candidate in &placed {
+            usage.add(&questions, &answers_of(candidate)?);
+        }
+
+        let mut pool: Vec<_> = pool
+            .into_iter()
+            .map(|c| (ticket(&seed, &c.registration_id, c.weight), c))
+            .collect();
+        pool.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        let mut registration_ids = Vec::new();
+        let mut user_ids = Vec::new();
+        let mut weights = Vec::new();
+        let mut tickets = Vec::new();
+        let mut outcomes = Vec::new();
+        let mut waiting_list_positions = Vec::new();
+        let mut waiting_list_length = 0;
+        let mut waiting_list_places = 0;
+
+        for (ticket, candidate) in pool {
+            let quota = rules
+                .iter()
+                .find(|rule| rule.membership == candidate.membership)
+                .and_then(|rule| rule.quota);
+            let registered = per_membership.entry(candidate.membership).or_default();
+            let places = candidate.places as i64;
+            let answers = answers_of(&candidate)?;
+
+            // A winner whose chosen option is full goes to the waiting list
+            let outcome = if places_left.is_none_or(|left| left >= places)
+                && quota.is_none_or(|quota| *registered + places <= quota as i64)
+                && usage.has_room(&questions, &answers)
+            {
+                places_left = places_left.map(|left| left - places);
+                *registered += places;
+                usage.add(&questions, &answers);
+                waiting_list_positions.push(None);
+                LotteryOutcome::Placed
+            } else if event
+                .waiting_list_max
+                .is_none_or(|max| waiting_list_places + candidate.places <= max)
+            {
+                waiting_list_positions.push(Some(waiting_list_length));
+                waiting_list_length += 1;
+                waiting_list_places += candidate.places;
+                LotteryOutcome::WaitingList
+            } else {
+                waiting_list_positions.push(None);
+                LotteryOutcome::Rejected
+            };
+
+            registration_ids.push(candidate.registration_id);
+            user_ids.push(candidate.user_id);
+            weights.push(candidate.weight);
+            tickets.push(ticket);
+            outcomes.push(outcome);
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO lottery_result (event_id, registration_id, user_id, weight, ticket, rank, outcome)
+            SELECT $1, r.registration_id, r.user_id, r.weight, r.ticket, r.rank::int - 1, r.outcome
+            FROM unnest($2::uuid[], $3::uuid[], $4::float8[], $5::float8[], $6::lottery_outcome[])
+                WITH ORDINALITY AS r(registration_id, user_id, weight, ticket, outcome, rank)
+            "#,
+            **event_id,
+            &registration_ids,
+            &user_ids as &[Option<Uuid>],
+            &weights,
+            &tickets,
+            &outcomes as &[LotteryOutcome],
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for (registration_id, outcome) in registration_ids.iter().zip(&outcomes) {
+            if *outcome == LotteryOutcome::Rejected {
+                payments
+                    .refund_registration(&mut tx, &(*registration_id).into())
+                    .await?;
+            }
+        }
+
+        sqlx::query!(
+            r#"
+            DELETE FROM event_registration r
+            USING lottery_result lr
+            WHERE lr.event_id = $1
+              AND lr.registration_id = r.registration_id
+              AND lr.outcome = 'rejected'
+            "#,
+            **event_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Clear the pool first, as the waiting list positions must stay unique
+        sqlx::query!(
+            r#"
+            UPDATE event_registration
+            SET waiting_list_position = NULL, updated = now()
+            WHERE registration_id = ANY($1)
+            "#,
+            &registration_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE event_registration r
+            SET waiting_list_position = p.position
+            FROM unnest($1::uuid[], $2::int[]) AS p(registration_id, position)
+            WHERE r.registration_id = p.registration_id
+            "#,
+            &registration_ids,
+            &waiting_list_positions as &[Option<i32>]
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO notification (id, user_id, event_id, message_nl, message_en, created)
+            SELECT gen_random_uuid(),
+                   lr.user_id,
+                   e.id,
+                   CASE lr.outcome
+                       WHEN 'placed' THEN format('Je bent ingeloot voor %s', e.name_nl)
+                       WHEN 'waiting_list' THEN format('Je bent uitgeloot voor %s en staat op de wachtlijst', e.name_nl)
+                       ELSE format('Je bent uitgeloot voor %s', e.name_nl)
+                   END,
+                   CASE lr.outcome
+                       WHEN 'placed' THEN format('You won a place for %s', e.name_en)
+                       WHEN 'waiting_list' THEN format('You did not win a place for %s and are on the waiting list', e.name_en)
+                       ELSE format('You did not win a place for %s', e.name_en)
+                   END,
+                   now()
+            FROM lottery_result lr
+                JOIN event e ON lr.event_id = e.id
+            WHERE lr.event_id = $1
+              AND lr.user_id IS NOT NULL
+            "#,
+            **event_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!(
+            event_id = event_id.to_string(),
+            entries = registration_ids.len(),
+            "Drew the lottery"
+        );
+        self.get_draw(event_id).await
+    }
+
+    /// Draws every due lottery, a failing draw does not hold up the others.
+    /// Returns the number of lotteries drawn and the draws that failed
+    pub async fn draw_due(
+        &self,
+        payments: &PaymentStore,
+    ) -> AppResult<(usize, Vec<(EventId, Error)>)> {
+        let mut drawn = 0;
+        let mut failed = Vec::new();
+        for event_id in self.get_due().await? {
+            match self.draw(payments, &event_id).await {
+                Ok(_) => drawn += 1,
+                Err(err) => {
+                    error!("Failed to draw the lottery of event {event_id}: {err}");
+                    failed.push((event_id, err));
+                }
+            }
+        }
+        Ok((drawn, failed))
+    }
+}
+
+pub async fn run_lottery_draws(db: PgPool, payments: PaymentStore) {
+    let store = LotteryStore::new(db);
+    let mut interval = tokio::time::interval(LOTTERY_DRAW_INTERVAL);
+    loop {
+        interval.tick().await;
+        match store.draw_due(&payments).await {
+            Ok((drawn, failed)) => {
+                if drawn > 0 {
+                    info!("Drew {drawn} lotteries");
+                }
+                if !failed.is_empty() {
+                    error!("Failed to draw {} lotteries", failed.len());
+                }
+            }
+            Err(err) => error!("Failed to draw lotteries: {err}"),
+        }
+    }
+}
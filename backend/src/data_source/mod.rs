@@ -1,16 +1,40 @@
+mod api_token;
+mod attendance;
+mod carpool;
+mod certification;
 pub(crate) mod committee;
 pub(crate) mod event;
+mod event_series;
+mod event_template;
+mod feedback;
 mod file;
+mod history;
 mod location;
+mod lottery;
 mod material;
+mod notification;
 mod page;
+mod payment;
+mod sepa;
 mod user;
 
+pub use api_token::*;
+pub use attendance::*;
 use axum::http::HeaderMap;
+pub use carpool::*;
+pub use certification::*;
+pub use event_series::*;
+pub use event_template::*;
+pub use feedback::*;
 pub use file::*;
+pub use history::*;
 pub use location::*;
+pub use lottery::*;
 pub use material::*;
+pub use notification::*;
 pub use page::*;
+pub use payment::*;
+pub use sepa::*;
 pub use user::*;
 
 pub struct Count {
@@ -0,0 +1,88 @@
+use crate::{
+    AppState, Language,
+    error::{AppResult, Error},
+    notification::{Notification, NotificationId},
+    user::UserId,
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub struct NotificationStore {
+    db: PgPool,
+}
+
+impl FromRequestParts<AppState> for NotificationStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+        })
+    }
+}
+
+struct PgNotification {
+    id: Uuid,
+    event_id: Option<Uuid>,
+    message_nl: String,
+    message_en: String,
+    read: bool,
+    created: OffsetDateTime,
+}
+
+impl From<PgNotification> for Notification {
+    fn from(pg: PgNotification) -> Self {
+        Self {
+            id: pg.id.into(),
+            event_id: pg.event_id.map(Into::into),
+            message: Language {
+                en: pg.message_en,
+                nl: pg.message_nl,
+            },
+            read: pg.read,
+            created: pg.created,
+        }
+    }
+}
+
+impl NotificationStore {
+    pub async fn get_for_user(&self, user_id: &UserId) -> AppResult<Vec<Notification>> {
+        Ok(sqlx::query_as!(
+            PgNotification,
+            r#"
+            SELECT id, event_id, message_nl, message_en, read, created
+            FROM notification
+            WHERE user_id = $1
+            ORDER BY created DESC
+            "#,
+            **user_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    pub async fn mark_read(&self, user_id: &UserId, id: &NotificationId) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE notification SET read = true WHERE id = $1 AND user_id = $2
+            "#,
+            **id,
+            **user_id
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+}
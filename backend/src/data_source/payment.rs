@@ -0,0 +1,433 @@
+use crate::{
+    AppState, Language,
+    error::{AppResult, Error},
+    event::{EventId, Registration, RegistrationId},
+    payment::{Checkout, Payment, PaymentStatus, TransactionStatus},
+    payment_provider::{NewPayment, PaymentProvider, ProviderStatus, verify_signature},
+    user::{BasicUser, UserId},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde::Deserialize;
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub struct PaymentStore {
+    db: PgPool,
+    provider: Option<Arc<dyn PaymentProvider>>,
+    public_url: String,
+    webhook_secret: Option<String>,
+}
+
+impl From<&AppState> for PaymentStore {
+    fn from(state: &AppState) -> Self {
+        Self {
+            db: state.pool().clone(),
+            provider: state.payment_provider(),
+            public_url: state.config().public_url.clone(),
+            webhook_secret: state.webhook_secret().map(ToOwned::to_owned),
+        }
+    }
+}
+
+impl FromRequestParts<AppState> for PaymentStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(state.into())
+    }
+}
+
+struct PgPayment {
+    registration_id: Uuid,
+    event_id: Uuid,
+    name_en: String,
+    name_nl: String,
+    user_id: Option<Uuid>,
+    first_name: Option<String>,
+    infix: Option<String>,
+    last_name: Option<String>,
+    amount_due: i32,
+    payment_status: PaymentStatus,
+    payment_updated: Option<OffsetDateTime>,
+}
+
+impl From<PgPayment> for Payment {
+    fn from(pg: PgPayment) -> Self {
+        Self {
+            registration_id: pg.registration_id.into(),
+            event_id: pg.event_id.into(),
+            event_name: Language {
+                en: pg.name_en,
+                nl: pg.name_nl,
+            },
+            user: pg.user_id.map(|user_id| BasicUser {
+                id: user_id.into(),
+                first_name: pg.first_name.unwrap_or_default(),
+                infix: pg.infix,
+                last_name: pg.last_name.unwrap_or_default(),
+            }),
+            amount_due: pg.amount_due,
+            status: pg.payment_status,
+            updated: pg.payment_updated,
+        }
+    }
+}
+
+impl From<ProviderStatus> for TransactionStatus {
+    fn from(status: ProviderStatus) -> Self {
+        match status {
+            ProviderStatus::Open => TransactionStatus::Open,
+            ProviderStatus::Paid => TransactionStatus::Paid,
+            ProviderStatus::Failed => TransactionStatus::Failed,
+            ProviderStatus::Expired => TransactionStatus::Expired,
+            ProviderStatus::Canceled => TransactionStatus::Canceled,
+        }
+    }
+}
+
+struct PaidTransaction {
+    provider_id: String,
+    registration_id: Uuid,
+    amount: i32,
+}
+
+#[derive(Deserialize)]
+struct WebhookPayload {
+    id: String,
+}
+
+impl PaymentStore {
+    fn provider(&self) -> AppResult<&Arc<dyn PaymentProvider>> {
+        self.provider
+            .as_ref()
+            .ok_or(Error::BadRequest("Online payments are not enabled"))
+    }
+
+    /// Starts an online payment of the amount due, an open checkout is reused
+    pub async fn checkout(
+        &self,
+        registration: &Registration,
+        description: String,
+    ) -> AppResult<Checkout> {
+        let existing = sqlx::query_scalar!(
+            r#"
+            SELECT checkout_url as "checkout_url!"
+            FROM payment_transaction
+            WHERE registration_id = $1
+              AND status = 'open'
+              AND amount = $2
+              AND checkout_url IS NOT NULL
+            ORDER BY created DESC
+            LIMIT 1
+            "#,
+            *registration.registration_id,
+            registration.amount_due
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        if let Some(checkout_url) = existing {
+            return Ok(Checkout { checkout_url });
+        }
+
+        let payment = self
+            .provider()?
+            .create_payment(NewPayment {
+                amount: registration.amount_due,
+                description,
+                redirect_url: format!("{}/events/{}", self.public_url, registration.event_id),
+                webhook_url: format!("{}/api/payment/webhook", self.public_url),
+            })
+            .await?;
+        let Some(checkout_url) = payment.checkout_url else {
+            return Err(Error::PaymentProvider(
+                "Payment without a checkout URL".to_string(),
+            ));
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO payment_transaction (id, provider_id, registration_id, user_id, amount, status, checkout_url)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            Uuid::now_v7(),
+            payment.id,
+            *registration.registration_id,
+            registration.user.as_ref().map(|user| *user.id),
+            registration.amount_due,
+            TransactionStatus::from(payment.status) as TransactionStatus,
+            checkout_url
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(Checkout { checkout_url })
+    }
+
+    /// Processes a status change notification of the provider. The status is always fetched
+    /// from the provider, so replayed or repeated notifications do not change anything.
+    pub async fn handle_webhook(&self, signature: Option<&str>, body: &[u8]) -> AppResult<()> {
+        let provider = self.provider()?;
+        let secret = self
+            .webhook_secret
+            .as_deref()
+            .ok_or(Error::BadRequest("Online payments are not enabled"))?;
+        if !signature.is_some_and(|signature| verify_signature(secret, body, signature)) {
+            warn!("Rejected payment webhook with an invalid signature");
+            return Err(Error::Unauthorized);
+        }
+        let payload: WebhookPayload = serde_json::from_slice(body)?;
+
+        let payment = provider.get_payment(&payload.id).await?;
+        let status = TransactionStatus::from(payment.status);
+
+        let mut tx = self.db.begin().await?;
+        let transaction = sqlx::query!(
+            r#"
+            SELECT t.registration_id,
+                   t.amount,
+                   t.status as "status: TransactionStatus",
+                   r.amount_due as "amount_due?"
+            FROM payment_transaction t
+                LEFT JOIN event_registration r ON t.registration_id = r.registration_id
+            WHERE t.provider_id = $1
+            FOR UPDATE OF t
+            "#,
+            payment.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Refunds are initiated by us, the provider keeps reporting those as paid
+        if transaction.status == status || transaction.status == TransactionStatus::Refunded {
+            return Ok(());
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE payment_transaction SET status = $2, updated = now() WHERE provider_id = $1
+            "#,
+            payment.id,
+            status as TransactionStatus
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // The checkout may be for an outdated amount, or tampered with at the provider
+        let covers_amount_due = payment.currency == "EUR"
+            && payment.amount == transaction.amount
+            && transaction
+                .amount_due
+                .is_some_and(|amount_due| payment.amount == amount_due);
+        if status == TransactionStatus::Paid && !covers_amount_due {
+            warn!(
+                payment_id = payment.id,
+                amount = payment.amount,
+                currency = payment.currency,
+                "Paid payment does not match the amount due, the treasurer has to settle it"
+            );
+        }
+
+        if status == TransactionStatus::Paid
+            && covers_amount_due
+            && let Some(registration_id) = transaction.registration_id
+        {
+            sqlx::query!(
+                r#"
+                UPDATE event_registration
+                SET payment_status = 'paid',
+                    payment_updated = now(),
+                    updated = now()
+                WHERE registration_id = $1 AND payment_status = 'unpaid'
+                "#,
+                registration_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        info!(payment_id = payment.id, ?status, "Updated online payment");
+        Ok(())
+    }
+
+    /// Refunds the online payments of a registration that is about to be cancelled.
+    /// Runs in the transaction that drops the registration, so it is kept if a refund fails.
+    pub async fn refund_registration(
+        &self,
+        conn: &mut PgConnection,
+        registration_id: &RegistrationId,
+    ) -> AppResult<()> {
+        let paid = sqlx::query_as!(
+            PaidTransaction,
+            r#"
+            SELECT provider_id, registration_id as "registration_id!", amount
+            FROM payment_transaction
+            WHERE registration_id = $1 AND status = 'paid'
+            FOR UPDATE
+            "#,
+            **registration_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        self.refund(conn, paid).await
+    }
+
+    /// Refunds the online payments of all registrations of an event that is about to be
+    /// cancelled or deleted, in the transaction that does so
+    pub async fn refund_event(&self, conn: &mut PgConnection, event_id: &EventId) -> AppResult<()> {
+        let paid = sqlx::query_as!(
+            PaidTransaction,
+            r#"
+            SELECT t.provider_id, t.registration_id as "registration_id!", t.amount
+            FROM payment_transaction t
+                JOIN event_registration r ON t.registration_id = r.registration_id
+            WHERE r.event_id = $1 AND t.status = 'paid'
+            FOR UPDATE OF t
+            "#,
+            **event_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        self.refund(conn, paid).await
+    }
+
+    async fn refund(&self, conn: &mut PgConnection, paid: Vec<PaidTransaction>) -> AppResult<()> {
+        if paid.is_empty() {
+            return Ok(());
+        }
+
+        let provider = self.provider()?;
+        let mut registration_ids = Vec::with_capacity(paid.len());
+        for transaction in paid {
+            let refund_id = provider
+                .refund(&transaction.provider_id, transaction.amount)
+                .await?;
+            sqlx::query!(
+                r#"
+                UPDATE payment_transaction
+                SET status = 'refunded', refund_id = $2, updated = now()
+                WHERE provider_id = $1
+                "#,
+                transaction.provider_id,
+                refund_id
+            )
+            .execute(&mut *conn)
+            .await?;
+            registration_ids.push(transaction.registration_id);
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE event_registration
+            SET payment_status = 'refunded',
+                payment_updated = now(),
+                updated = now()
+            WHERE registration_id = ANY($1)
+            "#,
+            &registration_ids
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Registrations with a place at the event, people on the waiting list do not pay
+    pub async fn get_for_event(&self, event_id: &EventId) -> AppResult<Vec<Payment>> {
+        Ok(sqlx::query_as!(
+            PgPayment,
+            r#"
+            SELECT r.registration_id,
+                   r.event_id,
+                   e.name_en,
+                   e.name_nl,
+                   r.user_id,
+                   u.first_name as "first_name?",
+                   u.infix,
+                   u.last_name as "last_name?",
+                   r.amount_due,
+                   r.payment_status as "payment_status: PaymentStatus",
+                   r.payment_updated
+            FROM event_registration r
+                JOIN event e ON r.event_id = e.id
+                LEFT JOIN "user" u ON r.user_id = u.id
+            WHERE r.event_id = $1
+              AND r.waiting_list_position IS NULL
+            ORDER BY u.last_name, u.first_name
+            "#,
+            **event_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Registrations of the user with a place at a priced event, newest first
+    pub async fn get_for_user(&self, user_id: &UserId) -> AppResult<Vec<Payment>> {
+        Ok(sqlx::query_as!(
+            PgPayment,
+            r#"
+            SELECT r.registration_id,
+                   r.event_id,
+                   e.name_en,
+                   e.name_nl,
+                   r.user_id,
+                   u.first_name as "first_name?",
+                   u.infix,
+                   u.last_name as "last_name?",
+                   r.amount_due,
+                   r.payment_status as "payment_status: PaymentStatus",
+                   r.payment_updated
+            FROM event_registration r
+                JOIN event e ON r.event_id = e.id
+                LEFT JOIN "user" u ON r.user_id = u.id
+            WHERE r.user_id = $1
+              AND r.waiting_list_position IS NULL
+              AND r.amount_due > 0
+            ORDER BY e.start_dates[1] DESC
+            "#,
+            **user_id
+        )
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    pub async fn set_status(
+        &self,
+        registration_id: &RegistrationId,
+        status: PaymentStatus,
+    ) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE event_registration
+            SET payment_status = $2,
+                payment_updated = now(),
+                updated = now()
+            WHERE registration_id = $1
+            "#,
+            **registration_id,
+            status as PaymentStatus
+        )
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{
+    AppState,
+    auth::role::Membership,
+    error::{AppResult, Error},
+    sepa::{
+        CollectionBatch, CollectionBatchId, CollectionStatus, Creditor, FeeCollection,
+        FeeCollectionId, MembershipFee, SepaMandate, SepaMandateContent, SequenceType,
+        normalize_iban,
+    },
+    user::{BasicUser, UserId},
+};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use sqlx::{PgConnection, PgPool};
+use time::{Date, OffsetDateTime};
+use tracing::info;
+use uuid::Uuid;
+
+pub struct SepaStore {
+    db: PgPool,
+    creditor: Option<Creditor>,
+}
+
+impl FromRequestParts<AppState> for SepaStore {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            db: state.pool().clone(),
+            creditor: state.config().sepa_creditor.clone(),
+        })
+    }
+}
+
+struct PgSepaMandate {
+    user_id: Uuid,
+    account_holder: String,
+    iban: String,
+    mandate_id: String,
+    signed: Date,
+    created: OffsetDateTime,
+    updated: OffsetDateTime,
+}
+
+impl From<PgSepaMandate> for SepaMandate {
+    fn from(pg: PgSepaMandate) -> Self {
+        Self {
+            user_id: pg.user_id.into(),
+            account_holder: pg.account_holder,
+            iban: pg.iban,
+            mandate_id: pg.mandate_id,
+            signed: pg.signed,
+            created: pg.created,
+            updated: pg.updated,
+        }
+    }
+}
+
+struct PgFeeCollection {
+    id: Uuid,
+    batch_id: Uuid,
+    user_id: Option<Uuid>,
+    first_name: Option<String>,
+    infix: Option<String>,
+    last_name: Option<String>,
+    year: i32,
+    amount: i32,
+    account_holder: String,
+    iban: String,
+    mandate_id: String,
+    signed: Date,
+    sequence_type: SequenceType,
+    status: CollectionStatus,
+    updated: OffsetDateTime,
+}
+
+impl From<PgFeeCollection> for FeeCollection {
+    fn from(pg: PgFeeCollection) -> Self {
+        Self {
+            id: pg.id.into(),
+            batch_id: pg.batch_id.into(),
+            user: pg.user_id.map(|user_id| BasicUser {
+                id: user_id.into(),
+                first_name: pg.first_name.unwrap_or_default(),
+                infix: pg.infix,
+                last_name: pg.last_name.unwrap_or_default(),
+            }),
+            year: pg.year,
+            amount: pg.amount,
+            account_holder: pg.account_holder,
+            iban: pg.iban,
+            mandate_id: pg.mandate_id,
+            signed: pg.signed,
+            sequence_type: pg.sequence_type,
+            status: pg.status,
+            updated: pg.updated,
+        }
+    }
+}
+
+impl SepaStore {
+    pub fn creditor(&self) -> AppResult<&Creditor> {
+        self.creditor
+            .as_ref()
+            .ok_or(Error::BadRequest("Direct debit is not configured"))
+    }
+
+    pub async fn get_mandate(&self, user_id: &UserId) -> AppResult<SepaMandate> {
+        Ok(sqlx::query_as!(
+            PgSepaMandate,
+            r#"
+            SELECT user_id, account_holder, iban, mandate_id, signed, created, updated
+            FROM sepa_mandate
+            WHERE user_id = $1
+            "#,
+            **user_id
+        )
+        .fetch_one(&self.db)
+        .await?
+        .into())
+    }
+
+    pub async fn set_mandate(
+        &self,
+        user_id: &UserId,
+        mandate: SepaMandateContent,
+    ) -> AppResult<SepaMandate> {
+        Ok(sqlx::query_as!(
+            PgSepaMandate,
+            r#"
+            INSERT INTO sepa_mandate (user_id, account_holder, iban, mandate_id, signed, created, updated)
+            VALUES ($1, $2, $3, $4, $5, now(), now())
+            ON CONFLICT (user_id) DO UPDATE
+            SET account_holder = excluded.account_holder,
+                iban = excluded.iban,
+                mandate_id = excluded.mandate_id,
+                signed = excluded.signed,
+                updated = now()
+            RETURNING user_id, account_holder, iban, mandate_id, signed, created, updated
+            "#,
+            **user_id,
+            mandate.account_holder,
+            normalize_iban(&mandate.iban),
+            mandate.mandate_id,
+            mandate.signed
+        )
+        .fetch_one(&self.db)
+        .await?
+        .into())
+    }
+
+    pub async fn delete_mandate(&self, user_id: &UserId) -> AppResult<()> {
+        let result = sqlx::query!(r#"DELETE FROM sepa_mandate WHERE user_id = $1"#, **user_id)
+            .execute(&self.db)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn get_fees(&self, year: i32) -> AppResult<Vec<MembershipFee>> {
+        Ok(sqlx::query_as!(
+            MembershipFee,
+            r#"
+            SELECT membership as "membership: Membership", amount
+            FROM membership_fee
+            WHERE year = $1
+            ORDER BY membership
+            "#,
+            year
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    /// Replaces the fees of the year
+    pub async fn set_fees(&self, year: i32, fees: Vec<MembershipFee>) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(r#"DELETE FROM membership_fee WHERE year = $1"#, year)
+            .execute(&mut *tx)
+            .await?;
+
+        let (memberships, amounts): (Vec<_>, Vec<_>) = fees
+            .into_iter()
+            .map(|fee| (fee.membership, fee.amount))
+            .unzip();
+        sqlx::query!(
+            r#"
+            INSERT INTO membership_fee (year, membership, amount)
+            SELECT $1, f.membership, f.amount
+            FROM unnest($2::membership[], $3::int[]) AS f(membership, amount)
+            "#,
+            year,
+            memberships as Vec<Membership>,
+            &amounts
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_collections_where(
+        conn: &mut PgConnection,
+        batch_id: Option<&CollectionBatchId>,
+        year: Option<i32>,
+    ) -> AppResult<Vec<FeeCollection>> {
+        Ok(sqlx::query_as!(
+            PgFeeCollection,
+            r#"
+            SELECT c.id,
+                   c.batch_id,
+                   c.user_id,
+                   u.first_name as "first_name?",
+                   u.infix,
+                   u.last_name as "last_name?",
+                   c.year,
+                   c.amount,
+                   c.account_holder,
+                   c.iban,
+                   c.mandate_id,
+                   c.signed,
+                   c.sequence_type as "sequence_type: SequenceType",
+                   c.status as "status: CollectionStatus",
+                   c.updated
+            FROM fee_collection c
+                LEFT JOIN "user" u ON c.user_id = u.id
+            WHERE ($1::uuid IS NULL OR c.batch_id = $1)
+              AND ($2::int IS NULL OR c.year = $2)
+            ORDER BY u.last_name, u.first_name, c.updated
+            "#,
+            batch_id.map(|id| **id),
+            year
+        )
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Collection status per member for the year
+    pub async fn get_collections(&self, year: i32) -> AppResult<Vec<FeeCollection>> {
+        Self::get_collections_where(&mut *self.db.acquire().await?, None, Some(year)).await
+    }
+
+    pub async fn get_batch(&self, id: &CollectionBatchId) -> AppResult<CollectionBatch> {
+        let mut conn = self.db.acquire().await?;
+        let batch = sqlx::query!(
+            r#"SELECT id, year, collection_date, created FROM collection_batch WHERE id = $1"#,
+            **id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(CollectionBatch {
+            id: batch.id.into(),
+            year: batch.year,
+            collection_date: batch.collection_date,
+            created: batch.created,
+            collections: Self::get_collections_where(&mut conn, Some(id), None).await?,
+        })
+    }
+
+    /// Collects the fee of the year from every accepted member with a mandate,
+    /// unless it has been collected already or is still pending
+    pub async fn create_batch(
+        &self,
+        year: i32,
+        collection_date: Date,
+        created_by: &UserId,
+    ) -> AppResult<CollectionBatch> {
+        let id: CollectionBatchId = Uuid::now_v7().into();
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO collection_batch (id, year, collection_date, created_by)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            *id,
+            year,
+            collection_date,
+            **created_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO fee_collection (id, batch_id, user_id, year, amount, account_holder, iban, mandate_id, signed, sequence_type)
+            SELECT gen_random_uuid(),
+                   $1,
+                   u.id,
+                   $2,
+                   f.amount,
+                   m.account_holder,
+                   m.iban,
+                   m.mandate_id,
+                   m.signed,
+                   CASE
+                       WHEN EXISTS(SELECT 1
+                                   FROM fee_collection c
+                                   WHERE c.mandate_id = m.mandate_id
+                                     AND c.status <> 'failed') THEN 'recurring'::sequence_type
+                       ELSE 'first'::sequence_type
+                   END
+            FROM "user" u
+                JOIN sepa_mandate m ON m.user_id = u.id
+                JOIN membership_fee f ON f.year = $2 AND f.membership = u.membership
+            WHERE u.status = 'accepted'
+              AND f.amount > 0
+              AND NOT EXISTS(SELECT 1
+                             FROM fee_collection c
+                             WHERE c.user_id = u.id
+                               AND c.year = $2
+                               AND c.status <> 'failed')
+            "#,
+            *id,
+            year
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::BadRequest("Nothing to collect"));
+        }
+
+        tx.commit().await?;
+
+        info!(
+            batch_id = id.to_string(),
+            year,
+            collections = result.rows_affected(),
+            "Created direct debit batch"
+        );
+        self.get_batch(&id).await
+    }
+
+    pub async fn set_collection_status(
+        &self,
+        id: &FeeCollectionId,
+        status: CollectionStatus,
+    ) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE fee_collection SET status = $2, updated = now() WHERE id = $1
+            "#,
+            **id,
+            status as CollectionStatus
+        )
+        .execute(&self.db)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+}
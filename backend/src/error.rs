@@ -26,8 +26,12 @@ pub enum Error {
     SerdeJson(#[from] serde_json::error::Error),
     #[error("Bad request {0}")]
     BadRequest(&'static str),
+    #[error("Missing required certifications {0:?}")]
+    MissingCertifications(Vec<String>),
     #[error("Unauthorized")]
     Unauthorized,
+    #[error("Forbidden {0}")]
+    Forbidden(&'static str),
     #[error("JSON error {0}")]
     AxumJson(#[from] JsonRejection),
     #[error("Query error {0}")]
@@ -50,7 +54,30 @@ pub enum Error {
     Path(#[from] object_store::path::Error),
     #[error("Image error")]
     Image(#[from] image::ImageError),
+    #[error("Payment provider error {0}")]
+    PaymentProvider(String),
 }
+
+impl Error {
+    /// Whether the request was refused by a business rule, e.g. because an event is full,
+    /// closed or one is already registered, as opposed to something going wrong
+    pub fn is_refusal(&self) -> bool {
+        matches!(
+            self,
+            Error::BadRequest(_)
+                | Error::MissingCertifications(_)
+                | Error::Conflict(_)
+                | Error::NotFound
+        )
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Error::PaymentProvider(value.to_string())
+    }
+}
+
 impl From<sqlx::Error> for Error {
     fn from(value: sqlx::Error) -> Self {
         match value {
@@ -101,6 +128,17 @@ impl IntoResponse for Error {
                     reference,
                 }
             }
+            Error::MissingCertifications(names) => {
+                trace!(%reference, "Missing required certifications: {names:?}");
+                Problem {
+                    message: format!(
+                        "Bad request: Missing required certifications: {}",
+                        names.join(", ")
+                    ),
+                    status: StatusCode::BAD_REQUEST,
+                    reference,
+                }
+            }
             Error::Unauthorized => {
                 trace!(%reference, "Unauthorized");
                 Problem {
@@ -109,6 +147,14 @@ impl IntoResponse for Error {
                     reference,
                 }
             }
+            Error::Forbidden(err) => {
+                trace!(%reference, "Forbidden: {err}");
+                Problem {
+                    message: format!("Forbidden: {err}"),
+                    status: StatusCode::FORBIDDEN,
+                    reference,
+                }
+            }
             Error::AxumJson(err) => {
                 trace!(%reference, "Json error: {err:?}");
                 Problem {
@@ -205,6 +251,14 @@ impl IntoResponse for Error {
                     reference,
                 }
             }
+            Error::PaymentProvider(err) => {
+                error!(%reference, "Payment provider error: {err}");
+                Problem {
+                    message: "Payment provider error".to_string(),
+                    status: StatusCode::BAD_GATEWAY,
+                    reference,
+                }
+            }
         }
         .into_response()
     }
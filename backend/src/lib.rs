@@ -2,6 +2,7 @@ mod api;
 mod auth;
 mod data_source;
 mod error;
+pub mod payment_provider;
 mod router;
 mod state;
 mod wire;
@@ -15,6 +15,7 @@ async fn main() {
 
     let state = AppState::new().await.unwrap();
     // sqlx::migrate!().run(state.pool()).await.unwrap();
+    state.spawn_background_tasks();
 
     let app = create_router(state);
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
@@ -0,0 +1,88 @@
+mod mollie;
+
+use crate::error::AppResult;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub use mollie::MollieProvider;
+
+/// Header with the hex encoded HMAC-SHA256 of the webhook body, prefixed with `sha256=`
+pub const SIGNATURE_HEADER: &str = "X-Mollie-Signature";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderStatus {
+    Open,
+    Paid,
+    Failed,
+    Expired,
+    Canceled,
+}
+
+#[derive(Debug)]
+pub struct NewPayment {
+    /// In euro cents
+    pub amount: i32,
+    pub description: String,
+    /// Where the member returns after the checkout
+    pub redirect_url: String,
+    /// Called by the provider whenever the status changes
+    pub webhook_url: String,
+}
+
+#[derive(Debug)]
+pub struct ProviderPayment {
+    pub id: String,
+    pub status: ProviderStatus,
+    /// In cents of the currency
+    pub amount: i32,
+    pub currency: String,
+    pub checkout_url: Option<String>,
+}
+
+/// A payment service provider that handles iDEAL payments
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    async fn create_payment(&self, payment: NewPayment) -> AppResult<ProviderPayment>;
+
+    async fn get_payment(&self, id: &str) -> AppResult<ProviderPayment>;
+
+    /// Refunds the amount of a paid payment, returns the id of the refund
+    async fn refund(&self, id: &str, amount: i32) -> AppResult<String>;
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(Ok(signature)) = signature.strip_prefix("sha256=").map(hex::decode) else {
+        return false;
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    // Constant time comparison
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Amounts are sent as a string with two decimals, e.g. `"12.50"`
+fn format_amount(cents: i32) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Parses an amount formatted by [`format_amount`] back into cents
+fn parse_amount(value: &str) -> Option<i32> {
+    let (units, cents) = value.split_once('.')?;
+    if cents.len() != 2 || !cents.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    units
+        .parse::<i32>()
+        .ok()?
+        .checked_mul(100)?
+        .checked_add(cents.parse().ok()?)
+}
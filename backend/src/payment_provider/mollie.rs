@@ -0,0 +1,166 @@
+use crate::{
+    error::{AppResult, Error},
+    payment_provider::{
+        NewPayment, PaymentProvider, ProviderPayment, ProviderStatus, format_amount, parse_amount,
+    },
+};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Client for the Mollie payments API, or a local mock that speaks the same API
+pub struct MollieProvider {
+    client: Client,
+    api_url: String,
+    api_key: String,
+}
+
+impl MollieProvider {
+    pub fn new(api_url: String, api_key: String) -> Self {
+        Self {
+            client: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Amount {
+    currency: &'static str,
+    value: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatePayment<'a> {
+    amount: Amount,
+    description: &'a str,
+    redirect_url: &'a str,
+    webhook_url: &'a str,
+    method: &'static str,
+}
+
+#[derive(Serialize)]
+struct CreateRefund {
+    amount: Amount,
+}
+
+#[derive(Deserialize)]
+struct PaymentAmount {
+    currency: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct Links {
+    checkout: Option<Link>,
+}
+
+#[derive(Deserialize)]
+struct MolliePayment {
+    id: String,
+    status: String,
+    amount: PaymentAmount,
+    #[serde(rename = "_links")]
+    links: Links,
+}
+
+#[derive(Deserialize)]
+struct MollieRefund {
+    id: String,
+}
+
+impl TryFrom<MolliePayment> for ProviderPayment {
+    type Error = Error;
+
+    fn try_from(payment: MolliePayment) -> Result<Self, Self::Error> {
+        let status = match payment.status.as_str() {
+            "open" | "pending" | "authorized" => ProviderStatus::Open,
+            "paid" => ProviderStatus::Paid,
+            "failed" => ProviderStatus::Failed,
+            "expired" => ProviderStatus::Expired,
+            "canceled" => ProviderStatus::Canceled,
+            other => {
+                return Err(Error::PaymentProvider(format!(
+                    "Unknown payment status {other}"
+                )));
+            }
+        };
+        let amount = parse_amount(&payment.amount.value).ok_or_else(|| {
+            Error::PaymentProvider(format!("Invalid payment amount {}", payment.amount.value))
+        })?;
+        Ok(Self {
+            id: payment.id,
+            status,
+            amount,
+            currency: payment.amount.currency,
+            checkout_url: payment.links.checkout.map(|link| link.href),
+        })
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for MollieProvider {
+    async fn create_payment(&self, payment: NewPayment) -> AppResult<ProviderPayment> {
+        let created: MolliePayment = self
+            .client
+            .post(format!("{}/v2/payments", self.api_url))
+            .bearer_auth(&self.api_key)
+            .json(&CreatePayment {
+                amount: Amount {
+                    currency: "EUR",
+                    value: format_amount(payment.amount),
+                },
+                description: &payment.description,
+                redirect_url: &payment.redirect_url,
+                webhook_url: &payment.webhook_url,
+                method: "ideal",
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        debug!(payment_id = created.id, "Created payment");
+        created.try_into()
+    }
+
+    async fn get_payment(&self, id: &str) -> AppResult<ProviderPayment> {
+        self.client
+            .get(format!("{}/v2/payments/{id}", self.api_url))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<MolliePayment>()
+            .await?
+            .try_into()
+    }
+
+    async fn refund(&self, id: &str, amount: i32) -> AppResult<String> {
+        let refund: MollieRefund = self
+            .client
+            .post(format!("{}/v2/payments/{id}/refunds", self.api_url))
+            .bearer_auth(&self.api_key)
+            .json(&CreateRefund {
+                amount: Amount {
+                    currency: "EUR",
+                    value: format_amount(amount),
+                },
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        debug!(payment_id = id, refund_id = refund.id, "Refunded payment");
+        Ok(refund.id)
+    }
+}
@@ -1,23 +1,41 @@
 use crate::{
     api::{
-        add_user_to_committee, create_committee, create_event, create_location, create_page,
-        create_registration, delete_committee, delete_event, delete_location, delete_page,
-        delete_registration, delete_user, get_activities, get_all_users, get_committee,
-        get_committee_members, get_committees, get_event, get_event_registrations,
-        get_file_content, get_file_metadata, get_files, get_location, get_locations,
-        get_material_list, get_page_by_slug, get_pages, get_registration, get_user,
-        get_user_committees, get_user_events, get_user_materials, get_user_registrations,
-        location_used_by, make_chair, register, remove_user_from_committee, update_committee,
-        update_event, update_location, update_page, update_pwd, update_registration, update_user,
-        update_user_material, upload, who_am_i,
+        add_user_to_committee, approve_cancellation, assign_passengers, cancel_event, check_in,
+        clone_event, create_api_token, create_certification, create_checkout,
+        create_collection_batch, create_committee, create_event, create_event_from_template,
+        create_event_series, create_event_template, create_feedback_response, create_location,
+        create_logbook_entry, create_page, create_registration, create_series_registration,
+        delete_api_token, delete_certification, delete_committee, delete_event,
+        delete_event_series, delete_event_template, delete_feedback_form, delete_location,
+        delete_logbook_entry, delete_page, delete_registration, delete_sepa_mandate, delete_user,
+        export_collection_batch, get_activities, get_all_users, get_api_tokens, get_attendance,
+        get_carpool, get_certifications, get_check_in_token, get_collection_batch, get_committee,
+        get_committee_members, get_committees, get_event, get_event_certifications,
+        get_event_organisers, get_event_payments, get_event_registrations, get_event_series,
+        get_event_template, get_event_templates, get_fee_collections, get_feedback_form,
+        get_feedback_results, get_file_content, get_file_metadata, get_files,
+        get_late_cancellations, get_location, get_locations, get_logbook, get_lottery_draw,
+        get_material_list, get_membership_fees, get_metadata_schema, get_notifications,
+        get_page_by_slug, get_pages, get_question_summary, get_registration, get_registration_car,
+        get_sepa_mandate, get_user, get_user_certifications, get_user_committees, get_user_events,
+        get_user_history, get_user_materials, get_user_payments, get_user_registrations,
+        issue_certification, location_used_by, make_chair, mark_notification_read, payment_webhook,
+        register, reject_cancellation, remove_event_organiser, remove_user_from_committee,
+        revoke_user_certification, send_feedback_form, set_event_organiser, set_feedback_form,
+        set_membership_fees, set_sepa_mandate, start_impersonation, stop_impersonation,
+        update_attendance, update_certification, update_committee, update_event,
+        update_event_series, update_event_template, update_fee_collection, update_location,
+        update_logbook_entry, update_page, update_payment, update_pwd, update_registration,
+        update_user, update_user_material, upload, upload_answer_file, who_am_i,
     },
-    auth::{login, logout},
+    auth::{csrf::csrf_protection, login, logout},
     state::AppState,
 };
 use axum::{
     Json, Router,
     extract::{DefaultBodyLimit, State},
-    routing::{get, post, put},
+    middleware,
+    routing::{delete, get, post, put},
 };
 use memory_serve::{MemoryServe, load_assets};
 use tower_http::{trace, trace::TraceLayer};
@@ -49,6 +67,11 @@ fn api_router() -> Router<AppState> {
         // The `POST /file` endpoint has a size limit of 50 MB,
         // instead of the default 2MB other endpoints have
         .route("/file", post(upload).layer(DefaultBodyLimit::max(52428800)))
+        // Files answering registration questions can be uploaded by anyone, up to 10 MB
+        .route(
+            "/file/answer",
+            post(upload_answer_file).layer(DefaultBodyLimit::max(10485760)),
+        )
         .route("/file", get(get_files))
         .route("/file/{:id}", get(get_file_content))
         .route("/file/{:id}/metadata", get(get_file_metadata))
@@ -58,20 +81,121 @@ fn api_router() -> Router<AppState> {
             get(get_user).put(update_user).delete(delete_user),
         )
         .route("/user/{:id}/password", post(update_pwd))
+        .route("/user/{:id}/impersonation", post(start_impersonation))
+        .route("/impersonation", delete(stop_impersonation))
+        .route(
+            "/user/{:id}/api_token",
+            get(get_api_tokens).post(create_api_token),
+        )
+        .route(
+            "/user/{:id}/api_token/{:token_id}",
+            delete(delete_api_token),
+        )
         .route(
             "/user/{:id}/event_registrations",
             get(get_user_registrations),
         )
         .route("/user/{:id}/events", get(get_user_events))
         .route("/user/{:id}/committees", get(get_user_committees))
+        .route("/user/{:id}/payment", get(get_user_payments))
+        .route("/user/{:id}/history", get(get_user_history))
+        .route("/user/{:id}/certification", get(get_user_certifications))
+        .route(
+            "/user/{:id}/certification/{:user_certification_id}",
+            delete(revoke_user_certification),
+        )
+        .route(
+            "/user/{:id}/logbook",
+            get(get_logbook).post(create_logbook_entry),
+        )
+        .route(
+            "/user/{:id}/logbook/{:entry_id}",
+            put(update_logbook_entry).delete(delete_logbook_entry),
+        )
+        .route(
+            "/user/{:id}/sepa_mandate",
+            get(get_sepa_mandate)
+                .put(set_sepa_mandate)
+                .delete(delete_sepa_mandate),
+        )
+        .route(
+            "/membership_fee/{:year}",
+            get(get_membership_fees).put(set_membership_fees),
+        )
+        .route(
+            "/membership_fee/{:year}/collection",
+            get(get_fee_collections).post(create_collection_batch),
+        )
+        .route("/collection_batch/{:id}", get(get_collection_batch))
+        .route(
+            "/collection_batch/{:id}/pain008",
+            get(export_collection_batch),
+        )
+        .route("/fee_collection/{:id}", put(update_fee_collection))
+        .route("/user/{:id}/notification", get(get_notifications))
+        .route(
+            "/user/{:id}/notification/{:notification_id}/read",
+            post(mark_notification_read),
+        )
         .route("/user/{:id}/material", get(get_material_list))
         .route("/user/{:id}/getMaterial", get(get_user_materials))
         .route("/user/{:id}/material/update", put(update_user_material))
         .route("/event", get(get_activities).post(create_event))
+        .route(
+            "/event_type/{:event_type}/metadata_schema",
+            get(get_metadata_schema),
+        )
         .route(
             "/event/{:id}",
             get(get_event).put(update_event).delete(delete_event),
         )
+        .route("/event/{:id}/cancel", post(cancel_event))
+        .route("/event/{:id}/clone", post(clone_event))
+        .route("/event/{:id}/lottery", get(get_lottery_draw))
+        .route("/event/{:id}/organiser", get(get_event_organisers))
+        .route("/event/{:id}/questions/summary", get(get_question_summary))
+        .route(
+            "/event/{:id}/organiser/{:user_id}",
+            put(set_event_organiser).delete(remove_event_organiser),
+        )
+        .route(
+            "/event/{:id}/late_cancellation",
+            get(get_late_cancellations),
+        )
+        .route("/event/{:id}/payment", get(get_event_payments))
+        .route("/event/{:id}/check_in", post(check_in))
+        .route(
+            "/event/{:id}/certification",
+            get(get_event_certifications).post(issue_certification),
+        )
+        .route(
+            "/certification",
+            get(get_certifications).post(create_certification),
+        )
+        .route(
+            "/certification/{:id}",
+            put(update_certification).delete(delete_certification),
+        )
+        .route(
+            "/event/{:id}/attendance",
+            get(get_attendance).put(update_attendance),
+        )
+        .route(
+            "/event/{:id}/feedback",
+            get(get_feedback_form)
+                .put(set_feedback_form)
+                .delete(delete_feedback_form),
+        )
+        .route("/event/{:id}/feedback/send", post(send_feedback_form))
+        .route(
+            "/event/{:id}/feedback/response",
+            post(create_feedback_response),
+        )
+        .route("/event/{:id}/feedback/results", get(get_feedback_results))
+        .route(
+            "/event/{:id}/carpool",
+            get(get_carpool).put(assign_passengers),
+        )
         .route(
             "/event/{:event_id}/registration",
             get(get_event_registrations).post(create_registration),
@@ -82,6 +206,42 @@ fn api_router() -> Router<AppState> {
                 .put(update_registration)
                 .delete(delete_registration),
         )
+        .route(
+            "/event/{:event_id}/registration/{:registration_id}/cancellation/approve",
+            post(approve_cancellation),
+        )
+        .route(
+            "/event/{:event_id}/registration/{:registration_id}/cancellation/reject",
+            post(reject_cancellation),
+        )
+        .route(
+            "/event/{:event_id}/registration/{:registration_id}/payment",
+            put(update_payment),
+        )
+        .route(
+            "/event/{:event_id}/registration/{:registration_id}/checkout",
+            post(create_checkout),
+        )
+        .route(
+            "/event/{:event_id}/registration/{:registration_id}/check_in_token",
+            get(get_check_in_token),
+        )
+        .route(
+            "/event/{:event_id}/registration/{:registration_id}/car",
+            get(get_registration_car),
+        )
+        .route("/event_series", post(create_event_series))
+        .route("/payment/webhook", post(payment_webhook))
+        .route(
+            "/event_series/{:id}",
+            get(get_event_series)
+                .put(update_event_series)
+                .delete(delete_event_series),
+        )
+        .route(
+            "/event_series/{:id}/registration",
+            post(create_series_registration),
+        )
         .route("/location", get(get_locations).post(create_location))
         .route(
             "/location/{:id}",
@@ -97,6 +257,20 @@ fn api_router() -> Router<AppState> {
                 .put(update_committee)
                 .delete(delete_committee),
         )
+        .route(
+            "/committee/{:id}/event_template",
+            get(get_event_templates).post(create_event_template),
+        )
+        .route(
+            "/committee/{:id}/event_template/{:template_id}",
+            get(get_event_template)
+                .put(update_event_template)
+                .delete(delete_event_template),
+        )
+        .route(
+            "/committee/{:id}/event_template/{:template_id}/event",
+            post(create_event_from_template),
+        )
         .route(
             "/committee/{:id}/user/{:user_id}",
             post(add_user_to_committee).delete(remove_user_from_committee),
@@ -106,6 +280,7 @@ fn api_router() -> Router<AppState> {
         .route("/page", get(get_pages).post(create_page))
         .route("/page/{slug}", get(get_page_by_slug))
         .route("/page/id/{:id}", put(update_page).delete(delete_page))
+        .layer(middleware::from_fn(csrf_protection))
 }
 
 async fn version(State(state): State<AppState>) -> Json<String> {
@@ -1,13 +1,36 @@
-use crate::error::{AppResult, Error};
+use crate::{
+    auth::{check_in::CheckInSigner, password::Params, session::run_session_cleanup},
+    data_source::{event::run_scheduled_publishing, run_lottery_draws},
+    error::{AppResult, Error},
+    payment_provider::{MollieProvider, PaymentProvider},
+    sepa::Creditor,
+};
 use axum::{extract::FromRequestParts, http::request::Parts};
 use object_store::{ObjectStore, memory::InMemory};
+use rand::distr::{Alphanumeric, SampleString};
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use std::{env, ops::Deref, sync::Arc};
-use tracing::error;
+use tracing::{error, warn};
 
 pub struct Config {
     database_url: String,
     pub version: String,
+    /// Parameters used to hash new passwords, weaker hashes are upgraded on login
+    pub argon2_params: Params,
+    /// Where the website is reachable, used in links handed to external services
+    pub public_url: String,
+    /// Online payments are disabled if no payment provider is configured
+    payment: Option<PaymentConfig>,
+    /// Direct debit batches can only be exported if the creditor is configured
+    pub sepa_creditor: Option<Creditor>,
+    /// Signs the check-in QR codes, these become invalid when the secret changes
+    check_in_secret: String,
+}
+
+struct PaymentConfig {
+    api_url: String,
+    api_key: String,
+    webhook_secret: String,
 }
 
 impl Config {
@@ -16,14 +39,59 @@ impl Config {
         Ok(Self {
             database_url: env::var("DATABASE_URL").expect("DATABASE_URL env var must be set"),
             version: env::var("VERSION").unwrap_or_else(|_| "development".to_string()),
+            argon2_params: Params::new(
+                env_or("ARGON2_M_COST", Params::DEFAULT_M_COST),
+                env_or("ARGON2_T_COST", Params::DEFAULT_T_COST),
+                env_or("ARGON2_P_COST", Params::DEFAULT_P_COST),
+                None,
+            )
+            .expect(
+                "ARGON2_M_COST, ARGON2_T_COST and ARGON2_P_COST must be valid Argon2 parameters",
+            ),
+            public_url: env::var("PUBLIC_URL")
+                .unwrap_or_else(|_| "http://localhost:3000".to_string()),
+            payment: env::var("PAYMENT_API_KEY")
+                .ok()
+                .map(|api_key| PaymentConfig {
+                    api_url: env::var("PAYMENT_API_URL")
+                        .unwrap_or_else(|_| "https://api.mollie.com".to_string()),
+                    api_key,
+                    webhook_secret: env::var("PAYMENT_WEBHOOK_SECRET").expect(
+                        "PAYMENT_WEBHOOK_SECRET env var must be set if PAYMENT_API_KEY is set",
+                    ),
+                }),
+            sepa_creditor: env::var("SEPA_CREDITOR_ID").ok().map(|id| Creditor {
+                name: env::var("SEPA_CREDITOR_NAME")
+                    .expect("SEPA_CREDITOR_NAME env var must be set if SEPA_CREDITOR_ID is set"),
+                iban: env::var("SEPA_CREDITOR_IBAN")
+                    .expect("SEPA_CREDITOR_IBAN env var must be set if SEPA_CREDITOR_ID is set"),
+                bic: env::var("SEPA_CREDITOR_BIC")
+                    .expect("SEPA_CREDITOR_BIC env var must be set if SEPA_CREDITOR_ID is set"),
+                id,
+            }),
+            check_in_secret: env::var("CHECK_IN_SECRET").unwrap_or_else(|_| {
+                warn!("CHECK_IN_SECRET is not set, check-in codes are only valid until a restart");
+                Alphanumeric.sample_string(&mut rand::rng(), 32)
+            }),
         })
     }
 }
 
+fn env_or(name: &str, default: u32) -> u32 {
+    env::var(name)
+        .map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{name} env var must be a number"))
+        })
+        .unwrap_or(default)
+}
+
 #[derive(Clone)]
 pub struct AppState {
     pool: PgPool,
     object_store: Arc<dyn ObjectStore>,
+    payment_provider: Option<Arc<dyn PaymentProvider>>,
     config: Arc<Config>,
 }
 
@@ -40,6 +108,28 @@ impl AppState {
         Arc::clone(&self.object_store)
     }
 
+    pub fn payment_provider(&self) -> Option<Arc<dyn PaymentProvider>> {
+        self.payment_provider.clone()
+    }
+
+    pub fn check_in_signer(&self) -> CheckInSigner {
+        CheckInSigner::new(self.config.check_in_secret.clone())
+    }
+
+    pub fn webhook_secret(&self) -> Option<&str> {
+        self.config
+            .payment
+            .as_ref()
+            .map(|payment| payment.webhook_secret.as_str())
+    }
+
+    /// Spawns the periodic maintenance tasks that run next to the web server
+    pub fn spawn_background_tasks(&self) {
+        tokio::spawn(run_session_cleanup(self.pool.clone()));
+        tokio::spawn(run_scheduled_publishing(self.pool.clone()));
+        tokio::spawn(run_lottery_draws(self.pool.clone(), self.into()));
+    }
+
     pub async fn new() -> AppResult<Self> {
         let config = Config::from_env()?;
         let pool = PgPoolOptions::new()
@@ -51,9 +141,17 @@ impl AppState {
         // TODO use a proper storage layer
         let object_store = InMemory::new();
 
+        let payment_provider = config.payment.as_ref().map(|payment| {
+            Arc::new(MollieProvider::new(
+                payment.api_url.clone(),
+                payment.api_key.clone(),
+            )) as Arc<dyn PaymentProvider>
+        });
+
         Ok(Self {
             pool,
             object_store: Arc::new(object_store),
+            payment_provider,
             config: Arc::new(config),
         })
     }
@@ -77,3 +175,14 @@ impl FromRequestParts<AppState> for PgPool {
         Ok(state.pool.clone())
     }
 }
+
+impl FromRequestParts<AppState> for Params {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(state.config.argon2_params.clone())
+    }
+}
@@ -0,0 +1,61 @@
+use crate::event::RegistrationId;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use validator::Validate;
+
+/// Signed token of a registration, shown to the participant as a QR code
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckInToken {
+    pub token: String,
+}
+
+/// A scanned QR code, scanners without a connection upload their scans later
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct Scan {
+    #[validate(length(max = 200))]
+    pub token: String,
+    /// When the code was scanned, defaults to the moment the scan is received
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub scanned: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanStatus {
+    CheckedIn,
+    /// Scanned before, the earliest scan is kept
+    AlreadyCheckedIn,
+    /// Not a valid token, or not a registration of this event
+    Invalid,
+    /// The registration was cancelled or is on the waiting list
+    Rejected,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanResult {
+    pub token: String,
+    pub registration_id: Option<RegistrationId>,
+    pub status: ScanStatus,
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct Attendance {
+    pub registration_id: RegistrationId,
+    pub attended: Option<bool>,
+}
+
+/// Attendance of the participants that have a place, people on the waiting list are not counted
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceSummary {
+    pub participants: i64,
+    pub checked_in: i64,
+    pub attended: i64,
+    pub absent: i64,
+    /// Attendance has not been registered yet
+    pub unknown: i64,
+}
@@ -0,0 +1,48 @@
+use crate::{
+    event::{Car, RegistrationId},
+    user::BasicUser,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// A placed registration travelling together, the participant and their guests
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Passenger {
+    pub registration_id: RegistrationId,
+    pub user: Option<BasicUser>,
+    /// Names of the guests
+    pub guests: Vec<String>,
+    pub places: i32,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CarpoolCar {
+    pub driver: Passenger,
+    #[serde(flatten)]
+    pub car: Car,
+    pub passengers: Vec<Passenger>,
+    /// Negative when the driver offers fewer seats than were assigned
+    pub free_seats: i32,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Carpool {
+    pub cars: Vec<CarpoolCar>,
+    /// Participants without a car that are not assigned to a driver yet
+    pub unassigned: Vec<Passenger>,
+    pub seats_offered: i64,
+    /// Places taken by participants that do not drive themselves
+    pub seats_needed: i64,
+    pub shortfall: i64,
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CarAssignment {
+    pub registration_id: RegistrationId,
+    /// The registration of the driver, none to unassign
+    pub driver: Option<RegistrationId>,
+}
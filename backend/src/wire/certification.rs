@@ -0,0 +1,99 @@
+use crate::{
+    Language,
+    event::EventId,
+    user::{BasicUser, UserId},
+    wire::iso_date,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[serde(transparent)]
+pub struct CertificationId(Uuid);
+
+impl From<Uuid> for CertificationId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl Deref for CertificationId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[serde(transparent)]
+pub struct UserCertificationId(Uuid);
+
+impl From<Uuid> for UserCertificationId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl Deref for UserCertificationId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A skill granted by a course, e.g. lead climbing or glacier travel
+#[derive(Serialize, Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificationContent {
+    #[validate(nested)]
+    pub name: Language,
+    #[validate(nested)]
+    #[serde(default)]
+    pub description: Language,
+    /// How long the certification stays valid after it is issued, it never expires if absent
+    #[validate(range(min = 1, max = 240, message = "Validity is at most 20 years"))]
+    pub validity_months: Option<i32>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Certification {
+    pub id: CertificationId,
+    #[serde(flatten)]
+    pub content: CertificationContent,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
+
+/// A certification a member earned
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UserCertification {
+    pub id: UserCertificationId,
+    pub certification_id: CertificationId,
+    pub name: Language,
+    pub user: BasicUser,
+    /// The course the certification was earned at
+    pub event_id: Option<EventId>,
+    #[serde(with = "iso_date")]
+    pub issued: Date,
+    #[serde(with = "iso_date::option")]
+    pub expires: Option<Date>,
+    pub issued_by: Option<UserId>,
+}
+
+/// Issues a certification to attendees of a completed course
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueCertification {
+    pub certification_id: CertificationId,
+    /// Defaults to everyone that attended the course
+    pub user_ids: Option<Vec<UserId>>,
+}
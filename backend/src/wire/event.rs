@@ -1,19 +1,28 @@
 use crate::{
     Language,
     auth::role::Membership,
+    certification::CertificationId,
+    committee::CommitteeId,
     error::Error,
+    event_metadata::EventMetadata,
+    event_series::EventSeriesId,
     file::FileId,
+    location::LocationId,
+    payment::{PaymentStatus, PricedExtra, Pricing},
     user::{BasicUser, UserId},
 };
 use derive_more::{Display, From, Into};
 use serde::{Deserialize, Serialize};
-use serde_with::skip_serializing_none;
-use std::{borrow::Cow, ops::Deref, str::FromStr};
+use serde_with::{DisplayFromStr, serde_as, skip_serializing_none};
+use std::{borrow::Cow, collections::HashMap, ops::Deref, str::FromStr};
 use strum_macros::IntoStaticStr;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
+/// Fixed id of the question asking non-members for their name
+pub const NON_MEMBER_NAME_QUESTION_ID: &str = "8d3d4e48-4e8f-4e15-a7d9-6ff5e4c8e8ad";
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, From, Display, Into)]
 #[serde(transparent)]
 pub struct EventId(Uuid);
@@ -22,6 +31,10 @@ pub struct EventId(Uuid);
 #[serde(transparent)]
 pub struct RegistrationId(Uuid);
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, From, Display, Into)]
+#[serde(transparent)]
+pub struct LateCancellationId(Uuid);
+
 impl Deref for EventId {
     type Target = Uuid;
 
@@ -38,6 +51,14 @@ impl Deref for RegistrationId {
     }
 }
 
+impl Deref for LateCancellationId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 #[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, IntoStaticStr)]
 #[serde(rename_all = "camelCase")]
 pub enum ActivityType {
@@ -66,6 +87,19 @@ impl FromStr for ActivityType {
     }
 }
 
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "event_status", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum EventStatus {
+    /// Only visible to the board and the organising committee
+    Draft,
+    Published,
+    /// Registrations are kept, but no new ones are accepted
+    Cancelled,
+    /// Hidden from the default event list
+    Archived,
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Debug, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -80,13 +114,23 @@ where
     pub updated: OffsetDateTime,
     pub registration_count: i64,
     pub waiting_list_count: i64,
+    pub cancellation_reason: Option<String>,
+    /// Set if this is an occurrence of a recurring event
+    pub series_id: Option<EventSeriesId>,
+    /// When each membership with a staged opening can sign up
+    pub registration_windows: Vec<RegistrationWindow>,
+    /// Set once the lottery has been drawn
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub lottery_drawn: Option<OffsetDateTime>,
     #[serde(flatten)]
     #[validate(nested)]
     pub content: EventContent<T>,
 }
 
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Validate)]
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[validate(schema(function = "validate_pricing"))]
+#[validate(schema(function = "validate_metadata"))]
 #[serde(rename_all = "camelCase")]
 pub struct EventContent<T> {
     #[validate(nested)]
@@ -98,22 +142,198 @@ pub struct EventContent<T> {
     #[validate(nested)]
     pub dates: Vec<Date>,
     pub registration_period: Option<Date>,
+    /// Last moment to cancel without approval of the organisers,
+    /// defaults to the end of the registration period
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub cancellation_deadline: Option<OffsetDateTime>,
     #[validate(range(min = 0, max = 999, message = "Maximum registrations is 999"))]
     pub registration_max: Option<i32>,
     #[validate(range(min = 0, max = 999, message = "Maximum waiting list is 999"))]
     pub waiting_list_max: Option<i32>,
-    pub is_published: bool,
+    /// Guests each member may bring, guests count toward the maximum and the waiting list
+    #[validate(range(min = 0, max = 20, message = "At most 20 guests per registration"))]
+    #[serde(default)]
+    pub max_guests: i32,
+    /// Registrations during the registration period enter a pool,
+    /// places are drawn by lot when the period closes
+    #[serde(default)]
+    pub lottery: bool,
+    pub status: EventStatus,
+    /// A draft is published automatically once this moment has passed
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub publish_at: Option<OffsetDateTime>,
     pub required_membership: Vec<Membership>,
+    /// Participants must hold these certifications when the event starts
+    #[serde(default)]
+    pub required_certifications: Vec<CertificationId>,
+    /// Quotas and staged opening per membership
+    #[validate(nested, custom(function = "validate_membership_rules"))]
+    #[serde(default)]
+    pub membership_rules: Vec<MembershipRule>,
+    #[validate(nested)]
+    #[serde(default)]
+    pub pricing: Pricing,
     pub event_type: ActivityType,
-    #[validate[nested]]
+    #[validate(nested, custom(function = "validate_questions"))]
     pub questions: Vec<Question>,
+    #[validate(nested)]
     #[serde(default)]
-    pub metadata: serde_json::Value,
+    pub metadata: EventMetadata,
     pub location: T,
     pub created_by: Uuid,
 }
 
-#[derive(Serialize, Deserialize, Debug, Validate)]
+impl<T> EventContent<T> {
+    /// Turns this into a new draft with the given dates.
+    /// Question ids are regenerated, so answers never collide between events
+    pub fn into_copy<L>(self, copy: EventCopy, location: L) -> EventContent<L> {
+        let non_member_name_question_id = Uuid::parse_str(NON_MEMBER_NAME_QUESTION_ID)
+            .expect("NON_MEMBER_NAME_QUESTION_ID must be a valid UUID");
+        let question_ids: HashMap<Uuid, Uuid> = self
+            .questions
+            .iter()
+            .map(|question| {
+                let id = if question.id == non_member_name_question_id {
+                    question.id
+                } else {
+                    Uuid::now_v7()
+                };
+                (question.id, id)
+            })
+            .collect();
+
+        EventContent {
+            name: self.name,
+            image: self.image,
+            description: self.description,
+            dates: copy.dates,
+            registration_period: copy.registration_period,
+            cancellation_deadline: None,
+            registration_max: self.registration_max,
+            waiting_list_max: self.waiting_list_max,
+            max_guests: self.max_guests,
+            lottery: self.lottery,
+            status: EventStatus::Draft,
+            publish_at: None,
+            required_membership: self.required_membership,
+            required_certifications: self.required_certifications,
+            membership_rules: self.membership_rules,
+            event_type: self.event_type,
+            pricing: Pricing {
+                extras: self
+                    .pricing
+                    .extras
+                    .into_iter()
+                    .map(|extra| PricedExtra {
+                        question_id: question_ids
+                            .get(&extra.question_id)
+                            .copied()
+                            .unwrap_or(extra.question_id),
+                        ..extra
+                    })
+                    .collect(),
+                ..self.pricing
+            },
+            questions: self
+                .questions
+                .into_iter()
+                .map(|question| Question {
+                    id: question_ids[&question.id],
+                    condition: question.condition.map(|condition| QuestionCondition {
+                        question_id: question_ids
+                            .get(&condition.question_id)
+                            .copied()
+                            .unwrap_or(condition.question_id),
+                        ..condition
+                    }),
+                    ..question
+                })
+                .collect(),
+            metadata: self.metadata,
+            location,
+            created_by: self.created_by,
+        }
+    }
+}
+
+impl<T> EventContent<T> {
+    pub fn membership_rule(&self, membership: Membership) -> Option<&MembershipRule> {
+        self.membership_rules
+            .iter()
+            .find(|rule| rule.membership == membership)
+    }
+
+    /// The moment registrations open for the given membership,
+    /// or `None` if registrations are not open at all
+    pub fn registration_opens(&self, membership: Membership) -> Option<OffsetDateTime> {
+        let period = self.registration_period.as_ref()?;
+        let delay = self
+            .membership_rule(membership)
+            .map_or(0, |rule| rule.opens_after_hours);
+        Some(period.start + Duration::hours(delay as i64))
+    }
+
+    pub fn cancellation_deadline(&self) -> Option<OffsetDateTime> {
+        self.cancellation_deadline
+            .or(self.registration_period.as_ref().map(|period| period.end))
+    }
+
+    pub fn registration_windows(&self) -> Vec<RegistrationWindow> {
+        self.membership_rules
+            .iter()
+            .filter_map(|rule| {
+                Some(RegistrationWindow {
+                    membership: rule.membership,
+                    opens: self.registration_opens(rule.membership)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct MembershipRule {
+    pub membership: Membership,
+    /// Maximum number of registrations with this membership, the rest goes to the waiting list
+    #[validate(range(min = 0, max = 999, message = "Quota is at most 999"))]
+    pub quota: Option<i32>,
+    /// Hours after the start of the registration period before this membership can sign up
+    #[validate(range(min = 0, max = 8760, message = "Opening can be delayed at most a year"))]
+    #[serde(default)]
+    pub opens_after_hours: i32,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationWindow {
+    pub membership: Membership,
+    #[serde(with = "time::serde::rfc3339")]
+    pub opens: OffsetDateTime,
+}
+
+fn validate_pricing<T>(content: &EventContent<T>) -> Result<(), ValidationError> {
+    content.pricing.validate_extras(&content.questions)
+}
+
+fn validate_metadata<T>(content: &EventContent<T>) -> Result<(), ValidationError> {
+    content.metadata.validate_for(content.event_type)
+}
+
+fn validate_membership_rules(rules: &[MembershipRule]) -> Result<(), ValidationError> {
+    let duplicate = rules
+        .iter()
+        .enumerate()
+        .any(|(i, rule)| rules[..i].iter().any(|r| r.membership == rule.membership));
+    if duplicate {
+        Err(ValidationError::new("membership_rules")
+            .with_message(Cow::Borrowed("Each membership can only have one rule")))
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
 #[validate(schema(function = "validate_date"))]
 #[serde(rename_all = "camelCase")]
 pub struct Date {
@@ -123,6 +343,79 @@ pub struct Date {
     pub end: OffsetDateTime,
 }
 
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EventPeriod {
+    /// Events that have not ended yet
+    Upcoming,
+    Past,
+}
+
+#[serde_as]
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+    /// If not given, all but archived events are returned
+    pub status: Option<EventStatus>,
+    pub period: Option<EventPeriod>,
+    /// Events that end after this moment
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    /// Events that start before this moment
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+    pub event_type: Option<ActivityType>,
+    pub committee: Option<CommitteeId>,
+    pub location: Option<LocationId>,
+    /// Events open to this membership
+    pub membership: Option<Membership>,
+    /// Searches the name and description in both languages
+    #[validate(length(min = 1, max = 100))]
+    pub search: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub has_free_spots: Option<bool>,
+    /// Events are only paged if a limit is given
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<i64>,
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(default)]
+    #[validate(range(min = 0))]
+    pub offset: i64,
+}
+
+impl EventFilter {
+    /// The search term as `ILIKE` pattern
+    pub fn search_pattern(&self) -> Option<String> {
+        self.search.as_ref().map(|search| {
+            let escaped = search
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        })
+    }
+}
+
+/// New dates for an event that is copied from an existing event or a template
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct EventCopy {
+    #[validate(nested, length(min = 1, message = "At least one date is required"))]
+    pub dates: Vec<Date>,
+    #[validate(nested)]
+    pub registration_period: Option<Date>,
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelEvent {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
 fn validate_date(event: &Date) -> Result<(), ValidationError> {
     if event.start > event.end {
         Err(ValidationError::new("date").with_message(Cow::Borrowed("Start cannot be after end")))
@@ -142,29 +435,110 @@ pub struct Registration {
     pub attended: Option<bool>,
     pub waiting_list_position: Option<i32>,
     pub answers: Vec<Answer>,
+    pub guests: Vec<Guest>,
+    /// Places taken by the registration, including the guests
+    pub places: i32,
+    /// In euro cents
+    pub amount_due: i32,
+    pub payment_status: PaymentStatus,
+    /// Set when someone cancels after the cancellation deadline, until the organisers decide
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub cancellation_requested: Option<OffsetDateTime>,
+    /// When the QR code of the participant was first scanned
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub checked_in: Option<OffsetDateTime>,
+    pub car: Option<Car>,
+    /// The registration of the driver the participant rides with
+    pub driver: Option<RegistrationId>,
     #[serde(with = "time::serde::rfc3339")]
     pub created: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
     pub updated: OffsetDateTime,
 }
 
-#[derive(Deserialize, Debug, Validate)]
+/// A cancellation after the deadline that was approved by the organisers,
+/// the registration is kept so the treasurer can charge the costs
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LateCancellation {
+    pub id: LateCancellationId,
+    pub event_id: EventId,
+    pub registration_id: RegistrationId,
+    pub user: Option<BasicUser>,
+    pub amount_due: i32,
+    pub payment_status: PaymentStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub cancellation_requested: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub approved: OffsetDateTime,
+    pub approved_by: Option<UserId>,
+}
+
+#[derive(Deserialize, Debug, Clone, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct NewRegistration {
     pub user_id: Option<UserId>,
     pub answers: Vec<Answer>,
+    /// People brought along by the member. Anonymous registrations list the non-member
+    /// that signs up as the first guest.
+    #[validate(length(max = 20), nested)]
+    #[serde(default)]
+    pub guests: Vec<Guest>,
+    /// Set if the participant drives to the event
+    #[validate(nested)]
+    pub car: Option<Car>,
     pub attended: Option<bool>,
     pub waiting_list_position: Option<i32>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl NewRegistration {
+    /// Places the registration takes, see the `places` column of `event_registration`
+    pub fn places(&self) -> i32 {
+        (self.user_id.is_some() as i32 + self.guests.len() as i32).max(1)
+    }
+
+    /// Guests on top of the person that signs up, these count toward the guest limit
+    pub fn extra_guests(&self) -> i32 {
+        self.places() - 1
+    }
+}
+
+/// Someone without an account that joins an event, with an emergency contact
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct Guest {
+    #[validate(length(min = 1, max = 200))]
+    pub name: String,
+    #[validate(length(min = 1, max = 100))]
+    pub ice_contact_name: Option<String>,
+    #[validate(email)]
+    pub ice_contact_email: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub ice_contact_phone: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct Car {
+    /// Seats left for others, besides the driver and their guests
+    #[validate(range(min = 0, max = 8, message = "A car has at most 8 free seats"))]
+    pub seats: i32,
+    /// Where the driver leaves from
+    #[validate(length(min = 1, max = 200))]
+    pub departure: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Answer {
     pub question_id: Uuid,
     pub answer: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Validate)]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[validate(schema(function = "validate_question"))]
 #[serde(rename_all = "camelCase")]
 pub struct Question {
     pub id: Uuid,
@@ -172,14 +546,261 @@ pub struct Question {
     pub question: Language,
     pub question_type: QuestionType,
     pub required: bool,
+    /// Only asked when an earlier question was answered in a certain way
+    pub condition: Option<QuestionCondition>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum QuestionType {
     Text,
     Number,
-    MultipleChoice { options: Vec<Language> },
+    MultipleChoice {
+        options: Vec<QuestionOption>,
+    },
     Boolean,
     Date,
+    /// Any number of options can be chosen, the answer is a JSON array of the chosen options
+    Checkboxes {
+        options: Vec<QuestionOption>,
+    },
+    /// A number between `min` and `max`, both inclusive
+    Range {
+        min: f64,
+        max: f64,
+    },
+    /// The answer is the id of a file uploaded by the participant
+    File,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionOption {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub label: Language,
+    /// Number of participants that can choose this option, e.g. rental gear
+    #[validate(range(min = 0, max = 999, message = "Capacity is at most 999"))]
+    pub capacity: Option<i32>,
+}
+
+/// Aggregated answers to a question, over the registrations that have a place
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionSummary {
+    pub question_id: Uuid,
+    /// Registrations that answered the question
+    pub answered: i64,
+    #[serde(flatten)]
+    pub answers: AnswerSummary,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum AnswerSummary {
+    /// Times each option was chosen, in the order of the options
+    Options {
+        counts: Vec<i64>,
+    },
+    Boolean {
+        yes: i64,
+        no: i64,
+    },
+    Number {
+        min: Option<f64>,
+        max: Option<f64>,
+        average: Option<f64>,
+    },
+    /// The answers themselves, oldest registration first
+    List {
+        answers: Vec<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionCondition {
+    pub question_id: Uuid,
+    /// The answer that makes the question apply, for questions with options
+    /// it suffices that the option is chosen
+    pub answer: String,
+}
+
+fn validate_question(question: &Question) -> Result<(), ValidationError> {
+    if let QuestionType::Range { min, max } = question.question_type
+        && min > max
+    {
+        return Err(ValidationError::new("range")
+            .with_message(Cow::Borrowed("Minimum cannot be larger than the maximum")));
+    }
+    if question
+        .options()
+        .iter()
+        .any(|option| option.validate().is_err())
+    {
+        return Err(ValidationError::new("options").with_message(Cow::Borrowed(
+            "Options need a valid label and a capacity of at most 999",
+        )));
+    }
+    Ok(())
+}
+
+/// Conditions can only refer to earlier questions, so they never form a cycle
+pub(crate) fn validate_questions(questions: &[Question]) -> Result<(), ValidationError> {
+    let valid = questions.iter().enumerate().all(|(i, question)| {
+        question.condition.as_ref().is_none_or(|condition| {
+            questions[..i]
+                .iter()
+                .any(|earlier| earlier.id == condition.question_id)
+        })
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(
+            ValidationError::new("questions").with_message(Cow::Borrowed(
+                "Conditions must refer to an earlier question",
+            )),
+        )
+    }
+}
+
+impl Question {
+    pub fn options(&self) -> &[QuestionOption] {
+        match &self.question_type {
+            QuestionType::MultipleChoice { options } | QuestionType::Checkboxes { options } => {
+                options
+            }
+            _ => &[],
+        }
+    }
+
+    /// Indices of the options chosen in the answer, options are answered in either language
+    pub fn chosen_options(&self, answer: &str) -> Vec<usize> {
+        let find = |answer: &str| {
+            self.options()
+                .iter()
+                .position(|option| option.label.en == answer || option.label.nl == answer)
+        };
+        match &self.question_type {
+            QuestionType::MultipleChoice { .. } => find(answer).into_iter().collect(),
+            QuestionType::Checkboxes { .. } => serde_json::from_str::<Vec<String>>(answer)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|answer| find(answer))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether the answer fits the type of the question. Files are checked separately,
+    /// as they must have been uploaded by the participant
+    pub fn accepts(&self, answer: &str) -> bool {
+        match &self.question_type {
+            QuestionType::Text | QuestionType::Date => true,
+            QuestionType::Number => answer.parse::<f64>().is_ok(),
+            QuestionType::Range { min, max } => answer
+                .parse::<f64>()
+                .is_ok_and(|number| (*min..=*max).contains(&number)),
+            QuestionType::Boolean => answer == "true" || answer == "false",
+            QuestionType::MultipleChoice { .. } => !self.chosen_options(answer).is_empty(),
+            QuestionType::Checkboxes { .. } => serde_json::from_str::<Vec<String>>(answer)
+                .is_ok_and(|chosen| chosen.len() == self.chosen_options(answer).len()),
+            QuestionType::File => Uuid::parse_str(answer).is_ok(),
+        }
+    }
+
+    /// Whether the question applies, given the answers to the earlier questions
+    pub fn is_asked(&self, questions: &[Question], answers: &[Answer]) -> bool {
+        let Some(condition) = &self.condition else {
+            return true;
+        };
+        let Some(answer) = answers
+            .iter()
+            .find(|answer| answer.question_id == condition.question_id)
+        else {
+            return false;
+        };
+        match questions
+            .iter()
+            .find(|question| question.id == condition.question_id)
+        {
+            Some(question) if !question.options().is_empty() => {
+                question.chosen_options(&answer.answer).iter().any(|&i| {
+                    let label = &question.options()[i].label;
+                    label.en == condition.answer || label.nl == condition.answer
+                })
+            }
+            _ => answer.answer == condition.answer,
+        }
+    }
+}
+
+/// How often each option with a capacity was chosen by the registrations that have a place
+#[derive(Debug, Default)]
+pub struct OptionUsage(HashMap<(Uuid, usize), i32>);
+
+impl OptionUsage {
+    pub fn new<'a>(questions: &[Question], placed: impl IntoIterator<Item = &'a [Answer]>) -> Self {
+        let mut usage = Self::default();
+        for answers in placed {
+            usage.add(questions, answers);
+        }
+        usage
+    }
+
+    /// Chosen options that have a capacity, with that capacity
+    fn limited_choices(
+        questions: &[Question],
+        answers: &[Answer],
+    ) -> impl Iterator<Item = ((Uuid, usize), i32)> {
+        questions.iter().flat_map(move |question| {
+            answers
+                .iter()
+                .filter(|answer| answer.question_id == question.id)
+                .flat_map(|answer| question.chosen_options(&answer.answer))
+                .filter_map(|i| Some(((question.id, i), question.options()[i].capacity?)))
+        })
+    }
+
+    pub fn is_limited(questions: &[Question], answers: &[Answer]) -> bool {
+        Self::limited_choices(questions, answers).next().is_some()
+    }
+
+    /// Whether every chosen option still has room for one more registration
+    pub fn has_room(&self, questions: &[Question], answers: &[Answer]) -> bool {
+        Self::limited_choices(questions, answers)
+            .all(|(option, capacity)| self.0.get(&option).copied().unwrap_or_default() < capacity)
+    }
+
+    pub fn add(&mut self, questions: &[Question], answers: &[Answer]) {
+        for (option, _) in Self::limited_choices(questions, answers) {
+            *self.0.entry(option).or_default() += 1;
+        }
+    }
+}
+
+/// The answers to the questions that are asked, in question order.
+/// Empty answers and answers to questions that are not asked are dropped.
+pub fn asked_answers(questions: &[Question], given: &[Answer]) -> Result<Vec<Answer>, Error> {
+    // Conditions refer to earlier questions, so asked questions can be determined in order
+    let mut answers: Vec<Answer> = Vec::new();
+    for question in questions {
+        if !question.is_asked(questions, &answers) {
+            continue;
+        }
+        let answer = given
+            .iter()
+            .find(|answer| answer.question_id == question.id && !answer.answer.trim().is_empty());
+        match answer {
+            Some(answer) if question.accepts(&answer.answer) => answers.push(answer.clone()),
+            Some(_) => return Err(Error::BadRequest("Invalid answer for question")),
+            None if question.required => {
+                return Err(Error::BadRequest("Missing answer for required question"));
+            }
+            None => {}
+        }
+    }
+    Ok(answers)
 }
@@ -0,0 +1,97 @@
+use crate::{Language, event::ActivityType};
+use schemars::{JsonSchema, Schema, schema_for};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+use std::borrow::Cow;
+use validator::{Validate, ValidationError};
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClimbingType {
+    #[serde(rename = "sp")]
+    SinglePitch,
+    #[serde(rename = "mp")]
+    MultiPitch,
+    #[serde(rename = "boulder")]
+    Boulder,
+    #[serde(rename = "trad")]
+    Trad,
+    #[serde(rename = "education")]
+    Education,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Difficulty {
+    Easy,
+    Moderate,
+    Hard,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CourseLevel {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+/// Properties shown on the event page and used to filter the calendar,
+/// some of them only apply to a single activity type
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, Validate)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EventMetadata {
+    #[serde(default, rename = "type")]
+    pub types: Vec<ClimbingType>,
+    /// Experience participants need to join
+    #[serde(default)]
+    pub experience: Vec<ClimbingType>,
+    /// Gear participants have to bring
+    #[validate(nested)]
+    pub gear: Option<Language>,
+    /// Weekends only
+    pub difficulty: Option<Difficulty>,
+    /// Courses only
+    pub level: Option<CourseLevel>,
+}
+
+impl EventMetadata {
+    /// Properties that do not apply to the given activity type
+    fn excluded_properties(event_type: ActivityType) -> &'static [&'static str] {
+        match event_type {
+            ActivityType::Weekend => &["level"],
+            ActivityType::Course => &["difficulty"],
+            ActivityType::Activity | ActivityType::Training => &["difficulty", "level"],
+        }
+    }
+
+    pub fn validate_for(&self, event_type: ActivityType) -> Result<(), ValidationError> {
+        let excluded = Self::excluded_properties(event_type);
+        let present = [
+            ("difficulty", self.difficulty.is_some()),
+            ("level", self.level.is_some()),
+        ];
+        if present
+            .iter()
+            .any(|(property, set)| *set && excluded.contains(property))
+        {
+            Err(ValidationError::new("metadata").with_message(Cow::Borrowed(
+                "Metadata contains properties of another activity type",
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// JSON Schema of the metadata of the given activity type, used by the event editor
+    pub fn json_schema(event_type: ActivityType) -> Schema {
+        let mut schema = schema_for!(EventMetadata);
+        if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+            for property in Self::excluded_properties(event_type) {
+                properties.remove(*property);
+            }
+        }
+        schema
+    }
+}
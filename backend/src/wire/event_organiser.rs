@@ -0,0 +1,40 @@
+use crate::user::BasicUser;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "organiser_role", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum OrganiserRole {
+    /// Has the same rights as the chair of the owning committee
+    Lead,
+    CoOrganiser,
+    FirstAid,
+    Treasurer,
+}
+
+impl OrganiserRole {
+    pub const ALL: &[Self] = &[
+        Self::Lead,
+        Self::CoOrganiser,
+        Self::FirstAid,
+        Self::Treasurer,
+    ];
+
+    /// Organisers that may edit the event and handle its registrations
+    pub const MANAGERS: &[Self] = &[Self::Lead, Self::CoOrganiser];
+}
+
+/// A user helping to organise an event, regardless of committee membership
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EventOrganiser {
+    pub user: BasicUser,
+    pub role: OrganiserRole,
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SetOrganiser {
+    pub role: OrganiserRole,
+}
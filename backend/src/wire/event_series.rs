@@ -0,0 +1,108 @@
+use crate::{
+    event::{EventContent, EventId, Registration},
+    location::LocationId,
+    wire::iso_date,
+};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, ops::Deref};
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(transparent)]
+pub struct EventSeriesId(Uuid);
+
+impl From<Uuid> for EventSeriesId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl Deref for EventSeriesId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "recurrence_frequency", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum RecurrenceFrequency {
+    Weekly,
+    Biweekly,
+}
+
+impl RecurrenceFrequency {
+    pub fn interval_days(&self) -> i32 {
+        match self {
+            RecurrenceFrequency::Weekly => 7,
+            RecurrenceFrequency::Biweekly => 14,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurrenceRule {
+    pub frequency: RecurrenceFrequency,
+    /// Last day on which an occurrence may start (inclusive)
+    #[serde(with = "iso_date")]
+    pub until: Date,
+    /// Days on which the series is skipped, e.g. holidays
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub exceptions: Vec<SkippedDate>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SkippedDate(#[serde(with = "iso_date")] pub Date);
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSeries {
+    pub id: EventSeriesId,
+    pub rule: RecurrenceRule,
+    /// The content of the series, the dates are those of the first occurrence
+    pub content: EventContent<LocationId>,
+    pub occurrences: Vec<EventId>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[validate(schema(function = "validate_series"))]
+#[serde(rename_all = "camelCase")]
+pub struct EventSeriesContent {
+    #[validate(nested)]
+    pub rule: RecurrenceRule,
+    /// The dates are those of the first occurrence, later occurrences are shifted from these
+    #[validate(nested)]
+    pub content: EventContent<LocationId>,
+}
+
+fn validate_series(series: &EventSeriesContent) -> Result<(), ValidationError> {
+    match series.content.dates.iter().map(|date| date.start).min() {
+        None => Err(ValidationError::new("dates").with_message(Cow::Borrowed(
+            "The first occurrence needs at least one date",
+        ))),
+        Some(start) if start.date() > series.rule.until => Err(ValidationError::new("until")
+            .with_message(Cow::Borrowed(
+                "The series must end after the first occurrence",
+            ))),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesRegistration {
+    pub registrations: Vec<Registration>,
+    /// Occurrences one could not be registered for, e.g. because they are full
+    pub skipped: Vec<EventId>,
+}
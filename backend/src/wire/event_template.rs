@@ -0,0 +1,47 @@
+use crate::{committee::CommitteeId, event::EventContent, location::LocationId};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(transparent)]
+pub struct EventTemplateId(Uuid);
+
+impl From<Uuid> for EventTemplateId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl Deref for EventTemplateId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTemplate {
+    pub id: EventTemplateId,
+    pub committee_id: CommitteeId,
+    pub name: String,
+    pub content: EventContent<LocationId>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTemplateContent {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    /// The dates are ignored, they are given when creating an event from the template
+    #[validate(nested)]
+    pub content: EventContent<LocationId>,
+}
@@ -0,0 +1,83 @@
+use crate::{
+    event::{Answer, EventId, Question, QuestionSummary, QuestionType, validate_questions},
+    user::BasicUser,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// Feedback asked from the attendees of an event, using the same questions as registrations
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackForm {
+    pub event_id: EventId,
+    #[serde(flatten)]
+    pub content: FeedbackFormContent,
+    /// When the attendees were last asked to respond
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub sent: Option<OffsetDateTime>,
+    /// Whether the current user has responded
+    pub responded: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackFormContent {
+    #[validate(
+        length(min = 1, max = 50),
+        nested,
+        custom(function = "validate_feedback_questions")
+    )]
+    pub questions: Vec<Question>,
+    /// Responses are stored without the respondent
+    pub anonymous: bool,
+    /// Whether the board may see the results, they are only for the organisers otherwise
+    pub shared_with_board: bool,
+}
+
+/// Uploaded files have an owner, which would reveal anonymous respondents
+fn validate_feedback_questions(questions: &[Question]) -> Result<(), ValidationError> {
+    validate_questions(questions)?;
+    if questions
+        .iter()
+        .any(|question| matches!(question.question_type, QuestionType::File))
+    {
+        Err(ValidationError::new("questions")
+            .with_message(Cow::Borrowed("Feedback forms cannot ask for files")))
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct NewFeedbackResponse {
+    #[validate(length(max = 50))]
+    pub answers: Vec<Answer>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackResponse {
+    pub id: Uuid,
+    /// Only set for attributed forms
+    #[serde(flatten)]
+    pub user: Option<BasicUser>,
+    pub answers: Vec<Answer>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackResults {
+    /// Attendees that were asked to respond
+    pub attendees: i64,
+    pub respondents: i64,
+    pub questions: Vec<QuestionSummary>,
+    pub responses: Vec<FeedbackResponse>,
+}
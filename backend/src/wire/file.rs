@@ -43,6 +43,8 @@ pub struct FileMetadata {
     pub mime_type: Option<Mime>,
     pub size: i32,
     pub is_public: bool,
+    /// Answers a registration question, only the uploader and the organisers may read it
+    pub is_answer: bool,
     pub created_by: UserId,
     #[serde(with = "time::serde::rfc3339")]
     pub created: OffsetDateTime,
@@ -0,0 +1,105 @@
+use crate::{
+    Language,
+    event::EventId,
+    user::{BasicUser, UserId},
+    wire::iso_date,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+/// An event the member attended
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub event_id: EventId,
+    pub name: Language,
+    pub location: Language,
+    #[serde(with = "time::serde::rfc3339")]
+    pub start: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub end: OffsetDateTime,
+}
+
+/// Attended events grouped by their type, most recent first
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct History {
+    pub activity: Vec<HistoryEntry>,
+    pub course: Vec<HistoryEntry>,
+    pub weekend: Vec<HistoryEntry>,
+    pub training: Vec<HistoryEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(transparent)]
+pub struct LogbookEntryId(Uuid);
+
+impl From<Uuid> for LogbookEntryId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl Deref for LogbookEntryId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[sqlx(type_name = "logbook_visibility", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum LogbookVisibility {
+    /// Only visible to the member that wrote the entry
+    #[default]
+    Private,
+    Members,
+    Public,
+}
+
+/// A route or tour a member logged themselves
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct LogbookEntryContent {
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    #[validate(length(min = 1, max = 200))]
+    pub area: String,
+    #[validate(length(min = 1, max = 200))]
+    pub route: String,
+    #[validate(length(max = 20))]
+    pub grade: Option<String>,
+    #[validate(length(max = 10000))]
+    #[serde(default)]
+    pub description: String,
+    /// Members that joined the tour
+    #[validate(length(max = 50))]
+    #[serde(default)]
+    pub partners: Vec<UserId>,
+    #[serde(default)]
+    pub visibility: LogbookVisibility,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogbookEntry {
+    pub id: LogbookEntryId,
+    pub user_id: UserId,
+    #[serde(with = "iso_date")]
+    pub date: Date,
+    pub area: String,
+    pub route: String,
+    pub grade: Option<String>,
+    pub description: String,
+    pub partners: Vec<BasicUser>,
+    pub visibility: LogbookVisibility,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
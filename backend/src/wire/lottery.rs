@@ -0,0 +1,39 @@
+use crate::{
+    event::{EventId, RegistrationId},
+    user::UserId,
+};
+use serde::Serialize;
+use time::OffsetDateTime;
+
+#[derive(sqlx::Type, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "lottery_outcome", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum LotteryOutcome {
+    Placed,
+    WaitingList,
+    /// Did not fit on the waiting list, the registration was removed
+    Rejected,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LotteryEntry {
+    pub registration_id: RegistrationId,
+    pub user_id: Option<UserId>,
+    pub weight: f64,
+    /// Derived from the seed and the registration id, the highest ticket wins
+    pub ticket: f64,
+    pub rank: i32,
+    pub outcome: LotteryOutcome,
+}
+
+/// Everything needed to reproduce and verify a draw
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LotteryDraw {
+    pub event_id: EventId,
+    pub seed: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub drawn: OffsetDateTime,
+    pub entries: Vec<LotteryEntry>,
+}
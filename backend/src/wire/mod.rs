@@ -1,15 +1,32 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+pub mod api_token;
+pub mod attendance;
+pub mod carpool;
+pub mod certification;
 pub mod committee;
 pub mod event;
+pub mod event_metadata;
+pub mod event_organiser;
+pub mod event_series;
+pub mod event_template;
+pub mod feedback;
 pub mod file;
+pub mod history;
 pub mod location;
+pub mod lottery;
 pub mod material;
+pub mod notification;
 pub mod page;
+pub mod payment;
+pub mod sepa;
 pub mod user;
 
-#[derive(Serialize, Deserialize, Debug, Validate, Default)]
+time::serde::format_description!(pub(crate) iso_date, Date, "[year]-[month]-[day]");
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Validate, Default)]
 pub struct Language {
     #[validate(length(min = 0, max = 50000))]
     pub en: String,
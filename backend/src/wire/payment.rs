@@ -0,0 +1,197 @@
+use crate::{
+    Language,
+    auth::role::Membership,
+    event::{Answer, EventId, Question, RegistrationId},
+    user::BasicUser,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "payment_status", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum PaymentStatus {
+    Unpaid,
+    Paid,
+    Refunded,
+}
+
+/// What an event costs, all amounts are in euro cents
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Validate)]
+#[validate(schema(function = "validate_tiers"))]
+#[serde(rename_all = "camelCase", default)]
+pub struct Pricing {
+    /// Memberships without a tier participate for free
+    #[validate(nested)]
+    pub tiers: Vec<PriceTier>,
+    #[validate(nested)]
+    pub extras: Vec<PricedExtra>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceTier {
+    pub membership: Membership,
+    #[validate(range(min = 0, max = 1000000, message = "Price is at most 10000 euro"))]
+    pub amount: i32,
+}
+
+/// Extra costs when choosing an option of a question, e.g. gear rental
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct PricedExtra {
+    pub question_id: Uuid,
+    /// Index of the option in the question
+    pub option: usize,
+    #[validate(range(min = 0, max = 1000000, message = "Price is at most 10000 euro"))]
+    pub amount: i32,
+}
+
+fn validate_tiers(pricing: &Pricing) -> Result<(), ValidationError> {
+    let tiers = &pricing.tiers;
+    let duplicate = tiers
+        .iter()
+        .enumerate()
+        .any(|(i, tier)| tiers[..i].iter().any(|t| t.membership == tier.membership));
+    if duplicate {
+        Err(ValidationError::new("tiers")
+            .with_message(Cow::Borrowed("Each membership can only have one price")))
+    } else {
+        Ok(())
+    }
+}
+
+impl Pricing {
+    /// The question with options an extra belongs to
+    fn question<'a>(extra: &PricedExtra, questions: &'a [Question]) -> Option<&'a Question> {
+        questions
+            .iter()
+            .find(|question| question.id == extra.question_id)
+            .filter(|question| question.options().get(extra.option).is_some())
+    }
+
+    /// Every extra must belong to an existing option of a question with options
+    pub fn validate_extras(&self, questions: &[Question]) -> Result<(), ValidationError> {
+        if self
+            .extras
+            .iter()
+            .all(|extra| Self::question(extra, questions).is_some())
+        {
+            Ok(())
+        } else {
+            Err(ValidationError::new("extras").with_message(Cow::Borrowed(
+                "Extras must belong to an option of a question",
+            )))
+        }
+    }
+
+    fn tier_amount(&self, membership: Membership) -> i32 {
+        self.tiers
+            .iter()
+            .find(|tier| tier.membership == membership)
+            .map_or(0, |tier| tier.amount)
+    }
+
+    /// The price of the tier plus the chosen extras, guests pay the non-member price
+    pub fn amount_due(
+        &self,
+        questions: &[Question],
+        membership: Membership,
+        answers: &[Answer],
+        guests: i32,
+    ) -> i32 {
+        let tier = self.tier_amount(membership) + guests * self.tier_amount(Membership::NonMember);
+
+        let extras: i32 = self
+            .extras
+            .iter()
+            .filter(|extra| {
+                Self::question(extra, questions).is_some_and(|question| {
+                    answers.iter().any(|answer| {
+                        answer.question_id == extra.question_id
+                            && question
+                                .chosen_options(&answer.answer)
+                                .contains(&extra.option)
+                    })
+                })
+            })
+            .map(|extra| extra.amount)
+            .sum();
+
+        tier + extras
+    }
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePayment {
+    pub status: PaymentStatus,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Payment {
+    pub registration_id: RegistrationId,
+    pub event_id: EventId,
+    pub event_name: Language,
+    pub user: Option<BasicUser>,
+    pub amount_due: i32,
+    pub status: PaymentStatus,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub updated: Option<OffsetDateTime>,
+}
+
+/// Payments of the registrations that got a place, either for an event or a member
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentOverview {
+    pub total_due: i64,
+    pub paid: i64,
+    pub refunded: i64,
+    /// Still to be paid
+    pub outstanding: i64,
+    pub payments: Vec<Payment>,
+}
+
+impl From<Vec<Payment>> for PaymentOverview {
+    fn from(payments: Vec<Payment>) -> Self {
+        let sum = |status: Option<PaymentStatus>| {
+            payments
+                .iter()
+                .filter(|payment| status.is_none_or(|status| payment.status == status))
+                .map(|payment| payment.amount_due as i64)
+                .sum()
+        };
+
+        Self {
+            total_due: sum(None),
+            paid: sum(Some(PaymentStatus::Paid)),
+            refunded: sum(Some(PaymentStatus::Refunded)),
+            outstanding: sum(Some(PaymentStatus::Unpaid)),
+            payments,
+        }
+    }
+}
+
+/// Status of an online payment at the payment service provider
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "transaction_status", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionStatus {
+    Open,
+    Paid,
+    Failed,
+    Expired,
+    Canceled,
+    Refunded,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkout {
+    /// Where the member completes the payment
+    pub checkout_url: String,
+}
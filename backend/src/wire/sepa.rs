@@ -0,0 +1,372 @@
+use crate::{
+    auth::role::Membership,
+    user::{BasicUser, UserId},
+    wire::iso_date,
+};
+use derive_more::{Display, From, Into};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, fmt::Write, ops::Deref};
+use time::{Date, OffsetDateTime, format_description::well_known::Rfc3339};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SepaMandate {
+    pub user_id: UserId,
+    pub account_holder: String,
+    pub iban: String,
+    pub mandate_id: String,
+    #[serde(with = "iso_date")]
+    pub signed: Date,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
+
+#[derive(Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SepaMandateContent {
+    #[validate(length(min = 1, max = 70))]
+    pub account_holder: String,
+    #[validate(custom(function = "validate_iban"))]
+    pub iban: String,
+    #[validate(length(min = 1, max = 35))]
+    pub mandate_id: String,
+    #[serde(with = "iso_date")]
+    pub signed: Date,
+}
+
+/// Without spaces and in upper case, as banks expect it
+pub fn normalize_iban(iban: &str) -> String {
+    iban.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Checks the format and the ISO 7064 mod 97-10 checksum
+fn validate_iban(iban: &str) -> Result<(), ValidationError> {
+    let iban = normalize_iban(iban);
+    let invalid = || ValidationError::new("iban").with_message(Cow::Borrowed("Invalid IBAN"));
+
+    // Checked before slicing, as byte offsets may fall inside other characters
+    if !iban.chars().all(|c| c.is_ascii_alphanumeric())
+        || !(15..=34).contains(&iban.len())
+        || !iban[..2].chars().all(|c| c.is_ascii_uppercase())
+        || !iban[2..4].chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    // Move the country code and check digits to the end, letters count as 10 to 35
+    let remainder = iban[4..]
+        .chars()
+        .chain(iban[..4].chars())
+        .fold(0u32, |remainder, c| {
+            let value = c.to_digit(36).expect("IBAN is alphanumeric");
+            if value < 10 {
+                (remainder * 10 + value) % 97
+            } else {
+                (remainder * 100 + value) % 97
+            }
+        });
+
+    if remainder == 1 {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Yearly contribution in euro cents
+#[derive(Serialize, Deserialize, Debug, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct MembershipFee {
+    pub membership: Membership,
+    #[validate(range(min = 0, max = 100000, message = "Fee is at most 1000 euro"))]
+    pub amount: i32,
+}
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "collection_status", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum CollectionStatus {
+    Pending,
+    Collected,
+    /// E.g. reversed by the member or insufficient funds
+    Failed,
+}
+
+/// The first collection with a mandate is announced to the bank separately
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(type_name = "sequence_type", rename_all = "snake_case")]
+#[serde(rename_all = "camelCase")]
+pub enum SequenceType {
+    First,
+    Recurring,
+}
+
+impl SequenceType {
+    fn code(&self) -> &'static str {
+        match self {
+            SequenceType::First => "FRST",
+            SequenceType::Recurring => "RCUR",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, From, Display, Into)]
+#[serde(transparent)]
+pub struct FeeCollectionId(Uuid);
+
+impl Deref for FeeCollectionId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, From, Display, Into)]
+#[serde(transparent)]
+pub struct CollectionBatchId(Uuid);
+
+impl Deref for CollectionBatchId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeCollection {
+    pub id: FeeCollectionId,
+    pub batch_id: CollectionBatchId,
+    pub user: Option<BasicUser>,
+    pub year: i32,
+    pub amount: i32,
+    pub account_holder: String,
+    pub iban: String,
+    pub mandate_id: String,
+    #[serde(with = "iso_date")]
+    pub signed: Date,
+    pub sequence_type: SequenceType,
+    pub status: CollectionStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated: OffsetDateTime,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionBatch {
+    pub id: CollectionBatchId,
+    pub year: i32,
+    #[serde(with = "iso_date")]
+    pub collection_date: Date,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created: OffsetDateTime,
+    pub collections: Vec<FeeCollection>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCollectionBatch {
+    #[serde(with = "iso_date")]
+    pub collection_date: Date,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCollection {
+    pub status: CollectionStatus,
+}
+
+/// The association, as registered for collecting direct debits
+#[derive(Debug, Clone)]
+pub struct Creditor {
+    pub name: String,
+    pub iban: String,
+    pub bic: String,
+    /// Creditor identifier, e.g. `NL00ZZZ123456780000`
+    pub id: String,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Amounts are written with two decimals, e.g. `12.50`
+fn amount(cents: i64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+impl CollectionBatch {
+    /// A pain.008.001.02 customer direct debit initiation, with a payment information block
+    /// per sequence type. Failed collections are left out.
+    pub fn to_pain008(&self, creditor: &Creditor) -> String {
+        let collections: Vec<&FeeCollection> = self
+            .collections
+            .iter()
+            .filter(|collection| collection.status != CollectionStatus::Failed)
+            .collect();
+        let total = |collections: &[&FeeCollection]| {
+            collections
+                .iter()
+                .map(|collection| collection.amount as i64)
+                .sum::<i64>()
+        };
+        let message_id = self.id.simple().to_string();
+        let created = self
+            .created
+            .replace_nanosecond(0)
+            .ok()
+            .and_then(|created| created.format(&Rfc3339).ok())
+            .unwrap_or_default();
+
+        let mut xml = String::new();
+        let _ = write!(
+            xml,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pain.008.001.02" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <CstmrDrctDbtInitn>
+    <GrpHdr>
+      <MsgId>{message_id}</MsgId>
+      <CreDtTm>{created}</CreDtTm>
+      <NbOfTxs>{}</NbOfTxs>
+      <CtrlSum>{}</CtrlSum>
+      <InitgPty>
+        <Nm>{}</Nm>
+      </InitgPty>
+    </GrpHdr>
+"#,
+            collections.len(),
+            amount(total(&collections)),
+            escape(&creditor.name),
+        );
+
+        for sequence_type in [SequenceType::First, SequenceType::Recurring] {
+            let group: Vec<&FeeCollection> = collections
+                .iter()
+                .copied()
+                .filter(|collection| collection.sequence_type == sequence_type)
+                .collect();
+            if group.is_empty() {
+                continue;
+            }
+
+            let _ = write!(
+                xml,
+                r#"    <PmtInf>
+      <PmtInfId>{short_message_id}-{code}</PmtInfId>
+      <PmtMtd>DD</PmtMtd>
+      <BtchBookg>true</BtchBookg>
+      <NbOfTxs>{}</NbOfTxs>
+      <CtrlSum>{}</CtrlSum>
+      <PmtTpInf>
+        <SvcLvl>
+          <Cd>SEPA</Cd>
+        </SvcLvl>
+        <LclInstrm>
+          <Cd>CORE</Cd>
+        </LclInstrm>
+        <SeqTp>{code}</SeqTp>
+      </PmtTpInf>
+      <ReqdColltnDt>{}</ReqdColltnDt>
+      <Cdtr>
+        <Nm>{}</Nm>
+      </Cdtr>
+      <CdtrAcct>
+        <Id>
+          <IBAN>{}</IBAN>
+        </Id>
+      </CdtrAcct>
+      <CdtrAgt>
+        <FinInstnId>
+          <BIC>{}</BIC>
+        </FinInstnId>
+      </CdtrAgt>
+      <ChrgBr>SLEV</ChrgBr>
+      <CdtrSchmeId>
+        <Id>
+          <PrvtId>
+            <Othr>
+              <Id>{}</Id>
+              <SchmeNm>
+                <Prtry>SEPA</Prtry>
+              </SchmeNm>
+            </Othr>
+          </PrvtId>
+        </Id>
+      </CdtrSchmeId>
+"#,
+                group.len(),
+                amount(total(&group)),
+                self.collection_date,
+                escape(&creditor.name),
+                escape(&creditor.iban),
+                escape(&creditor.bic),
+                escape(&creditor.id),
+                code = sequence_type.code(),
+                // PmtInfId is Max35Text, so the message id makes room for the sequence type
+                short_message_id = &message_id[..30],
+            );
+
+            for collection in group {
+                let _ = write!(
+                    xml,
+                    r#"      <DrctDbtTxInf>
+        <PmtId>
+          <EndToEndId>{}</EndToEndId>
+        </PmtId>
+        <InstdAmt Ccy="EUR">{}</InstdAmt>
+        <DrctDbtTx>
+          <MndtRltdInf>
+            <MndtId>{}</MndtId>
+            <DtOfSgntr>{}</DtOfSgntr>
+          </MndtRltdInf>
+        </DrctDbtTx>
+        <DbtrAgt>
+          <FinInstnId>
+            <Othr>
+              <Id>NOTPROVIDED</Id>
+            </Othr>
+          </FinInstnId>
+        </DbtrAgt>
+        <Dbtr>
+          <Nm>{}</Nm>
+        </Dbtr>
+        <DbtrAcct>
+          <Id>
+            <IBAN>{}</IBAN>
+          </Id>
+        </DbtrAcct>
+        <RmtInf>
+          <Ustrd>Contributie {}</Ustrd>
+        </RmtInf>
+      </DrctDbtTxInf>
+"#,
+                    collection.id.simple(),
+                    amount(collection.amount as i64),
+                    escape(&collection.mandate_id),
+                    collection.signed,
+                    escape(&collection.account_holder),
+                    escape(&collection.iban),
+                    collection.year,
+                );
+            }
+
+            xml.push_str("    </PmtInf>\n");
+        }
+
+        xml.push_str("  </CstmrDrctDbtInitn>\n</Document>\n");
+        xml
+    }
+}
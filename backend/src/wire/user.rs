@@ -1,11 +1,11 @@
 use crate::{
-    auth::role::{Membership, Roles, Status},
+    auth::{
+        password::{Params, hash_password, validate_password_policy},
+        role::{Membership, Roles, Status},
+    },
     error::Error,
 };
-use argon2::{
-    Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash,
-    password_hash::{SaltString, rand_core::OsRng},
-};
+use argon2::{Argon2, PasswordHash, PasswordVerifier, password_hash};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use sqlx::FromRow;
@@ -48,6 +48,16 @@ pub struct User {
     pub content: UserContent,
 }
 
+/// The logged in user, marked if an admin is impersonating them
+#[skip_serializing_none]
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WhoAmI {
+    #[serde(flatten)]
+    pub user: User,
+    pub impersonated_by: Option<BasicUser>,
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -90,6 +100,7 @@ pub struct UserCredentials {
 }
 
 impl UserCredentials {
+    /// Verifies the password using the parameters stored in the hash itself
     pub fn verify_pwd(&self, hash: &PasswordHash) -> Result<(), Error> {
         Argon2::default()
             .verify_password(self.password.as_bytes(), hash)
@@ -98,22 +109,22 @@ impl UserCredentials {
                 _ => Error::Argon2(err),
             })
     }
+
+    pub fn pwd_hash(&self, params: &Params) -> Result<String, Error> {
+        hash_password(&self.password, params)
+    }
 }
 
 #[derive(Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct Password {
-    #[validate(length(min = 10, max = 128))]
+    #[validate(length(max = 128), custom(function = "validate_password_policy"))]
     password: String,
 }
 
 impl Password {
-    pub fn pwd_hash(&self) -> Result<String, Error> {
-        let salt = SaltString::generate(&mut OsRng);
-        Ok(Argon2::default()
-            .hash_password(self.password.as_bytes(), &salt)
-            .map_err(Error::Argon2)?
-            .to_string())
+    pub fn pwd_hash(&self, params: &Params) -> Result<String, Error> {
+        hash_password(&self.password, params)
     }
 }
 
@@ -137,11 +148,10 @@ pub struct RegisterNewUser {
     pub last_name: String,
     #[validate(email)]
     pub email: String,
-    #[validate(length(
-        min = 10,
-        max = 128,
-        message = "Password must contain between 10 and 128 characters"
-    ))]
+    #[validate(
+        length(max = 128, message = "Password must contain at most 128 characters"),
+        custom(function = "validate_password_policy")
+    )]
     password: String,
     pub phone: String,
     #[validate(length(min = 5, max = 20))]
@@ -163,12 +173,8 @@ pub struct RegisterNewUser {
 }
 
 impl RegisterNewUser {
-    pub fn pwd_hash(&self) -> Result<String, Error> {
-        let salt = SaltString::generate(&mut OsRng);
-        Ok(Argon2::default()
-            .hash_password(self.password.as_bytes(), &salt)
-            .map_err(Error::Argon2)?
-            .to_string())
+    pub fn pwd_hash(&self, params: &Params) -> Result<String, Error> {
+        hash_password(&self.password, params)
     }
 }
 
@@ -9,10 +9,29 @@ interface CachedGetResponse {
 
 const etagCache = new Map<string, CachedGetResponse>();
 
-export async function apiFetch<T>(
+const SAFE_METHODS = ['GET', 'HEAD', 'OPTIONS'];
+
+function getCookie(name: string): string | undefined {
+  return document.cookie
+    .split('; ')
+    .find((cookie) => cookie.startsWith(name + '='))
+    ?.substring(name.length + 1);
+}
+
+// The backend requires the CSRF cookie to be echoed in a header on all mutating requests
+function withCsrfToken(headers: Headers, method: string): Headers {
+  const csrfToken = getCookie('CSRF_TOKEN');
+  if (csrfToken && !SAFE_METHODS.includes(method.toUpperCase())) {
+    headers.set('X-CSRF-Token', csrfToken);
+  }
+  return headers;
+}
+
+// Performs the request and throws on error responses, for callers that need the status
+export async function apiResponse(
   url: string,
   options: RequestInit = {}
-): Promise<T> {
+): Promise<Response> {
   const method = options.method ?? 'GET';
   const cacheKey = method === 'GET' ? url : null;
   const cached = cacheKey ? etagCache.get(cacheKey) : undefined;
@@ -28,8 +47,11 @@ export async function apiFetch<T>(
   try {
     response = await fetch('/api' + url, {
       credentials: 'include',
-      headers,
-      ...options
+      ...options,
+      headers: withCsrfToken(
+        options.headers ? new Headers(options.headers) : headers,
+        method
+      )
     });
   } catch (err) {
     const networkError = new ApiError(String(err), 0, `URL: ${url}`);
@@ -82,6 +104,15 @@ export async function apiFetch<T>(
     }
   }
 
+  return finalResponse;
+}
+
+export async function apiFetch<T>(
+  url: string,
+  options: RequestInit = {}
+): Promise<T> {
+  const finalResponse = await apiResponse(url, options);
+
   // Handle empty responses
   if (
     finalResponse.status === 204 ||
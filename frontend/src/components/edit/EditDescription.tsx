@@ -1,7 +1,7 @@
 import MarkdownEditor from '../markdown/MarkdownEditor.tsx';
 import {FormControl, InputLabel, MenuItem, Select, TextField} from '@mui/material';
 import {memo} from 'react';
-import {Controller, useFormContext, useWatch} from 'react-hook-form';
+import {useFormContext, useWatch} from 'react-hook-form';
 import OptionSelector from '../OptionSelector.tsx';
 import {
   experienceOptions,
@@ -10,6 +10,7 @@ import {
 } from '../../types.ts';
 import { useLanguage } from '../../providers/LanguageProvider.tsx';
 import {useEventRegistrationHook} from '../../hooks/useEventRegistrationHook.ts';
+import {useEventHook} from '../../hooks/useEventHook.ts';
 import {useParams} from 'react-router-dom';
 
 function EditDescription() {
@@ -23,6 +24,21 @@ function EditDescription() {
   });
 
   const eventRegistrations = useEventRegistrations(params.eventId)
+  const {useEventOrganisers, setOrganiser, removeOrganiser} = useEventHook();
+  const organisers = useEventOrganisers(params.eventId);
+  const lead = organisers?.find((organiser) => organiser.role === 'lead');
+
+  const changeLead = async (userId: string) => {
+    if (!params.eventId) {
+      return;
+    }
+    if (lead) {
+      await removeOrganiser(params.eventId, lead.user.id);
+    }
+    if (userId !== 'nobody') {
+      await setOrganiser(params.eventId, userId, 'lead');
+    }
+  };
 
   return (
     <div className="xl:col-span-2 flex flex-col justify-between w-full rounded-2xl bg-[rgba(255,255,255,0.9)] dark:bg-[rgba(18,18,18,0.7)] border border-solid border-b-2 border-[rgba(1,1,1,0.1)] dark:border-[rgba(255,255,255,0.1)] border-b-[#1976d2] dark:border-b-[#90caf9]">
@@ -95,37 +111,35 @@ function EditDescription() {
           />
         </div>
 
-        {/* Worga */}
-        {category === 'weekend' && (
+        {/* Lead organiser, organisers are kept separately from the event */}
+        {params.eventId && (
           <div className="xl:col-span-2 grid">
             <FormControl fullWidth>
-              <InputLabel id="worga-select-label">
-                {text('Weekend Organiser', 'Worga')}
+              <InputLabel id="lead-organiser-select-label">
+                {text(category === 'weekend' ? 'Weekend Organiser' : 'Lead Organiser', category === 'weekend' ? 'Worga' : 'Hoofdorganisator')}
               </InputLabel>
-              <Controller
-                name="metadata.worga"
-                control={control}
-                render={({field}) => (
-                  <Select
-                    labelId="worga-select-label"
-                    value={field.value ?? ''}
-                    label={text('Weekend Organiser', 'Worga')}
-                    variant="outlined"
-                    onChange={field.onChange}
-                  >
-                    <MenuItem value={'nobody'}>
-                      {text('No one assigned', 'Niemand toegewezen')}
-                    </MenuItem>
-                    {eventRegistrations?.map((registration, index) => (
-                      <MenuItem key={index} value={registration.id}>
-                        {`${registration.firstName} ${registration.infix ?? ''} ${registration.lastName}`}
-                      </MenuItem>
-                    ))}
-                  </Select>
+              <Select
+                labelId="lead-organiser-select-label"
+                value={lead?.user.id ?? 'nobody'}
+                label={text(category === 'weekend' ? 'Weekend Organiser' : 'Lead Organiser', category === 'weekend' ? 'Worga' : 'Hoofdorganisator')}
+                variant="outlined"
+                onChange={(e) => changeLead(e.target.value)}
+              >
+                <MenuItem value={'nobody'}>
+                  {text('No one assigned', 'Niemand toegewezen')}
+                </MenuItem>
+                {lead && !eventRegistrations?.some((registration) => registration.id === lead.user.id) && (
+                  <MenuItem value={lead.user.id}>
+                    {`${lead.user.firstName} ${lead.user.infix ?? ''} ${lead.user.lastName}`}
+                  </MenuItem>
                 )}
-              />
+                {eventRegistrations?.filter((registration) => registration.id).map((registration, index) => (
+                  <MenuItem key={index} value={registration.id}>
+                    {`${registration.firstName} ${registration.infix ?? ''} ${registration.lastName}`}
+                  </MenuItem>
+                ))}
+              </Select>
             </FormControl>
-
           </div>
         )}
       </div>
@@ -31,7 +31,7 @@ export default function EditEvent() {
           nl: 'Nieuwe activiteit'
         },
         dates: [{start: now, end: now}],
-        isPublished: false,
+        status: 'draft',
         requiredMembership: ['member'],
         eventType: 'activity',
         questions: [],
@@ -55,19 +55,23 @@ export default function EditEvent() {
   }, [form, initialEvent]);
 
   const image = useWatch({control: form.control, name: 'image'});
-  const isPublished = useWatch({control: form.control, name: 'isPublished'});
+  const status = useWatch({control: form.control, name: 'status'});
 
   if (!initialEvent) {
     return <LoadingPage/>;
   }
 
   const handleSave = async (event: EventContent, isPublishedNext: boolean) => {
+    // Cancelled and archived events keep their status when edited
+    const statusNext = event.status === 'draft' || event.status === 'published'
+      ? (isPublishedNext ? 'published' : 'draft')
+      : event.status;
     if (eventId) {
-      if (await updateEvent(eventId, {...event, isPublished: isPublishedNext})) {
+      if (await updateEvent(eventId, {...event, status: statusNext})) {
         navigate(`/events/${eventId}`);
       }
     } else {
-      if (await createEvent({...event, isPublished: isPublishedNext})) {
+      if (await createEvent({...event, status: statusNext})) {
         navigate('/events');
       }
     }
@@ -85,7 +89,7 @@ export default function EditEvent() {
                 {text(`Back to Event${!eventId ? 's' : ''}`, `Terug naar Evenement${!eventId ? 'en' : ''}`)}
               </Button>
             </div>
-            {!isPublished && (
+            {status === 'draft' && (
               <Button variant="contained">
                 <b>{text('Draft', 'Concept')}</b>
               </Button>
@@ -6,18 +6,16 @@ import {useLanguage} from '../../providers/LanguageProvider.tsx';
 import {useCommitteeHook} from '../../hooks/useCommitteeHook.ts';
 import {useNavigate, useParams} from 'react-router-dom';
 import {useEventHook} from '../../hooks/useEventHook.ts';
-import {useUserHook} from '../../hooks/useUserHook.ts';
 
 export default function DescriptionCard() {
   const {text, language} = useLanguage();
   const {useCommittees} = useCommitteeHook();
   const params = useParams();
-  const {useEvent} = useEventHook();
+  const {useEvent, useEventOrganisers} = useEventHook();
   const currentEvent = useEvent(params.eventId)
   const committees = useCommittees()
   const navigate = useNavigate();
-  const {useUser} = useUserHook()
-  const worgaUser = useUser(currentEvent?.metadata?.worga);
+  const organisers = useEventOrganisers(params.eventId);
 
   if (!currentEvent) {
     return null;
@@ -92,18 +90,21 @@ export default function DescriptionCard() {
             </div>
           </div>
         )}
-        {currentEvent.eventType === 'weekend' && (
+        {(organisers?.length ?? 0) > 0 && (
           <div>
             <b className="text-[#1976d2] dark:text-[#90caf9]">
-              {text('Weekend Organiser', 'Worga')}
+              {text('Organisers', 'Organisatoren')}
             </b>
-            <div>
-              <Chip
-                label={currentEvent.metadata?.worga === 'nobody' ? text('No one assigned', 'Niemand toegewezen') : `${worgaUser?.firstName} ${worgaUser?.infix ?? ''} ${worgaUser?.lastName}`}
-                className="uppercase font-semibold"
-                onClick={() => navigate(`/user/${currentEvent?.metadata?.worga}`)}
-                size="small"
-              />
+            <div className="flex flex-wrap gap-1 mt-1">
+              {organisers?.map((organiser) => (
+                <Chip
+                  key={organiser.user.id}
+                  label={`${organiser.user.firstName} ${organiser.user.infix ?? ''} ${organiser.user.lastName}`}
+                  className="uppercase font-semibold"
+                  onClick={() => navigate(`/user/${organiser.user.id}`)}
+                  size="small"
+                />
+              ))}
             </div>
           </div>
         )}
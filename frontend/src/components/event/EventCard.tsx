@@ -76,7 +76,7 @@ export default function EventCard({event, agendaPage}: AgendaCardProps) {
           color="primary"
           sx={{fontSize: 16}}
         />
-        {agendaPage && !event.isPublished && (
+        {agendaPage && event.status === 'draft' && (
           <Chip
             label={text('Draft', 'Concept')}
             className="absolute uppercase font-semibold top-5 left-5"
@@ -13,6 +13,7 @@ import {useLanguage} from '../../providers/LanguageProvider.tsx';
 import moment from 'moment';
 import {FormEvent, useState} from 'react';
 import {NON_MEMBER_NAME_QUESTION_ID} from './registration.ts';
+import {useFileHook} from '../../hooks/useFileHook.ts';
 
 interface RegisterFormProps {
   registrationQuestions: Question[];
@@ -28,6 +29,7 @@ export default function RegisterForm({
   requireNonMemberName = false
 }: RegisterFormProps) {
   const {text, language} = useLanguage();
+  const {uploadAnswerFile} = useFileHook();
   const now = new Date()
   const [answers, setAnswers] = useState<Answer[]>(
     existingAnswers && existingAnswers.length > 0
@@ -49,11 +51,40 @@ export default function RegisterForm({
   const [nonMemberNameError, setNonMemberNameError] = useState<ErrorType>(false);
   moment.locale(language);
 
+  // checkbox answers are a JSON array of the chosen options
+  const checkedOptions = (answer?: string): string[] => {
+    try {
+      return answer ? JSON.parse(answer) : [];
+    } catch {
+      return [];
+    }
+  };
+
+  // mirrors the server, conditions refer to an earlier question
+  const isAsked = (question: Question): boolean => {
+    const condition = question.condition;
+    if (!condition) {
+      return true;
+    }
+    const parent = registrationQuestions.find((q) => q.id === condition.questionId);
+    if (!parent || !isAsked(parent)) {
+      return false;
+    }
+    const answer = answers.find((a) => a.questionId === condition.questionId)?.answer ?? '';
+    const options = parent.questionType.options ?? [];
+    if (options.length > 0) {
+      const option = options.find((o) => o.en === condition.answer || o.nl === condition.answer);
+      const chosen = parent.questionType.type === 'checkboxes' ? checkedOptions(answer) : [answer];
+      return !!option && chosen.some((c) => c === option.en || c === option.nl);
+    }
+    return answer === condition.answer;
+  };
+
   const validateInputs = () => {
     const newErrors: ErrorType[] = registrationQuestions.map((question, index) => {
       const answer = answers[index];
 
-      if (question.required && (!answer || answer.answer.trim() === '')) {
+      if (question.required && isAsked(question) && (!answer || answer.answer.trim() === '')) {
         return {
           en: 'This field is required',
           nl: 'Dit veld is verplicht'
@@ -117,6 +148,10 @@ export default function RegisterForm({
         const error = errors[index];
         const answer = answers[index];
 
+        if (!isAsked(question)) {
+          return null;
+        }
+
         switch (question.questionType.type) {
         case 'text':
           return (
@@ -217,6 +252,89 @@ export default function RegisterForm({
             </FormControl>
           );
 
+        case 'checkboxes':
+          return (
+            <FormControl key={question.id} fullWidth error={!!error}>
+              <div className="border border-[#c4c4c4] dark:border-[#4c4c4c] rounded-xl pl-3 py-1.5">
+                {label}
+                {(question.questionType.options ?? []).map((opt, i) => {
+                  const checked = checkedOptions(answer?.answer);
+                  return (
+                    <div key={i} className="flex items-center justify-between">
+                      {text(opt.en, opt.nl)}
+                      <Checkbox
+                        checked={checked.includes(opt.en) || checked.includes(opt.nl)}
+                        onChange={(e) => {
+                          const others = checked.filter((c) => c !== opt.en && c !== opt.nl);
+                          const updated = [...answers];
+                          updated[index].answer = JSON.stringify(e.target.checked ? [...others, opt[language]] : others);
+                          setAnswers(updated);
+                        }}
+                      />
+                    </div>
+                  );
+                })}
+              </div>
+              {error && (
+                <span className="text-red-500 text-xs ml-3">
+                  {text(error as Language)}
+                </span>
+              )}
+            </FormControl>
+          );
+
+        case 'range':
+          return (
+            <FormControl key={question.id} fullWidth>
+              <TextField
+                label={`${label} (${question.questionType.min} - ${question.questionType.max})`}
+                type="number"
+                value={answer?.answer}
+                slotProps={{htmlInput: {min: question.questionType.min, max: question.questionType.max}}}
+                onChange={(e) => {
+                  const updated = [...answers];
+                  updated[index].answer = e.target.value;
+                  setAnswers(updated);
+                }}
+                error={!!error}
+                helperText={error && text(error as Language)}
+                fullWidth
+              />
+            </FormControl>
+          );
+
+        case 'file':
+          return (
+            <FormControl key={question.id} fullWidth error={!!error}>
+              <div
+                className="flex items-center justify-between border border-[#c4c4c4] dark:border-[#4c4c4c] rounded-xl pl-3 py-1.5">
+                {label}
+                <Button component="label" variant="outlined" size="small">
+                  {answer?.answer ? text('Replace file', 'Bestand vervangen') : text('Upload file', 'Bestand uploaden')}
+                  <input
+                    type="file"
+                    hidden
+                    onChange={async (e) => {
+                      const file = e.target.files?.[0];
+                      if (!file) {
+                        return;
+                      }
+                      const [uploaded] = await uploadAnswerFile(file);
+                      const updated = [...answers];
+                      updated[index].answer = uploaded.id;
+                      setAnswers(updated);
+                    }}
+                  />
+                </Button>
+              </div>
+              {error && (
+                <span className="text-red-500 text-xs ml-3">
+                  {text(error as Language)}
+                </span>
+              )}
+            </FormControl>
+          );
+
         default:
           return null;
         }
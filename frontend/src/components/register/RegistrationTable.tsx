@@ -1,13 +1,15 @@
-import {Table, TableBody, TableCell, TableRow} from '@mui/material';
+import {Button, Table, TableBody, TableCell, TableRow} from '@mui/material';
+import DownloadIcon from '@mui/icons-material/Download';
 import {Registration} from '../../types.ts';
 import {useLanguage} from '../../providers/LanguageProvider.tsx';
 import RegistrationRow from './RegistrationRow.tsx';
-import {inCommittee, isAdminOrBoard, isChair, isWorga} from '../../util.ts';
+import {inCommittee, isAdminOrBoard, isChair, isLeadOrganiser, isOrganiser} from '../../util.ts';
 import {useEventRegistrationHook} from '../../hooks/useEventRegistrationHook.ts';
 import {useEventHook} from '../../hooks/useEventHook.ts';
 import {useUserHook} from '../../hooks/useUserHook.ts';
 import {useParams} from 'react-router-dom';
 import {useAuth} from '../../providers/AuthProvider.tsx';
+import {registrationsToCsv} from './registration.ts';
 
 interface RegistrationTableProps {
   onEditClick: (registration: Registration) => void;
@@ -21,26 +23,44 @@ export default function RegistrationTable({onEditClick}: RegistrationTableProps)
   const {useEventRegistrations} = useEventRegistrationHook();
   const params = useParams();
   const eventRegistrations = useEventRegistrations(params.eventId)
-  const {useEvent} = useEventHook();
+  const {useEvent, useEventOrganisers} = useEventHook();
   const currentEvent = useEvent(params.eventId);
+  const organisers = useEventOrganisers(params.eventId);
 
   if (!currentEvent) {
     return null;
   }
 
+  const canViewDetailedRegistrations = !!user && (isAdminOrBoard(user.roles) || isOrganiser(organisers ?? [], user) || inCommittee(myCommittees ?? [], currentEvent.createdBy));
+
+  const handleExport = () => {
+    const csv = registrationsToCsv(eventRegistrations ?? [], currentEvent.questions, text);
+    const url = URL.createObjectURL(new Blob([csv], {type: 'text/csv;charset=utf-8'}));
+    const link = document.createElement('a');
+    link.href = url;
+    link.download = `${text(currentEvent.name)}.csv`;
+    link.click();
+    URL.revokeObjectURL(url);
+  };
+
   return (
     <div className="overflow-x-auto">
+      {canViewDetailedRegistrations && (
+        <Button className="mt-2" startIcon={<DownloadIcon/>} onClick={handleExport}>
+          {text('Export registrations', 'Inschrijvingen exporteren')}
+        </Button>
+      )}
       <div className="min-w-max">
         <Table>
           <TableBody>
-            {user && (isAdminOrBoard(user.roles) || isWorga(currentEvent, user) || inCommittee(myCommittees ?? [], currentEvent.createdBy)) && (
+            {user && canViewDetailedRegistrations && (
               <TableRow>
                 <TableCell><b>{text('Name', 'Naam')}</b></TableCell>
                 {currentEvent.questions.map((q) => (
                   <TableCell
                     key={q.id}><b>{`${text(q.question)} ${q.required ? '*' : ''}`}</b></TableCell>
                 ))}
-                {(isAdminOrBoard(user.roles) || isChair(myCommittees ?? [], currentEvent.createdBy)) && (
+                {(isAdminOrBoard(user.roles) || isChair(myCommittees ?? [], currentEvent.createdBy) || isLeadOrganiser(organisers ?? [], user)) && (
                   <>
                     <TableCell><b>{text('Attended', 'Aanwezig')}</b>
                     </TableCell><TableCell><b>{text('Actions', 'Acties')}</b></TableCell>
@@ -1,20 +1,26 @@
 export const NON_MEMBER_NAME_QUESTION_ID = '8d3d4e48-4e8f-4e15-a7d9-6ff5e4c8e8ad';
 
-import {Language, Registration} from '../../types.ts';
+import {Guest, Language, Question, Registration} from '../../types.ts';
 
-export function getRegistrationDisplayName(registration: Registration): Language {
-  const memberName = [registration.firstName, registration.infix, registration.lastName]
+function getMemberName(registration: Registration): string {
+  return [registration.firstName, registration.infix, registration.lastName]
     .filter((part) => !!part && part.trim().length > 0)
     .join(' ')
     .trim();
+}
+
+export function getRegistrationDisplayName(registration: Registration): Language {
+  const memberName = getMemberName(registration);
 
   if (memberName.length > 0) {
     return {en: memberName, nl: memberName};
   }
 
-  const nonMemberName = registration.answers
+  // Anonymous registrations list the non-member as the first guest,
+  // older registrations answered a fixed question instead
+  const nonMemberName = (registration.guests?.[0]?.name ?? registration.answers
     .find((answer) => answer.questionId === NON_MEMBER_NAME_QUESTION_ID)
-    ?.answer
+    ?.answer)
     ?.trim();
 
   return nonMemberName && nonMemberName.length > 0 ? {
@@ -22,3 +28,62 @@ export function getRegistrationDisplayName(registration: Registration): Language
     nl: `${nonMemberName} (Niet Lid)`
   } : {en: 'Unknown', nl: 'Onbekend'};
 }
+
+// The guests brought along, without the non-member of an anonymous registration
+export function getRegistrationGuests(registration: Registration): Guest[] {
+  return getMemberName(registration).length > 0 ? registration.guests ?? [] : registration.guests?.slice(1) ?? [];
+}
+
+function csvField(value: string | number | undefined): string {
+  const field = value === undefined ? '' : String(value);
+  return /[",\n\r]/.test(field) ? `"${field.replace(/"/g, '""')}"` : field;
+}
+
+// One line per registration followed by a line per guest
+export function registrationsToCsv(
+  registrations: Registration[],
+  questions: Question[],
+  text: (en: string | Language, nl?: string) => string
+): string {
+  const header = [
+    text('Name', 'Naam'),
+    text('Guest of', 'Gast van'),
+    text('ICE contact name', 'Naam noodcontact'),
+    text('ICE contact email', 'E-mail noodcontact'),
+    text('ICE contact phone', 'Telefoon noodcontact'),
+    text('Waiting list', 'Wachtlijst'),
+    text('Attended', 'Aanwezig'),
+    ...questions.map((q) => text(q.question)),
+  ];
+
+  const lines = registrations.flatMap((registration) => {
+    const name = text(getRegistrationDisplayName(registration));
+    const waitingList = registration.waitingListPosition;
+    const anonymous = getMemberName(registration).length === 0 ? registration.guests?.[0] : undefined;
+    const answers = questions.map((q) => registration.answers.find((a) => a.questionId === q.id)?.answer);
+
+    return [
+      [
+        name,
+        undefined,
+        anonymous?.iceContactName,
+        anonymous?.iceContactEmail,
+        anonymous?.iceContactPhone,
+        waitingList,
+        registration.attended ? 'x' : undefined,
+        ...answers,
+      ],
+      ...getRegistrationGuests(registration).map((guest) => [
+        guest.name,
+        name,
+        guest.iceContactName,
+        guest.iceContactEmail,
+        guest.iceContactPhone,
+        waitingList,
+        registration.attended ? 'x' : undefined,
+      ]),
+    ];
+  });
+
+  return [header, ...lines].map((line) => line.map(csvField).join(',')).join('\r\n');
+}
@@ -1,4 +1,4 @@
-import {Event, EventContent} from '../types.ts';
+import {Event, EventContent, EventOrganiser, OrganiserRole} from '../types.ts';
 import {apiFetch} from '../api.ts';
 import {enqueueSnackbar} from 'notistack';
 import {useLanguage} from '../providers/LanguageProvider.tsx';
@@ -14,10 +14,11 @@ export function useEventHook() {
   const {text} = useLanguage();
   const queryClient = useQueryClient();
 
-  function useEvents() {
+  function useEvents(includePast: boolean = false) {
+    const query = includePast ? '' : '?period=upcoming';
     const {data} = useQuery<Event[]>({
-      queryKey: queryKeys.events.all(),
-      queryFn: () => apiFetch<Event[]>('/event'),
+      queryKey: queryKeys.events.list(query),
+      queryFn: () => apiFetch<Event[]>(`/event${query}`),
       staleTime: 60_000,
     });
     return data;
@@ -34,6 +35,56 @@ export function useEventHook() {
     return data;
   }
 
+  function useEventOrganisers(eventId?: string) {
+    const {data} = useQuery<EventOrganiser[]>({
+      queryKey: queryKeys.events.organisers(eventId),
+      enabled: !!eventId,
+      queryFn: () =>
+        apiFetch<EventOrganiser[]>(`/event/${eventId}/organiser`),
+      staleTime: 60_000,
+    });
+    return data;
+  }
+
+  const setOrganiserMutation = useMutation<
+    EventOrganiser[],
+    ApiError,
+    {eventId: string; userId: string; role: OrganiserRole}
+  >({
+    mutationFn: async ({eventId, userId, role}) => {
+      return await apiFetch<EventOrganiser[]>(`/event/${eventId}/organiser/${userId}`, {
+        method: 'PUT',
+        body: JSON.stringify({role}),
+      });
+    },
+    onSuccess: (organisers, {eventId}) => {
+      queryClient.setQueryData(queryKeys.events.organisers(eventId), organisers);
+      enqueueSnackbar(text('Organiser updated', 'Organisator bijgewerkt'), {variant: 'success'});
+    },
+    onError: (error: ApiError) => enqueueSnackbar(`${error.message}: ${error.reference}`, {variant: 'error'})
+  });
+  const setOrganiser = (eventId: string, userId: string, role: OrganiserRole) =>
+    setOrganiserMutation.mutateAsync({eventId, userId, role});
+
+  const removeOrganiserMutation = useMutation<
+    void,
+    ApiError,
+    {eventId: string; userId: string}
+  >({
+    mutationFn: async ({eventId, userId}) => {
+      await apiFetch<void>(`/event/${eventId}/organiser/${userId}`, {
+        method: 'DELETE',
+      });
+    },
+    onSuccess: (_, {eventId}) => {
+      queryClient.invalidateQueries({queryKey: queryKeys.events.organisers(eventId)});
+      enqueueSnackbar(text('Organiser removed', 'Organisator verwijderd'), {variant: 'success'});
+    },
+    onError: (error: ApiError) => enqueueSnackbar(`${error.message}: ${error.reference}`, {variant: 'error'})
+  });
+  const removeOrganiser = (eventId: string, userId: string) =>
+    removeOrganiserMutation.mutateAsync({eventId, userId});
+
   const createEventMutation = useMutation<
     Event,
     ApiError,
@@ -103,6 +154,9 @@ export function useEventHook() {
   return {
     useEvent,
     useEvents,
+    useEventOrganisers,
+    setOrganiser,
+    removeOrganiser,
     createEvent,
     updateEvent,
     deleteEvent,
@@ -1,4 +1,4 @@
-import {apiFetch} from '../api.ts';
+import {apiFetch, apiResponse} from '../api.ts';
 import {Answer, Registration} from '../types.ts';
 import {enqueueSnackbar} from 'notistack';
 import {useLanguage} from '../providers/LanguageProvider.tsx';
@@ -120,20 +120,23 @@ export function useEventRegistrationHook() {
       waitingListPosition,
     });
 
+  // Resolves to true when the cancellation deadline has passed,
+  // the organisers then have to approve the cancellation
   const deleteRegistrationMutation = useMutation<
-    void,
+    boolean,
     ApiError,
     { eventId: string; userId?: string; registrationId: string }
   >({
     mutationFn: async ({ eventId, registrationId }) => {
-      await apiFetch<void>(
+      const response = await apiResponse(
         `/event/${eventId}/registration/${registrationId}`,
         {
           method: 'DELETE',
         }
       );
+      return response.status === 202;
     },
-    onSuccess: (_, { eventId, userId }) => {
+    onSuccess: (cancellationRequested, { eventId, userId }) => {
       queryClient.invalidateQueries({queryKey: queryKeys.events.registrations(eventId)});
       queryClient.invalidateQueries({queryKey: queryKeys.events.detail(eventId)});
       if (userId) {
@@ -141,7 +144,17 @@ export function useEventRegistrationHook() {
       } else if (user?.id) {
         queryClient.invalidateQueries({queryKey: queryKeys.users.registrations(user.id)});
       }
-      enqueueSnackbar(text('Deregistered', 'Uitgeschreven'), {variant: 'success'});
+      if (cancellationRequested) {
+        enqueueSnackbar(
+          text(
+            'The cancellation deadline has passed, the organisers will review your cancellation',
+            'De annuleringstermijn is verstreken, de organisatie beoordeelt je afmelding'
+          ),
+          {variant: 'info'}
+        );
+      } else {
+        enqueueSnackbar(text('Deregistered', 'Uitgeschreven'), {variant: 'success'});
+      }
     },
     onError: (error: ApiError) => enqueueSnackbar(`${error.message}: ${error.reference}`, {variant: 'error'})
   });
@@ -13,15 +13,17 @@ export function useFileHook() {
     {
       file: File;
       isPublic?: boolean;
+      answer?: boolean;
     }
   >({
-    mutationFn: async ({file, isPublic = true}) => {
+    mutationFn: async ({file, isPublic = true, answer = false}) => {
       const formData = new FormData();
 
       formData.append('isPublic', String(isPublic));
       formData.append(file.name, file);
 
-      return await apiFetch<FileMetadata[]>('/file', {
+      // Registration answers have their own endpoint, open to every logged-in user
+      return await apiFetch<FileMetadata[]>(answer ? '/file/answer' : '/file', {
         method: 'POST',
         body: formData,
         headers: {},
@@ -48,6 +50,9 @@ export function useFileHook() {
     isPublic = false
   ) => uploadFileMutation.mutateAsync({file, isPublic});
 
+  const uploadAnswerFile = (file: File) =>
+    uploadFileMutation.mutateAsync({file, isPublic: false, answer: true});
+
   const uploadFilesMutation = useMutation<
     FileMetadata[],
     ApiError,
@@ -93,6 +98,7 @@ export function useFileHook() {
 
   return {
     uploadFile,
+    uploadAnswerFile,
     uploadFiles,
     uploading: uploadFileMutation.isPending || uploadFilesMutation.isPending,
   };
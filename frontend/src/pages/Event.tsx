@@ -52,7 +52,7 @@ export default function Event() {
                 {text('Back to Events', 'Terug naar Evenementen')}
               </Button>
             </div>
-            {!currentEvent?.isPublished && (
+            {currentEvent?.status === 'draft' && (
               <Button variant="contained">
                 <b>{text('Draft', 'Concept')}</b>
               </Button>
@@ -14,12 +14,12 @@ import LoadingPage from '../components/loading/LoadingPage.tsx';
 export default function Events() {
   const {text} = useLanguage();
   const {useEvents} = useEventHook();
-  const events = useEvents();
   const [category, setCategory] = useState<EventType | 'all'>(
     'all'
   );
   const [type, setType] = useState<WeekendType | 'all'>('all');
   const [filterPastEvents, setFilterPastEvents] = useState<boolean>(false);
+  const events = useEvents(filterPastEvents);
 
   const now = new Date();
 
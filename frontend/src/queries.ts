@@ -36,11 +36,15 @@ export const queryKeys = {
 
   events: {
     all: () => ['event'] as const,
+    list: (query: string) => ['event', 'list', query] as const,
     detail: (id?: string) => ['event', id] as const,
 
     registrations: (eventId?: string) =>
       ['event', eventId, 'registration'] as const,
 
+    organisers: (eventId?: string) =>
+      ['event', eventId, 'organiser'] as const,
+
     registrationDetail: (
       eventId?: string,
       registrationId?: string
@@ -9,6 +9,8 @@ export type ExperienceType = 'sp' | 'mp';
 
 export type EventType = 'activity' | 'course' | 'training' | 'weekend';
 
+export type EventStatus = 'draft' | 'published' | 'cancelled' | 'archived';
+
 export type ErrorType = Language | boolean;
 
 export type Membership =
@@ -21,11 +23,60 @@ export type Status = 'pending' | 'accepted' | 'rejected';
 
 export type CommitteeRoleType = 'chair' | 'member';
 
-export type QuestionTypeType = 'text' | 'multipleChoice' | 'number' | 'boolean' | 'date';
+export type QuestionTypeType = 'text' | 'multipleChoice' | 'number' | 'boolean' | 'date' | 'checkboxes' | 'range' | 'file';
+
+export interface QuestionOption extends Language {
+  capacity?: number;
+}
 
 export interface QuestionType {
   type: QuestionTypeType;
-  options?: Language[];
+  options?: QuestionOption[];
+  // range only
+  min?: number;
+  max?: number;
+}
+
+export interface QuestionCondition {
+  questionId: string;
+  answer: string;
+}
+
+export type AnswerSummary =
+  | {type: 'options'; counts: number[]}
+  | {type: 'boolean'; yes: number; no: number}
+  | {type: 'number'; min?: number; max?: number; average?: number}
+  | {type: 'list'; answers: string[]};
+
+export type QuestionSummary = AnswerSummary & {
+  questionId: string;
+  answered: number;
+};
+
+export interface FeedbackFormContent {
+  questions: Question[];
+  anonymous: boolean;
+  sharedWithBoard: boolean;
+}
+
+export interface FeedbackForm extends FeedbackFormContent {
+  eventId: string;
+  sent?: string;
+  responded: boolean;
+  created: string;
+  updated: string;
+}
+
+export interface FeedbackResponse extends Partial<BasicUser> {
+  id: string;
+  answers: Answer[];
+}
+
+export interface FeedbackResults {
+  attendees: number;
+  respondents: number;
+  questions: QuestionSummary[];
+  responses: FeedbackResponse[];
 }
 
 export type LanguageEnum = 'nl' | 'en';
@@ -68,10 +119,178 @@ export interface Registration extends Partial<BasicUser> {
   attended?: boolean;
   waitingListPosition?: number;
   answers: Array<Answer>;
+  guests: Guest[];
+  places: number;
+  amountDue: number;
+  paymentStatus: PaymentStatus;
+  cancellationRequested?: string;
+  checkedIn?: string;
+  car?: Car;
+  driver?: string;
   created: string;
   updated: string;
 }
 
+export interface Guest {
+  name: string;
+  iceContactName?: string;
+  iceContactEmail?: string;
+  iceContactPhone?: string;
+}
+
+export interface Car {
+  seats: number;
+  departure: string;
+}
+
+export interface Passenger {
+  registrationId: string;
+  user?: BasicUser;
+  guests: string[];
+  places: number;
+}
+
+export interface CarpoolCar extends Car {
+  driver: Passenger;
+  passengers: Passenger[];
+  freeSeats: number;
+}
+
+export interface Carpool {
+  cars: CarpoolCar[];
+  unassigned: Passenger[];
+  seatsOffered: number;
+  seatsNeeded: number;
+  shortfall: number;
+}
+
+export interface CarAssignment {
+  registrationId: string;
+  driver?: string;
+}
+
+export type PaymentStatus = 'unpaid' | 'paid' | 'refunded';
+
+export interface PriceTier {
+  membership: Membership;
+  amount: number;
+}
+
+export interface PricedExtra {
+  questionId: string;
+  option: number;
+  amount: number;
+}
+
+export interface Pricing {
+  tiers: PriceTier[];
+  extras: PricedExtra[];
+}
+
+export interface Payment {
+  registrationId: string;
+  eventId: string;
+  eventName: Language;
+  user?: BasicUser;
+  amountDue: number;
+  status: PaymentStatus;
+  updated?: string;
+}
+
+export interface PaymentOverview {
+  totalDue: number;
+  paid: number;
+  refunded: number;
+  outstanding: number;
+  payments: Payment[];
+}
+
+export interface Checkout {
+  checkoutUrl: string;
+}
+
+export interface CheckInToken {
+  token: string;
+}
+
+export interface Scan {
+  token: string;
+  scanned?: string;
+}
+
+export type ScanStatus = 'checkedIn' | 'alreadyCheckedIn' | 'invalid' | 'rejected';
+
+export interface ScanResult {
+  token: string;
+  registrationId?: string;
+  status: ScanStatus;
+}
+
+export interface Attendance {
+  registrationId: string;
+  attended?: boolean;
+}
+
+export interface AttendanceSummary {
+  participants: number;
+  checkedIn: number;
+  attended: number;
+  absent: number;
+  unknown: number;
+}
+
+export interface SepaMandate {
+  userId: string;
+  accountHolder: string;
+  iban: string;
+  mandateId: string;
+  signed: string;
+  created: string;
+  updated: string;
+}
+
+export interface MembershipFee {
+  membership: Membership;
+  amount: number;
+}
+
+export type CollectionStatus = 'pending' | 'collected' | 'failed';
+
+export interface FeeCollection {
+  id: string;
+  batchId: string;
+  user?: BasicUser;
+  year: number;
+  amount: number;
+  accountHolder: string;
+  iban: string;
+  mandateId: string;
+  signed: string;
+  sequenceType: 'first' | 'recurring';
+  status: CollectionStatus;
+  updated: string;
+}
+
+export interface CollectionBatch {
+  id: string;
+  year: number;
+  collectionDate: string;
+  created: string;
+  collections: FeeCollection[];
+}
+
+export interface LateCancellation {
+  id: string;
+  eventId: string;
+  registrationId: string;
+  user?: BasicUser;
+  amountDue: number;
+  paymentStatus: PaymentStatus;
+  cancellationRequested: string;
+  approved: string;
+  approvedBy?: string;
+}
+
 export interface Answer {
   questionId: string;
   answer: string;
@@ -82,13 +301,28 @@ export interface Question {
   question: Language;
   questionType: QuestionType;
   required: boolean;
+  condition?: QuestionCondition;
 }
 
+export type Difficulty = 'easy' | 'moderate' | 'hard';
+
+export type CourseLevel = 'beginner' | 'intermediate' | 'advanced';
+
 export interface Metadata {
   experience?: ExperienceType[];
   type?: WeekendType[];
   gear?: Language;
-  worga?: string;
+  // weekends only
+  difficulty?: Difficulty;
+  // courses only
+  level?: CourseLevel;
+}
+
+export type OrganiserRole = 'lead' | 'coOrganiser' | 'firstAid' | 'treasurer';
+
+export interface EventOrganiser {
+  user: BasicUser;
+  role: OrganiserRole;
 }
 
 export interface Event extends Omit<EventContent, 'location'> {
@@ -97,6 +331,10 @@ export interface Event extends Omit<EventContent, 'location'> {
   updated: string;
   registrationCount: number;
   waitingListCount: number;
+  cancellationReason?: string;
+  seriesId?: string;
+  registrationWindows: RegistrationWindow[];
+  lotteryDrawn?: string;
   location: Location;
 }
 
@@ -107,16 +345,34 @@ export function toEventContent(event: Event): EventContent {
   return eventContent;
 }
 
+export interface MembershipRule {
+  membership: Membership;
+  quota?: number;
+  opensAfterHours: number;
+}
+
+export interface RegistrationWindow {
+  membership: Membership;
+  opens: string;
+}
+
 export interface EventContent {
   name: Language;
   image?: string;
   description?: Language;
   dates: DateType[];
   registrationPeriod?: DateType;
+  cancellationDeadline?: string;
   registrationMax?: number;
   waitingListMax?: number;
-  isPublished: boolean;
+  maxGuests?: number;
+  status: EventStatus;
+  publishAt?: string;
   requiredMembership: Membership[];
+  requiredCertifications?: string[];
+  membershipRules?: MembershipRule[];
+  pricing?: Pricing;
+  lottery?: boolean;
   eventType: EventType;
   questions: Question[];
   metadata?: Metadata;
@@ -175,6 +431,7 @@ export interface FileMetadata {
   mimeType?: string;
   size: number;
   isPublic: boolean;
+  isAnswer: boolean;
   createdBy: string;
   created: string;
 }
@@ -399,3 +656,66 @@ export const rentOptions: rentOption[] = [
     remark: {en: '(for max. 6 months)', nl: '(voor max. 6 maanden)'}
   }
 ];
+
+export interface HistoryEntry {
+  eventId: string;
+  name: Language;
+  location: Language;
+  start: string;
+  end: string;
+}
+
+export interface History {
+  activity: HistoryEntry[];
+  course: HistoryEntry[];
+  weekend: HistoryEntry[];
+  training: HistoryEntry[];
+}
+
+export type LogbookVisibility = 'private' | 'members' | 'public';
+
+export interface LogbookEntryContent {
+  date: string;
+  area: string;
+  route: string;
+  grade?: string;
+  description: string;
+  partners: string[];
+  visibility: LogbookVisibility;
+}
+
+export interface LogbookEntry extends Omit<LogbookEntryContent, 'partners'> {
+  id: string;
+  userId: string;
+  partners: BasicUser[];
+  created: string;
+  updated: string;
+}
+
+export interface CertificationContent {
+  name: Language;
+  description: Language;
+  validityMonths?: number;
+}
+
+export interface Certification extends CertificationContent {
+  id: string;
+  created: string;
+  updated: string;
+}
+
+export interface UserCertification {
+  id: string;
+  certificationId: string;
+  name: Language;
+  user: BasicUser;
+  eventId?: string;
+  issued: string;
+  expires?: string;
+  issuedBy?: string;
+}
+
+export interface IssueCertification {
+  certificationId: string;
+  userIds?: string[];
+}
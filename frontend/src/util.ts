@@ -1,4 +1,4 @@
-import {Language, labelOptions, User, Event, UserCommittee, CommitteeUser, RoleType} from './types.ts';
+import {Language, labelOptions, User, EventOrganiser, UserCommittee, CommitteeUser, RoleType} from './types.ts';
 
 export function getLabel(id: string): Language {
   const categoryOption = labelOptions.find((option) => option.id === id);
@@ -40,8 +40,12 @@ export function isAdminOrBoard(roles: RoleType[]): boolean {
   return roles.some(role => allowedRoles.includes(role));
 }
 
-export function isWorga(event: Event, user: User): boolean {
-  return event.metadata?.worga === user.id
+export function isOrganiser(organisers: EventOrganiser[], user: User): boolean {
+  return organisers.some(organiser => organiser.user.id === user.id)
+}
+
+export function isLeadOrganiser(organisers: EventOrganiser[], user: User): boolean {
+  return organisers.some(organiser => organiser.user.id === user.id && organiser.role === 'lead')
 }
 
 export function inCommittee(committees: UserCommittee[], committeeId: string): boolean {